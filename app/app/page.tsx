@@ -14,7 +14,6 @@ import { ResizablePanel, ResizablePanelGroup } from "@/components/ui/resizable";
 import { OutputViewer } from "@/components/OutputViewer";
 import { CallStack } from "@/components/CallStack";
 import { FileEntry, InMemoryFileSystem } from "@/lib/fileSystem";
-import { open } from "@tauri-apps/plugin-dialog";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 
@@ -28,6 +27,25 @@ export interface IBreakpoint {
 
 const initialFiles: FileEntry[] = [];
 
+// Launches a session, asking the user before running a binary the backend flags (outside the
+// workspace, a system binary or setuid) and retrying with `confirmed` if they agree.
+async function launchWithConfirmation(args: Record<string, unknown>) {
+  try {
+    await invoke("launch_debug_session", args);
+  } catch (error) {
+    const message = String(error);
+    const prefix = "Launch needs confirmation: ";
+    if (!message.startsWith(prefix)) throw error;
+
+    const concerns: Array<{ message: string }> = JSON.parse(
+      message.slice(prefix.length),
+    );
+    const reasons = concerns.map((c) => `- ${c.message}`).join("\n");
+    if (!window.confirm(`Launch anyway?\n\n${reasons}`)) throw error;
+    await invoke("launch_debug_session", { ...args, confirmed: true });
+  }
+}
+
 export default function Home() {
   const [fs, setFs] = useState(() => new InMemoryFileSystem(initialFiles));
   const [files, setFiles] = useState<FileEntry[]>(initialFiles);
@@ -244,13 +262,11 @@ export default function Home() {
 
   const handleOpenWorkspace = async (providedPath?: string) => {
     try {
-      // If a path is provided, use it directly; otherwise open a directory picker
-      const selected =
-        providedPath ||
-        (await open({
-          directory: true,
-          multiple: false,
-        }));
+      // The backend asks the user for the root: a directory picker, or a confirmation of the
+      // provided path. It confines file access to the root afterwards.
+      const selected = await invoke<string | null>("open_workspace", {
+        path: providedPath ?? null,
+      });
 
       if (selected) {
         console.log("Selected workspace path:", selected);
@@ -447,6 +463,30 @@ export default function Home() {
     };
   }, [files, handleFileSelect, selectedFile?.name]);
 
+  // Jump to the first compile error when a cargo pre-build fails
+  useEffect(() => {
+    const unlistenDiagnostics = listen("build-diagnostics", (event) => {
+      const { diagnostics } = event.payload as {
+        diagnostics: { file?: string; line?: number }[];
+      };
+      const first = diagnostics.find((d) => d.file && d.line);
+      if (!first?.file || !first.line) return;
+      const fileName = first.file.split("/").pop();
+      const fileEntry = files.find(
+        (f) => f.path === first.file || f.name === fileName,
+      );
+      if (fileEntry) {
+        handleFileSelect(fileEntry).then(() => {
+          setJumpRequest({ file: fileEntry.path, line: first.line! });
+        });
+      }
+    });
+
+    return () => {
+      unlistenDiagnostics.then((fn) => fn());
+    };
+  }, [files, handleFileSelect]);
+
   const fsRef = useRef(fs);
 
   // Keep the ref in sync with the state
@@ -658,6 +698,21 @@ export default function Home() {
     }
   };
 
+  // Breakpoints keyed by full path, the form launch_debug_session takes them in.
+  const breakpointsByPath = async (breakpoints: IBreakpoint[]) => {
+    const byPath: Record<string, Array<{ line: number }>> = {};
+    for (const bp of breakpoints) {
+      const fileEntry = bp.file ? await fs.getFile(bp.file) : undefined;
+      if (!fileEntry) continue;
+      const fullFilePath = fs.getFullPath(fileEntry.path);
+      byPath[fullFilePath] = [
+        ...(byPath[fullFilePath] || []),
+        { line: bp.line },
+      ];
+    }
+    return byPath;
+  };
+
   const handleDebugSessionStart = async (force: boolean = false) => {
     if (!force && isDebugSessionActive && debugStatus !== "terminated") {
       addLog("Debug session is already launching or active, skipping");
@@ -682,14 +737,7 @@ export default function Home() {
       try {
         addLog(`Using binary path: ${rustBinaryPath}`);
 
-        await invoke("launch_debug_session", {
-          scriptPath: rustBinaryPath,
-          debugEngine,
-        });
-
-        addLog(`${debugEngine} debug session launched successfully`);
-
-        // Merge queued and active breakpoints and set them for the new session.
+        // Merge queued and active breakpoints; the backend sets them before the program runs.
         const allBreakpoints = mergeBreakpoints(
           queuedBreakpointsRef.current,
           activeBreakpointsRef.current,
@@ -698,6 +746,14 @@ export default function Home() {
         queuedBreakpointsRef.current = [];
         addLog(`Merged breakpoints: ${JSON.stringify(allBreakpoints)}`);
 
+        await launchWithConfirmation({
+          scriptPath: rustBinaryPath,
+          debugEngine,
+          breakpoints: await breakpointsByPath(allBreakpoints),
+        });
+
+        addLog(`${debugEngine} debug session launched successfully`);
+
         const uniqueFiles = Array.from(
           new Set(
             allBreakpoints.map((bp) => bp.file).filter((f): f is string => !!f),
@@ -758,18 +814,6 @@ export default function Home() {
             });
           }
         }
-
-        // Call configuration_done after setting all breakpoints
-        await invoke("configuration_done")
-          .then((response) => {
-            addLog("configurationDone: " + response);
-          })
-          .catch((error) => {
-            addLog(
-              "Failed configuration_done: " +
-                (error instanceof Error ? error.message : error),
-            );
-          });
       } catch (error) {
         addLog(
           `Failed launching debug session: ${
@@ -796,14 +840,7 @@ export default function Home() {
       const scriptPath = fs.getFullPath(selectedFile.path);
       addLog(`Using path: ${scriptPath}`);
 
-      await invoke("launch_debug_session", {
-        scriptPath,
-        debugEngine,
-      });
-
-      addLog(`${debugEngine} debug session launched successfully`);
-
-      // Merge queued and active breakpoints and set them for the new session.
+      // Merge queued and active breakpoints; the backend sets them before the program runs.
       const allBreakpoints = mergeBreakpoints(
         queuedBreakpointsRef.current,
         activeBreakpointsRef.current,
@@ -813,6 +850,14 @@ export default function Home() {
       addLog(`Merged breakpoints: ${JSON.stringify(allBreakpoints)}`);
       console.log("Merged breakpoints", allBreakpoints);
 
+      await launchWithConfirmation({
+        scriptPath,
+        debugEngine,
+        breakpoints: await breakpointsByPath(allBreakpoints),
+      });
+
+      addLog(`${debugEngine} debug session launched successfully`);
+
       const uniqueFiles = Array.from(
         new Set(
           allBreakpoints.map((bp) => bp.file).filter((f): f is string => !!f),
@@ -895,18 +940,6 @@ export default function Home() {
         }
       }
 
-      // Only now, after setting all breakpoints, call configuration_done
-      await invoke("configuration_done")
-        .then((response) => {
-          addLog("configurationDone: " + response);
-        })
-        .catch((error) => {
-          addLog(
-            "Failed configuration_done: " +
-              (error instanceof Error ? error.message : error),
-          );
-        });
-
       // The debug-status listener is now set up early using useEffect.
     } catch (error) {
       addLog(
@@ -180,7 +180,7 @@ export function DebugToolbar({
     markManual();
     try {
       addLog("Restarting debug session...");
-      await invoke("terminate_program");
+      await invoke("stop_session", { mode: "stop" });
       onDebugSessionStart(true);
       addLog("Debug session restarted successfully.");
     } catch (err: unknown) {
@@ -193,7 +193,7 @@ export function DebugToolbar({
   async function handleTerminate() {
     markManual();
     try {
-      await invoke("terminate_program");
+      await invoke("stop_session", { mode: "stop" });
       addLog("Terminating debug session");
     } catch (err: unknown) {
       const errMsg = err instanceof Error ? err.message : String(err);
@@ -3,24 +3,61 @@
 import { useEffect, useState } from "react";
 import { listen } from "@tauri-apps/api/event";
 
+// A line of program output as captured by the backend. `seq` orders lines across stdout and
+// stderr, which are read separately and can arrive out of order.
+interface ConsoleLine {
+  session: string;
+  seq: number;
+  timestamp: number;
+  stream: "stdout" | "stderr";
+  text: string;
+}
+
+interface OutputEntry {
+  text: string;
+  console?: ConsoleLine;
+}
+
+// Inserts a console line after any earlier-captured lines of its session that arrived late.
+function insertConsoleLine(
+  entries: OutputEntry[],
+  line: ConsoleLine,
+): OutputEntry[] {
+  const entry = {
+    text: line.stream === "stderr" ? `[ERROR] ${line.text}` : line.text,
+    console: line,
+  };
+  let index = entries.length;
+  while (index > 0) {
+    const previous = entries[index - 1].console;
+    if (!previous || previous.session !== line.session) break;
+    if (previous.seq < line.seq) break;
+    index--;
+  }
+  return [...entries.slice(0, index), entry, ...entries.slice(index)];
+}
+
 export function OutputViewer() {
-  const [output, setOutput] = useState<string[]>([]);
+  const [output, setOutput] = useState<OutputEntry[]>([]);
 
   useEffect(() => {
-    // Listen for program output
-    const unlistenOutput = listen("program-output", (event) => {
-      setOutput((prev) => [...prev, event.payload as string]);
+    // Listen for program output (stdout and stderr)
+    const unlistenConsole = listen("console-line", (event) => {
+      setOutput((prev) =>
+        insertConsoleLine(prev, event.payload as ConsoleLine),
+      );
     });
 
-    // Listen for program errors
-    const unlistenError = listen("program-error", (event) => {
-      setOutput((prev) => [...prev, `[ERROR] ${event.payload as string}`]);
+    // Listen for pre-launch task output (cargo build, npm run build, ...)
+    const unlistenTask = listen("task-output", (event) => {
+      const { task, line } = event.payload as { task: string; line: string };
+      setOutput((prev) => [...prev, { text: `[${task}] ${line}` }]);
     });
 
     return () => {
       // Cleanup listeners
-      unlistenOutput.then((fn) => fn());
-      unlistenError.then((fn) => fn());
+      unlistenConsole.then((fn) => fn());
+      unlistenTask.then((fn) => fn());
     };
   }, []);
 
@@ -28,9 +65,17 @@ export function OutputViewer() {
     <div className="p-2 bg-gray-100 h-full overflow-auto text-xs flex flex-col">
       <h2 className="font-bold mb-2">Outputs</h2>
       <div className="font-mono">
-        {output.map((line, index) => (
-          <div key={index} className="whitespace-pre-wrap">
-            {line}
+        {output.map((entry, index) => (
+          <div
+            key={index}
+            className="whitespace-pre-wrap"
+            title={
+              entry.console
+                ? new Date(entry.console.timestamp).toISOString()
+                : undefined
+            }
+          >
+            {entry.text}
           </div>
         ))}
       </div>
@@ -0,0 +1,268 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashSet;
+use std::process::{Child, Command, Stdio};
+use tracing::info;
+
+use super::engine::{Connection, DebugAdapter, LaunchContext, PreparedLaunch};
+use crate::debugger::client::DAPClient;
+use crate::debugger::port_manager::spawn_on_available_port;
+use crate::processes;
+
+// Engine names of custom adapters start with this, e.g. "custom:delve".
+pub const ENGINE_PREFIX: &str = "custom:";
+
+// How wayfind talks to a custom adapter.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AdapterTransport {
+    // DAP over the adapter's stdin/stdout
+    #[default]
+    Stdio,
+    // The adapter listens on a port, passed to it through `${port}` in its args
+    Tcp,
+}
+
+// A DAP adapter the user registered in the settings, for languages without a built-in one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomAdapterConfig {
+    // Launched with the debug engine "custom:<name>"
+    pub name: String,
+    pub executable: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub transport: AdapterTransport,
+    // First port to try with the tcp transport; later ones are tried if it's taken
+    #[serde(default = "default_port")]
+    pub port: u16,
+    // "launch" or "attach"
+    #[serde(default = "default_request")]
+    pub request: String,
+    // The request's arguments. In strings, ${program}, ${cwd} and ${port} are replaced; a string
+    // that is just ${args}, ${env} or ${breakAtEntry} becomes the launch config's value. Without
+    // one, the usual launch arguments are sent.
+    #[serde(default)]
+    pub template: Option<Value>,
+}
+
+fn default_port() -> u16 {
+    9500
+}
+
+fn default_request() -> String {
+    "launch".to_string()
+}
+
+impl CustomAdapterConfig {
+    fn check(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Custom adapters need a name".to_string());
+        }
+        if self.executable.trim().is_empty() {
+            return Err(format!("Custom adapter {} has no executable", self.name));
+        }
+        if !matches!(self.request.as_str(), "launch" | "attach") {
+            return Err(format!(
+                "Custom adapter {} needs a request of launch or attach, not {}",
+                self.name, self.request
+            ));
+        }
+        Ok(())
+    }
+
+    // The template, or the launch arguments most adapters understand.
+    fn template(&self) -> Value {
+        self.template.clone().unwrap_or_else(|| {
+            json!({
+                "program": "${program}",
+                "args": "${args}",
+                "cwd": "${cwd}",
+                "env": "${env}",
+                "stopOnEntry": "${breakAtEntry}",
+            })
+        })
+    }
+}
+
+// Checks a settings file's custom adapters before it's saved: each must be complete, and names
+// must be unique since sessions pick adapters by name.
+pub fn validate(adapters: &[CustomAdapterConfig]) -> Result<(), String> {
+    let mut names = HashSet::new();
+    for adapter in adapters {
+        adapter.check()?;
+        if !names.insert(adapter.name.as_str()) {
+            return Err(format!(
+                "More than one custom adapter is named {}",
+                adapter.name
+            ));
+        }
+    }
+    Ok(())
+}
+
+// What the template's placeholders stand for in one launch.
+#[derive(Clone)]
+struct Substitutions {
+    program: String,
+    cwd: String,
+    port: Option<u16>,
+    args: Value,
+    env: Value,
+    break_at_entry: bool,
+}
+
+impl Substitutions {
+    fn apply(&self, template: &Value) -> Value {
+        match template {
+            Value::String(s) => match s.as_str() {
+                "${args}" => self.args.clone(),
+                "${env}" => self.env.clone(),
+                "${breakAtEntry}" => json!(self.break_at_entry),
+                _ => Value::String(self.apply_str(s)),
+            },
+            Value::Array(items) => Value::Array(items.iter().map(|v| self.apply(v)).collect()),
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(key, value)| (key.clone(), self.apply(value)))
+                    .collect::<Map<_, _>>(),
+            ),
+            other => other.clone(),
+        }
+    }
+
+    fn apply_str(&self, s: &str) -> String {
+        let port = self.port.map(|p| p.to_string()).unwrap_or_default();
+        s.replace("${program}", &self.program)
+            .replace("${cwd}", &self.cwd)
+            .replace("${port}", &port)
+    }
+}
+
+pub struct CustomAdapter {
+    engine: String,
+    config: CustomAdapterConfig,
+}
+
+impl CustomAdapter {
+    pub fn new(config: CustomAdapterConfig) -> Self {
+        CustomAdapter {
+            engine: format!("{}{}", ENGINE_PREFIX, config.name),
+            config,
+        }
+    }
+
+    fn spawn(&self, substitutions: &Substitutions) -> std::io::Result<Child> {
+        let mut command = Command::new(shellexpand::tilde(&self.config.executable).as_ref());
+        processes::own_process_group(&mut command)
+            .args(self.config.args.iter().map(|a| substitutions.apply_str(a)))
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if self.config.transport == AdapterTransport::Stdio {
+            command.stdin(Stdio::piped());
+        }
+        command.spawn()
+    }
+}
+
+impl DebugAdapter for CustomAdapter {
+    fn engine(&self) -> &str {
+        &self.engine
+    }
+
+    fn adapter_name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn prepare(
+        &self,
+        context: &LaunchContext,
+        client: &mut DAPClient,
+    ) -> Result<PreparedLaunch, String> {
+        let app_handle = context.app_handle;
+        self.config.check()?;
+
+        let program = shellexpand::tilde(context.script_path).into_owned();
+        let cwd = std::path::Path::new(&program)
+            .parent()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|| ".".to_string());
+        let mut substitutions = Substitutions {
+            program: program.clone(),
+            cwd,
+            port: None,
+            args: json!(context.launch_config.args),
+            env: json!(context.launch_config.env),
+            break_at_entry: context.launch_config.break_at_entry,
+        };
+
+        context.progress.stage("spawn", &self.config.name)?;
+        let spawn_error = |e: String| {
+            crate::launch_error(
+                app_handle,
+                "spawn_failed",
+                &format!("Failed to spawn adapter {}", self.config.name),
+                e,
+            )
+        };
+        let (connection, mut child) = match self.config.transport {
+            AdapterTransport::Stdio => {
+                let mut child = self
+                    .spawn(&substitutions)
+                    .map_err(|e| spawn_error(e.to_string()))?;
+                client.connect_child(&mut child).map_err(|e| {
+                    crate::launch_error(
+                        app_handle,
+                        "connect_failed",
+                        "Error connecting DAPClient",
+                        e,
+                    )
+                })?;
+                (Connection::Connected, child)
+            }
+            AdapterTransport::Tcp => {
+                let (port, child) = spawn_on_available_port(
+                    self.config.port,
+                    std::time::Duration::from_secs(1),
+                    |port| {
+                        let substitutions = Substitutions {
+                            port: Some(port),
+                            ..substitutions.clone()
+                        };
+                        self.spawn(&substitutions)
+                    },
+                )
+                .map_err(spawn_error)?;
+                substitutions.port = Some(port);
+                (Connection::Port(port), child)
+            }
+        };
+        info!(
+            "Spawned adapter {} with PID: {}",
+            self.config.name,
+            child.id()
+        );
+        crate::forward_child_output(
+            app_handle,
+            context.debug_state,
+            &mut child,
+            &self.config.name,
+        );
+
+        Ok(PreparedLaunch {
+            process: Some(child),
+            helper_processes: Vec::new(),
+            connection,
+            request: if self.config.request == "attach" {
+                "attach"
+            } else {
+                "launch"
+            },
+            arguments: substitutions.apply(&self.config.template()),
+            target: program,
+            label: self.config.name.clone(),
+            summary: format!("{} debug session launched successfully", self.config.name),
+        })
+    }
+}
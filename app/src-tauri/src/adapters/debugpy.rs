@@ -0,0 +1,223 @@
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use super::engine::{Connection, DebugAdapter, LaunchContext, PreparedLaunch};
+use crate::debugger::client::{emit_debug_error, emit_debug_warning, DAPClient};
+use crate::debugger::port_manager::spawn_on_available_port;
+use crate::pty::PtyConsole;
+use crate::sampler::PySpySampler;
+use crate::{interpreter, preflight, processes};
+
+// Python through debugpy: the program runs under `debugpy --listen` and the session attaches
+// to it, or to a program the user started that way.
+pub struct Debugpy;
+
+impl DebugAdapter for Debugpy {
+    fn engine(&self) -> &str {
+        "python"
+    }
+
+    fn adapter_name(&self) -> &str {
+        "debugpy"
+    }
+
+    fn prepare(
+        &self,
+        context: &LaunchContext,
+        _client: &mut DAPClient,
+    ) -> Result<PreparedLaunch, String> {
+        let app_handle = context.app_handle;
+        let debug_state = context.debug_state;
+        let launch_config = context.launch_config;
+
+        // debugpy ignores stopOnEntry when attaching, so break-at-entry is a breakpoint on the
+        // script's first line, sent in the configuration phase
+        if launch_config.break_at_entry {
+            *debug_state.entry_line.write() = entry_line(context);
+        }
+
+        // A program started with `debugpy --listen` runs in its own environment, so there's
+        // nothing to spawn or check
+        if let Some(endpoint) = &launch_config.wait_for_attach {
+            let address = format!("{}:{}", endpoint.host, endpoint.port);
+            context.progress.stage("waitForAttach", &address)?;
+            return Ok(PreparedLaunch {
+                process: None,
+                helper_processes: Vec::new(),
+                connection: Connection::WhenListening(endpoint.clone()),
+                request: "attach",
+                arguments: dap_client::DAPClient::attach_arguments(
+                    &endpoint.host,
+                    endpoint.port,
+                    launch_config.python_attach_arguments(),
+                ),
+                target: address.clone(),
+                label: "debugpy".to_string(),
+                summary: format!("Attached to debugpy at {}", address),
+            });
+        }
+
+        let python = interpreter::resolve(context.workspace_root.as_deref(), &context.settings)
+            .map_err(|e| {
+                crate::launch_error(
+                    app_handle,
+                    "interpreter_not_found",
+                    "No Python interpreter to debug with",
+                    e,
+                )
+            })?;
+        info!("Using Python interpreter: {}", python);
+
+        // Fail early if debugpy isn't importable; otherwise the adapter just exits on startup
+        preflight::check_debugpy(&python).map_err(|e| {
+            crate::launch_error(
+                app_handle,
+                "debugpy_missing",
+                &format!(
+                    "debugpy is not installed for {}; install it with `{} -m pip install debugpy`",
+                    python, python
+                ),
+                e,
+            )
+        })?;
+        debug_state.set_adapter_version("debugpy", preflight::debugpy_version(&python));
+
+        // Spawn the Python process running debugpy on an available port (starting at 5678).
+        // If debugpy loses the port to another process before binding it, the port manager
+        // retries on the next one. This also gives debugpy time to start up.
+        // The target is the script itself, a module, or a framework template's command.
+        let target = launch_config.python_target(context.script_path);
+        // The program runs in a terminal unless it asked for pipes or there's none to have
+        let mut console = if launch_config.pipes {
+            None
+        } else {
+            match PtyConsole::open() {
+                Ok(console) => Some(console),
+                Err(e) => {
+                    warn!("Running the program with pipes: {}", e);
+                    None
+                }
+            }
+        };
+        context.progress.stage("spawn", "debugpy")?;
+        let (debugpy_port, mut child) =
+            spawn_on_available_port(5678, std::time::Duration::from_secs(2), |port| {
+                let mut command = Command::new(&python);
+                command
+                    .args([
+                        "-Xfrozen_modules=off",
+                        "-u",
+                        "-m",
+                        "debugpy",
+                        "--listen",
+                        &format!("127.0.0.1:{}", port),
+                        "--wait-for-client",
+                    ])
+                    .args(&target.args)
+                    .envs(&target.env);
+                match &console {
+                    Some(console) => console.attach(&mut command)?,
+                    None => {
+                        processes::own_process_group(&mut command)
+                            .stdout(Stdio::piped())
+                            .stderr(Stdio::piped());
+                    }
+                }
+                if let Some(cwd) = &target.cwd {
+                    command.current_dir(cwd);
+                }
+                command.spawn()
+            })
+            .map_err(|e| {
+                crate::launch_error(
+                    app_handle,
+                    "spawn_failed",
+                    "Failed to spawn debugpy process",
+                    e,
+                )
+            })?;
+
+        info!("Using port {} for debugpy", debugpy_port);
+        info!("Spawned debugpy process with PID: {}", child.id());
+        let debuggee_pid = child.id();
+
+        match console.as_mut() {
+            Some(console) => console.start(app_handle, debug_state),
+            None => crate::forward_child_output(app_handle, debug_state, &mut child, "Python"),
+        }
+        *debug_state.pty.lock() = console;
+
+        // Optionally sample the running program with py-spy
+        if context.settings.py_spy_sampling {
+            match super::discovery::find_in_path("py-spy") {
+                Some(py_spy) => {
+                    let sampler = PySpySampler::start(
+                        py_spy,
+                        debuggee_pid,
+                        app_handle.clone(),
+                        Arc::clone(debug_state),
+                    );
+                    *debug_state.sampler.lock() = Some(sampler);
+                }
+                None => {
+                    let _ = emit_debug_error(
+                        app_handle,
+                        "companion_not_found",
+                        "py-spy sampling is enabled but py-spy was not found on PATH",
+                        None,
+                        true,
+                    );
+                }
+            }
+        }
+
+        Ok(PreparedLaunch {
+            process: Some(child),
+            helper_processes: Vec::new(),
+            connection: Connection::Port(debugpy_port),
+            request: "attach",
+            arguments: dap_client::DAPClient::attach_arguments(
+                "127.0.0.1",
+                debugpy_port,
+                launch_config.python_attach_arguments(),
+            ),
+            target: "debugpy".to_string(),
+            label: "debugpy".to_string(),
+            summary: "Debug session launched successfully".to_string(),
+        })
+    }
+}
+
+// Where break-at-entry stops: the script and its first line of code. A module target has no
+// script of its own to stop in, so it runs on with a warning.
+fn entry_line(context: &LaunchContext) -> Option<(String, u32)> {
+    let launch_config = context.launch_config;
+    let line = match (&launch_config.python_template, &launch_config.module) {
+        (None, Some(_)) => None,
+        _ => first_code_line(Path::new(context.script_path)),
+    };
+    if line.is_none() {
+        let _ = emit_debug_warning(
+            context.app_handle,
+            "break_at_entry_unsupported",
+            "Break at entry needs a Python script to stop in; the program will run until a breakpoint",
+            Some(context.script_path),
+        );
+    }
+    line.map(|line| (context.script_path.to_string(), line))
+}
+
+// The first line that isn't blank or a comment, where Python starts running a module.
+fn first_code_line(script: &Path) -> Option<u32> {
+    let source = fs::read_to_string(script).ok()?;
+    source
+        .lines()
+        .position(|line| {
+            let line = line.trim();
+            !line.is_empty() && !line.starts_with('#')
+        })
+        .map(|index| index as u32 + 1)
+}
@@ -0,0 +1,175 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::settings::Settings;
+
+// Well-known lldb-dap install locations, checked last.
+const KNOWN_LLDB_DAP_PATHS: &[&str] = &[
+    "/Applications/Xcode.app/Contents/Developer/usr/bin/lldb-dap",
+    "/opt/homebrew/opt/llvm/bin/lldb-dap",
+    "/usr/local/opt/llvm/bin/lldb-dap",
+    "/usr/bin/lldb-dap",
+    "/usr/local/bin/lldb-dap",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiscoverySource {
+    Settings,
+    Xcrun,
+    Path,
+    KnownLocation,
+}
+
+// One location that was considered while looking for an adapter.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveryCandidate {
+    pub source: DiscoverySource,
+    pub path: String,
+    pub found: bool,
+}
+
+// Result of looking for one adapter, as reported by detect_adapters.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdapterDetection {
+    pub adapter: String,
+    pub path: Option<String>,
+    pub source: Option<DiscoverySource>,
+    pub candidates: Vec<DiscoveryCandidate>,
+}
+
+// Walks the lldb-dap discovery chain: settings -> `xcrun -f lldb-dap` -> PATH -> known locations.
+// Every candidate is recorded so the UI can explain why nothing was found.
+pub fn discover_lldb_dap(settings: &Settings) -> AdapterDetection {
+    let mut candidates = Vec::new();
+
+    let mut check =
+        |source: DiscoverySource, path: PathBuf| -> Option<(PathBuf, DiscoverySource)> {
+            let found = path.is_file();
+            candidates.push(DiscoveryCandidate {
+                source,
+                path: path.to_string_lossy().to_string(),
+                found,
+            });
+            found.then_some((path, source))
+        };
+
+    let result = settings
+        .lldb_dap_path
+        .as_deref()
+        .filter(|p| !p.trim().is_empty())
+        .and_then(|p| {
+            check(
+                DiscoverySource::Settings,
+                PathBuf::from(shellexpand::tilde(p).into_owned()),
+            )
+        })
+        .or_else(|| xcrun_find("lldb-dap").and_then(|p| check(DiscoverySource::Xcrun, p)))
+        .or_else(|| {
+            search_path("lldb-dap")
+                .into_iter()
+                .find_map(|p| check(DiscoverySource::Path, p))
+        })
+        .or_else(|| {
+            KNOWN_LLDB_DAP_PATHS
+                .iter()
+                .find_map(|p| check(DiscoverySource::KnownLocation, PathBuf::from(p)))
+        });
+
+    AdapterDetection {
+        adapter: "lldb-dap".to_string(),
+        path: result
+            .as_ref()
+            .map(|(p, _)| p.to_string_lossy().to_string()),
+        source: result.map(|(_, s)| s),
+        candidates,
+    }
+}
+
+// Resolves the lldb-dap executable to launch, or explains where we looked.
+pub fn find_lldb_dap(settings: &Settings) -> Result<PathBuf, String> {
+    let detection = discover_lldb_dap(settings);
+    match detection.path {
+        Some(path) => Ok(PathBuf::from(path)),
+        None => {
+            let checked: Vec<String> = detection.candidates.into_iter().map(|c| c.path).collect();
+            Err(format!(
+                "Could not find lldb-dap executable. Please ensure LLDB with DAP support is installed or set its path in settings. Checked: {}",
+                if checked.is_empty() {
+                    "nothing".to_string()
+                } else {
+                    checked.join(", ")
+                }
+            ))
+        }
+    }
+}
+
+// Resolves the gdb executable: settings, then PATH.
+pub fn find_gdb(settings: &Settings) -> Result<PathBuf, String> {
+    let configured = settings
+        .gdb_path
+        .as_deref()
+        .filter(|p| !p.trim().is_empty())
+        .map(|p| PathBuf::from(shellexpand::tilde(p).into_owned()));
+    match configured {
+        Some(path) if path.is_file() => Ok(path),
+        Some(path) => Err(format!(
+            "gdb was not found at {}, as set in settings",
+            path.display()
+        )),
+        None => find_in_path("gdb").ok_or_else(|| {
+            "Could not find gdb on PATH. Install GDB 14 or later, or set its path in settings."
+                .to_string()
+        }),
+    }
+}
+
+pub fn detect_adapters(settings: &Settings) -> Vec<AdapterDetection> {
+    vec![discover_lldb_dap(settings)]
+}
+
+// Asks the Xcode toolchain for a tool; only meaningful on macOS.
+fn xcrun_find(tool: &str) -> Option<PathBuf> {
+    if !cfg!(target_os = "macos") {
+        return None;
+    }
+    let output = Command::new("xcrun").args(["-f", tool]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!path.is_empty()).then(|| PathBuf::from(path))
+}
+
+// Returns every PATH entry joined with `name`, in PATH order.
+pub fn search_path(name: &str) -> Vec<PathBuf> {
+    std::env::var_os("PATH")
+        .map(|paths| {
+            std::env::split_paths(&paths)
+                .map(|dir| dir.join(name))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Returns the first PATH entry where `name` is an existing file.
+pub fn find_in_path(name: &str) -> Option<PathBuf> {
+    search_path(name).into_iter().find(|p| p.is_file())
+}
+
+// First line of `<tool> --version`, if it has one.
+pub fn tool_version(path: &Path) -> Option<String> {
+    let output = Command::new(path).arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+}
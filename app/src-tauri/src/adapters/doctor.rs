@@ -0,0 +1,246 @@
+use dap_client::transport::{read_message, write_message};
+use dap_client::{DAPMessage, MessageType};
+use serde::Serialize;
+use std::io::BufReader;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use super::discovery::{discover_lldb_dap, find_gdb, find_in_path, tool_version};
+use super::gdb::{gdb_major_version, MIN_GDB_VERSION};
+use crate::preflight;
+use crate::settings::Settings;
+
+// How long an adapter gets to answer the smoke `initialize` request.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CheckStatus {
+    Ok,
+    // Usable, but something needs attention (e.g. an outdated version)
+    Warning,
+    Missing,
+    // Present but broken (doesn't start or doesn't answer)
+    Failed,
+}
+
+// One row of the setup checklist.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdapterCheck {
+    // The debug engine this serves: "python", "rust", "rr" or "gdb"
+    pub engine: String,
+    pub adapter: String,
+    pub status: CheckStatus,
+    pub path: Option<String>,
+    pub version: Option<String>,
+    // Whether the adapter answered an `initialize` request; None when not attempted
+    pub handshake: Option<bool>,
+    pub detail: Option<String>,
+}
+
+// Checks every adapter the engines depend on.
+pub fn run_diagnostics(settings: &Settings, python_interpreter: &str) -> Vec<AdapterCheck> {
+    vec![
+        check_debugpy(python_interpreter),
+        check_lldb_dap(settings),
+        check_rr(),
+        check_gdb(settings),
+    ]
+}
+
+fn check_debugpy(interpreter: &str) -> AdapterCheck {
+    let env = preflight::check_python_env(interpreter, false);
+    let mut check = AdapterCheck {
+        engine: "python".to_string(),
+        adapter: "debugpy".to_string(),
+        status: CheckStatus::Missing,
+        path: Some(interpreter.to_string()),
+        version: env.debugpy_version.clone(),
+        handshake: None,
+        detail: None,
+    };
+
+    if !env.runs {
+        check.status = CheckStatus::Failed;
+        check.detail = env.error;
+        return check;
+    }
+    if env.debugpy_version.is_none() {
+        check.detail = Some("debugpy is not installed; run check_python_env with install".into());
+        return check;
+    }
+
+    let mut command = Command::new(interpreter);
+    command.args(["-m", "debugpy.adapter"]);
+    finish_with_handshake(&mut check, command, "debugpy");
+    if check.status == CheckStatus::Ok && !env.debugpy_compatible {
+        check.status = CheckStatus::Warning;
+        check.detail = Some(format!(
+            "debugpy {} is older than {}",
+            env.debugpy_version.unwrap_or_default(),
+            env.min_debugpy_version
+        ));
+    }
+    check
+}
+
+fn check_lldb_dap(settings: &Settings) -> AdapterCheck {
+    let detection = discover_lldb_dap(settings);
+    let mut check = AdapterCheck {
+        engine: "rust".to_string(),
+        adapter: "lldb-dap".to_string(),
+        status: CheckStatus::Missing,
+        path: detection.path.clone(),
+        version: None,
+        handshake: None,
+        detail: None,
+    };
+    let Some(path) = detection.path else {
+        let checked: Vec<String> = detection.candidates.into_iter().map(|c| c.path).collect();
+        check.detail = Some(format!("Not found; checked {}", checked.join(", ")));
+        return check;
+    };
+
+    check.version = tool_version(Path::new(&path));
+    // Without arguments lldb-dap speaks DAP on stdio
+    finish_with_handshake(&mut check, Command::new(&path), "lldb");
+    check
+}
+
+// rr isn't a DAP adapter itself: replays are driven through lldb-dap's gdb-remote support.
+fn check_rr() -> AdapterCheck {
+    let path = find_in_path("rr");
+    AdapterCheck {
+        engine: "rr".to_string(),
+        adapter: "rr".to_string(),
+        status: if path.is_some() {
+            CheckStatus::Ok
+        } else {
+            CheckStatus::Missing
+        },
+        version: path.as_deref().and_then(tool_version),
+        path: path.map(|p| p.to_string_lossy().to_string()),
+        handshake: None,
+        detail: None,
+    }
+}
+
+fn check_gdb(settings: &Settings) -> AdapterCheck {
+    let mut check = AdapterCheck {
+        engine: "gdb".to_string(),
+        adapter: "gdb".to_string(),
+        status: CheckStatus::Missing,
+        path: None,
+        version: None,
+        handshake: None,
+        detail: None,
+    };
+    let path = match find_gdb(settings) {
+        Ok(path) => path,
+        Err(e) => {
+            check.detail = Some(e);
+            return check;
+        }
+    };
+    check.path = Some(path.to_string_lossy().to_string());
+    check.version = tool_version(&path);
+
+    // Older releases exit on the unknown interpreter, so there's nothing to shake hands with
+    let major = check.version.as_deref().and_then(gdb_major_version);
+    if major.is_some_and(|major| major < MIN_GDB_VERSION) {
+        check.status = CheckStatus::Failed;
+        check.detail = Some(format!(
+            "GDB {} or later is needed for DAP",
+            MIN_GDB_VERSION
+        ));
+        return check;
+    }
+    let mut command = Command::new(&path);
+    command.args(["--quiet", "--interpreter=dap"]);
+    finish_with_handshake(&mut check, command, "gdb");
+    check
+}
+
+fn finish_with_handshake(check: &mut AdapterCheck, command: Command, adapter_id: &str) {
+    match handshake(command, adapter_id) {
+        Ok(()) => {
+            check.status = CheckStatus::Ok;
+            check.handshake = Some(true);
+        }
+        Err(e) => {
+            check.status = CheckStatus::Failed;
+            check.handshake = Some(false);
+            check.detail = Some(e);
+        }
+    }
+}
+
+// Starts the adapter on stdio, sends `initialize` and waits for a successful response.
+fn handshake(mut command: Command, adapter_id: &str) -> Result<(), String> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to start adapter: {}", e))?;
+
+    let mut request = DAPMessage::request(
+        "initialize",
+        Some(serde_json::json!({
+            "adapterID": adapter_id,
+            "clientID": "wayfind",
+            "clientName": "Wayfind",
+            "linesStartAt1": true,
+            "columnsStartAt1": true,
+            "pathFormat": "path",
+        })),
+    );
+    request.seq = 1;
+
+    let result = (|| -> Result<(), String> {
+        let mut stdin = child.stdin.take().ok_or("Adapter has no stdin")?;
+        let stdout = child.stdout.take().ok_or("Adapter has no stdout")?;
+        write_message(&mut stdin, &request)
+            .map_err(|e| format!("Failed to send initialize: {}", e))?;
+
+        // Reads happen on a thread so a silent adapter can't hang the check
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(stdout);
+            loop {
+                let message = match read_message(&mut reader) {
+                    Ok(Some(raw)) => serde_json::from_str::<DAPMessage>(&raw).ok(),
+                    _ => {
+                        let _ = tx.send(None);
+                        return;
+                    }
+                };
+                if let Some(message) = message.filter(|m| m.message_type == MessageType::Response) {
+                    let _ = tx.send(Some(message));
+                    return;
+                }
+            }
+        });
+
+        match rx.recv_timeout(HANDSHAKE_TIMEOUT) {
+            Ok(Some(response)) if response.success == Some(true) => Ok(()),
+            Ok(Some(response)) => Err(format!(
+                "initialize failed: {}",
+                response.message.unwrap_or_default()
+            )),
+            Ok(None) => Err("Adapter exited without answering initialize".to_string()),
+            Err(_) => Err(format!(
+                "No initialize response within {}s",
+                HANDSHAKE_TIMEOUT.as_secs()
+            )),
+        }
+    })();
+
+    let _ = child.kill();
+    let _ = child.wait();
+    result
+}
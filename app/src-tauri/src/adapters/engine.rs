@@ -0,0 +1,116 @@
+use serde_json::Value;
+use std::path::PathBuf;
+use std::process::Child;
+use std::sync::Arc;
+
+use super::custom::{CustomAdapter, ENGINE_PREFIX};
+use super::debugpy::Debugpy;
+use super::gdb::GdbDap;
+use super::lldb::LldbDap;
+use super::pool::AdapterPool;
+use super::rr::RrReplay;
+use crate::debug_state::DebugSessionState;
+use crate::debugger::client::DAPClient;
+use crate::launch_config::{AttachEndpoint, LaunchConfig};
+use crate::launch_progress::LaunchProgress;
+use crate::settings::Settings;
+
+// What an adapter gets to prepare a launch with.
+pub struct LaunchContext<'a> {
+    pub app_handle: &'a tauri::AppHandle,
+    pub debug_state: &'a Arc<DebugSessionState>,
+    pub progress: &'a LaunchProgress,
+    pub settings: Settings,
+    pub adapter_pool: &'a Arc<AdapterPool>,
+    // The open workspace, for project-level configuration
+    pub workspace_root: Option<PathBuf>,
+    // The program (or script, or cargo workspace) the user picked, as given
+    pub script_path: &'a str,
+    pub launch_config: &'a LaunchConfig,
+}
+
+// How the session's client reaches the adapter.
+pub enum Connection {
+    // An adapter listening on localhost, retried while it starts up
+    Port(u16),
+    // A program started with `debugpy --listen`, retried until it listens
+    WhenListening(AttachEndpoint),
+    // Already connected by prepare, e.g. over the adapter's stdio
+    Connected,
+}
+
+// A launch prepared by an adapter: the processes it started, how to connect, and the request
+// that starts debugging once connected.
+pub struct PreparedLaunch {
+    // Owned by the session so cancel_launch and terminate can kill it
+    pub process: Option<Child>,
+    pub helper_processes: Vec<Child>,
+    pub connection: Connection,
+    // "launch" or "attach", and its arguments
+    pub request: &'static str,
+    pub arguments: Value,
+    // Detail of the launch stage that sends the request, e.g. the program path
+    pub target: String,
+    // Detail of the "ready" stage
+    pub label: String,
+    // Returned to the frontend once the session is up
+    pub summary: String,
+}
+
+// A debug engine: everything about launching and talking to one kind of adapter that isn't
+// plain DAP. launch_debug_session drives the rest.
+pub trait DebugAdapter: Send + Sync {
+    // The engine name launch_debug_session is called with, also the session's debugger type
+    fn engine(&self) -> &str;
+    // The adapter's name in launch progress
+    fn adapter_name(&self) -> &str;
+    // Builds or resolves the program, finds and spawns the adapter, and says how to start
+    // debugging. Runs after the session was reset for the launch.
+    fn prepare(
+        &self,
+        context: &LaunchContext,
+        client: &mut DAPClient,
+    ) -> Result<PreparedLaunch, String>;
+    // The expression and evaluate context to send for one typed in the debug console, with the
+    // program paused in a frame or not.
+    fn console_evaluate(&self, expression: &str, paused: bool) -> (String, &'static str) {
+        (expression.to_string(), console_context(paused))
+    }
+    // The value to show for an evaluate result, when the adapter's needs cleaning up.
+    fn evaluate_result(&self, _result: &str) -> Option<String> {
+        None
+    }
+}
+
+// Paused, console input is evaluated in the top frame; otherwise it goes to the adapter's repl.
+pub fn console_context(paused: bool) -> &'static str {
+    if paused {
+        "hover"
+    } else {
+        "repl"
+    }
+}
+
+// The adapter for engine `name`: a built-in one, or "custom:<name>" for one registered in the
+// settings.
+pub fn adapter(name: &str, settings: &Settings) -> Option<Box<dyn DebugAdapter>> {
+    match name {
+        "python" => Some(Box::new(Debugpy)),
+        "rust" => Some(Box::new(LldbDap)),
+        "rr" => Some(Box::new(RrReplay)),
+        "gdb" => Some(Box::new(GdbDap)),
+        _ => {
+            let custom = name.strip_prefix(ENGINE_PREFIX)?;
+            let config = settings.custom_adapters.iter().find(|a| a.name == custom)?;
+            Some(Box::new(CustomAdapter::new(config.clone())))
+        }
+    }
+}
+
+// The adapter the session was launched with, if any.
+pub fn for_session(
+    debug_state: &DebugSessionState,
+    settings: &Settings,
+) -> Option<Box<dyn DebugAdapter>> {
+    adapter(debug_state.debugger_type.read().as_deref()?, settings)
+}
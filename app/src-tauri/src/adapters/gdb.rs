@@ -0,0 +1,160 @@
+use serde_json::json;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use tracing::info;
+
+use super::discovery::{find_gdb, tool_version};
+use super::engine::{Connection, DebugAdapter, LaunchContext, PreparedLaunch};
+use super::lldb::resolve_program;
+use crate::debugger::client::{emit_debug_error, DAPClient};
+use crate::debugger::util::parse_gdb_result;
+use crate::processes;
+
+// The first GDB release with the DAP interpreter.
+pub const MIN_GDB_VERSION: u32 = 14;
+
+// C and C++ through GDB's own DAP interpreter (`gdb -i dap`), spoken over its stdio. Needs no
+// lldb-dap, which Linux distributions often don't package.
+pub struct GdbDap;
+
+impl DebugAdapter for GdbDap {
+    fn engine(&self) -> &str {
+        "gdb"
+    }
+
+    fn adapter_name(&self) -> &str {
+        "gdb"
+    }
+
+    fn prepare(
+        &self,
+        context: &LaunchContext,
+        client: &mut DAPClient,
+    ) -> Result<PreparedLaunch, String> {
+        let app_handle = context.app_handle;
+        let debug_state = context.debug_state;
+        let launch_config = context.launch_config;
+
+        let resolved_path = resolve_program(context, context.script_path)?;
+        let gdb_path = locate_gdb(context)?;
+
+        context.progress.stage("spawn", "gdb")?;
+        let mut child = processes::own_process_group(&mut Command::new(&gdb_path))
+            .args(["--quiet", "--interpreter=dap"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                crate::launch_error(app_handle, "spawn_failed", "Failed to spawn gdb", e)
+            })?;
+        info!("Spawned gdb with PID: {}", child.id());
+
+        // The DAP stream is gdb's stdout, so it's claimed before the output forwarding below
+        client.connect_child(&mut child).map_err(|e| {
+            crate::launch_error(
+                app_handle,
+                "connect_failed",
+                "Error connecting DAPClient",
+                e,
+            )
+        })?;
+        crate::forward_child_output(app_handle, debug_state, &mut child, "gdb");
+
+        let program = resolved_path.to_string_lossy().to_string();
+        let (request, arguments, target) = match launch_config.attach_pid {
+            Some(pid) => (
+                "attach",
+                json!({ "program": program, "pid": pid }),
+                format!("process {}", pid),
+            ),
+            None => (
+                "launch",
+                json!({
+                    "program": program,
+                    "args": launch_config.args,
+                    "env": launch_config.env,
+                    "cwd": resolved_path.parent()
+                        .map(|p| p.to_string_lossy().to_string())
+                        .unwrap_or_else(|| ".".to_string()),
+                    "stopAtBeginningOfMainSubprogram": launch_config.break_at_entry,
+                }),
+                program.clone(),
+            ),
+        };
+
+        Ok(PreparedLaunch {
+            process: Some(child),
+            helper_processes: Vec::new(),
+            connection: Connection::Connected,
+            request,
+            arguments,
+            target,
+            label: "gdb".to_string(),
+            summary: "GDB debug session launched successfully".to_string(),
+        })
+    }
+
+    fn console_evaluate(&self, expression: &str, paused: bool) -> (String, &'static str) {
+        gdb_console_input(expression, paused)
+    }
+
+    fn evaluate_result(&self, result: &str) -> Option<String> {
+        Some(parse_gdb_result(result))
+    }
+}
+
+// gdb runs repl input as CLI commands and everything else as expressions. Console input is an
+// expression, with the `print` habit tolerated, unless it's `-exec <command>`. With nothing
+// paused there's no frame to evaluate in, so expressions go through `print`.
+pub fn gdb_console_input(expression: &str, paused: bool) -> (String, &'static str) {
+    if let Some(command) = expression.strip_prefix("-exec ") {
+        return (command.trim().to_string(), "repl");
+    }
+    let expression = ["print ", "p "]
+        .iter()
+        .find_map(|prefix| expression.strip_prefix(prefix))
+        .unwrap_or(expression)
+        .trim();
+    if paused {
+        // "watch" rather than "hover", which gdb evaluates without calling functions
+        (expression.to_string(), "watch")
+    } else {
+        (format!("print {}", expression), "repl")
+    }
+}
+
+// The major version in a `gdb --version` line, e.g. 14 for "GNU gdb (GDB) 14.2".
+pub fn gdb_major_version(raw: &str) -> Option<u32> {
+    raw.split_whitespace()
+        .last()?
+        .split('.')
+        .next()?
+        .parse()
+        .ok()
+}
+
+// Locates gdb and checks it's new enough to speak DAP. Records its version for the session.
+fn locate_gdb(context: &LaunchContext) -> Result<PathBuf, String> {
+    let report = |code: &str, message: String| {
+        let _ = emit_debug_error(context.app_handle, code, &message, None, false);
+        message
+    };
+    let gdb_path = find_gdb(&context.settings).map_err(|e| report("adapter_not_found", e))?;
+    info!("Using gdb at: {}", gdb_path.display());
+
+    let version = tool_version(&gdb_path);
+    if let Some(major) = version.as_deref().and_then(gdb_major_version) {
+        if major < MIN_GDB_VERSION {
+            return Err(report(
+                "adapter_unsupported",
+                format!(
+                    "gdb {} has no DAP support; GDB {} or later is needed",
+                    major, MIN_GDB_VERSION
+                ),
+            ));
+        }
+    }
+    context.debug_state.set_adapter_version("gdb", version);
+    Ok(gdb_path)
+}
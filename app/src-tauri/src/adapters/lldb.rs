@@ -0,0 +1,265 @@
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use tauri::Emitter;
+use tracing::{info, warn};
+
+use super::discovery::find_lldb_dap;
+use super::engine::{console_context, Connection, DebugAdapter, LaunchContext, PreparedLaunch};
+use crate::debugger::client::{emit_debug_error, emit_debug_warning, DAPClient};
+use crate::debugger::util::parse_lldb_result;
+use crate::launch_config::FollowForkMode;
+use crate::launch_progress::CANCELLED_MESSAGE;
+use crate::{cargo_workspace, preflight};
+
+// Native programs (Rust, C, C++) launched under lldb-dap.
+pub struct LldbDap;
+
+impl DebugAdapter for LldbDap {
+    fn engine(&self) -> &str {
+        "rust"
+    }
+
+    fn adapter_name(&self) -> &str {
+        "lldb-dap"
+    }
+
+    fn prepare(
+        &self,
+        context: &LaunchContext,
+        client: &mut DAPClient,
+    ) -> Result<PreparedLaunch, String> {
+        let app_handle = context.app_handle;
+        let debug_state = context.debug_state;
+        let launch_config = context.launch_config;
+
+        // With a cargo package selected, the path is the workspace root and the binary
+        // comes from building that package.
+        let program_path = match &launch_config.cargo_package {
+            Some(package) => {
+                context.progress.stage("build", package)?;
+                let workspace_root = shellexpand::tilde(context.script_path).into_owned();
+                // Like rr record, the build is waited on from this worker with the runtime's
+                // other tasks moved off it; a cancelled launch kills cargo
+                let built = tokio::task::block_in_place(|| {
+                    cargo_workspace::build_binary(
+                        std::path::Path::new(&workspace_root),
+                        package,
+                        launch_config.cargo_bin.as_deref(),
+                        launch_config.cargo_target_kind,
+                        || context.progress.is_cancelled(),
+                    )
+                })
+                .map_err(|e| {
+                    if e.message == CANCELLED_MESSAGE {
+                        return e.message;
+                    }
+                    // Compiler errors go out structured so the editor can jump to them
+                    if !e.diagnostics.is_empty() {
+                        let _ = app_handle.emit(
+                            "build-diagnostics",
+                            json!({
+                                "session": debug_state.token,
+                                "package": package,
+                                "diagnostics": e.diagnostics,
+                            }),
+                        );
+                    }
+                    crate::launch_error(
+                        app_handle,
+                        "build_failed",
+                        "Failed to build cargo target",
+                        e,
+                    )
+                })?;
+                context.progress.stage("built", &built.to_string_lossy())?;
+                built.to_string_lossy().to_string()
+            }
+            None => context.script_path.to_string(),
+        };
+
+        let resolved_path = resolve_program(context, &program_path)?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            // On Unix-like systems, check if the file is executable
+            if let Ok(metadata) = std::fs::metadata(&resolved_path) {
+                let permissions = metadata.permissions();
+                if permissions.mode() & 0o111 == 0 {
+                    warn!("The selected file does not have executable permissions");
+                    // Just a warning, continue anyway
+                }
+            }
+        }
+
+        // Breakpoints silently fail to bind without debug info, so call it out up front
+        match preflight::has_debug_info(&resolved_path) {
+            Ok(true) => {}
+            Ok(false) => {
+                let _ = emit_debug_warning(
+                    app_handle,
+                    "missing_debug_info",
+                    "The selected binary has no debug info; breakpoints will not bind. Build it with debug symbols (e.g. a debug profile or `debug = true`).",
+                    Some(&resolved_path.to_string_lossy()),
+                );
+            }
+            Err(e) => warn!("Could not inspect binary for debug info: {}", e),
+        }
+
+        if cfg!(target_os = "macos") && launch_config.follow_fork == Some(FollowForkMode::Child) {
+            let _ = emit_debug_warning(
+                app_handle,
+                "follow_fork_unsupported",
+                "Following forked children is not supported by the macOS debug server; the session stays attached to the parent process.",
+                None,
+            );
+        }
+
+        // Break at entry with a function breakpoint on the crate's own main rather than
+        // stopOnEntry, which would stop in the loader before any user code.
+        if launch_config.break_at_entry {
+            let crate_name = resolved_path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().replace('-', "_"))
+                .unwrap_or_default();
+            let entry = if crate_name.is_empty() {
+                "main".to_string()
+            } else {
+                format!("{}::main", crate_name)
+            };
+            *debug_state.entry_function.write() = Some(entry);
+        }
+
+        let lldb_dap_path = locate_lldb_dap(context)?;
+
+        // Take the warm lldb-dap from the pool, or spawn one if there's none yet. On Windows it
+        // speaks DAP over its stdio rather than a port.
+        context.progress.stage("spawn", "lldb-dap")?;
+        let (lldb_port, mut child) = context.adapter_pool.take(&lldb_dap_path).map_err(|e| {
+            crate::launch_error(
+                app_handle,
+                "spawn_failed",
+                "Failed to spawn lldb-dap process",
+                e,
+            )
+        })?;
+
+        match lldb_port {
+            Some(port) => info!("Using port {} for lldb-dap", port),
+            None => info!("Using stdio for lldb-dap"),
+        }
+        info!("Spawned lldb-dap process with PID: {}", child.id());
+
+        // Over stdio the adapter's stdout is the DAP stream, so it's claimed before the
+        // output forwarding below
+        let connection = match lldb_port {
+            Some(port) => Connection::Port(port),
+            None => {
+                client.connect_child(&mut child).map_err(|e| {
+                    crate::launch_error(
+                        app_handle,
+                        "connect_failed",
+                        "Error connecting DAPClient",
+                        e,
+                    )
+                })?;
+                Connection::Connected
+            }
+        };
+        crate::forward_child_output(app_handle, debug_state, &mut child, "lldb-dap");
+
+        // Launch the program itself, rather than attaching to it
+        let mut launch_args = json!({
+            "program": resolved_path.to_string_lossy(),
+            "stopOnEntry": false,
+            "args": launch_config.args,
+            "env": launch_config
+                .env
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect::<Vec<_>>(),
+            "cwd": resolved_path.parent()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|| ".".to_string()),
+        });
+        // Pass through any initCommands/preRunCommands/postRunCommands
+        if let Value::Object(ref mut map) = launch_args {
+            map.extend(launch_config.lldb_command_arguments());
+        }
+        // Formatting limits set in an earlier session carry over
+        let format_commands = debug_state.format_limits.read().lldb_commands();
+        if !format_commands.is_empty() {
+            let init_commands = launch_args.as_object_mut().and_then(|map| {
+                map.entry("initCommands")
+                    .or_insert_with(|| json!([]))
+                    .as_array_mut()
+            });
+            if let Some(init_commands) = init_commands {
+                init_commands.extend(format_commands.into_iter().map(Value::from));
+            }
+        }
+
+        Ok(PreparedLaunch {
+            process: Some(child),
+            helper_processes: Vec::new(),
+            connection,
+            request: "launch",
+            arguments: launch_args,
+            target: resolved_path.to_string_lossy().to_string(),
+            label: "lldb-dap".to_string(),
+            summary: "Rust debug session launched successfully".to_string(),
+        })
+    }
+
+    // lldb-dap runs console input as lldb commands, so plain expressions go through `expr`
+    fn console_evaluate(&self, expression: &str, paused: bool) -> (String, &'static str) {
+        (lldb_expression(expression), console_context(paused))
+    }
+
+    fn evaluate_result(&self, result: &str) -> Option<String> {
+        Some(parse_lldb_result(result))
+    }
+}
+
+// `expression` as an lldb command, unless it already is one.
+pub fn lldb_expression(expression: &str) -> String {
+    if !expression.starts_with("expr ") && !expression.starts_with("expression ") {
+        format!("expr -- {}", expression)
+    } else {
+        expression.to_string()
+    }
+}
+
+// Resolves the program to debug (e.g. expands ~ and normalizes relative segments).
+pub fn resolve_program(context: &LaunchContext, path: &str) -> Result<PathBuf, String> {
+    let expanded_path = shellexpand::tilde(path).into_owned();
+    let resolved_path = std::fs::canonicalize(&expanded_path).map_err(|e| {
+        crate::launch_error(
+            context.app_handle,
+            "invalid_program",
+            &format!("Failed to resolve path {}", expanded_path),
+            e,
+        )
+    })?;
+    info!("Resolved binary path: {}", resolved_path.to_string_lossy());
+    Ok(resolved_path)
+}
+
+// Locates lldb-dap: settings, then xcrun, then PATH, then well-known install locations. Records
+// its version for the session's quirks.
+pub fn locate_lldb_dap(context: &LaunchContext) -> Result<PathBuf, String> {
+    let lldb_dap_path = find_lldb_dap(&context.settings).inspect_err(|message| {
+        let _ = emit_debug_error(
+            context.app_handle,
+            "adapter_not_found",
+            message,
+            None,
+            false,
+        );
+    })?;
+    info!("Using lldb-dap at: {}", lldb_dap_path.display());
+    context
+        .debug_state
+        .set_adapter_version("lldb-dap", super::discovery::tool_version(&lldb_dap_path));
+    Ok(lldb_dap_path)
+}
@@ -0,0 +1,10 @@
+pub mod custom;
+pub mod debugpy;
+pub mod discovery;
+pub mod doctor;
+pub mod engine;
+pub mod gdb;
+pub mod lldb;
+pub mod pool;
+pub mod quirks;
+pub mod rr;
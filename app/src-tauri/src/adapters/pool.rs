@@ -0,0 +1,99 @@
+use parking_lot::Mutex;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
+use std::thread;
+
+use crate::debugger::port_manager::spawn_on_available_port;
+use crate::processes;
+
+// An lldb-dap started ahead of a launch, not yet connected to.
+struct WarmAdapter {
+    path: PathBuf,
+    port: Option<u16>,
+    child: Child,
+}
+
+// Keeps one lldb-dap spawned and idle so a Rust launch doesn't wait seconds for the adapter to
+// load LLDB. debugpy runs inside the debuggee and starts with it, so Python sessions have
+// nothing to keep warm.
+#[derive(Default)]
+pub struct AdapterPool {
+    idle: Mutex<Option<WarmAdapter>>,
+}
+
+// Spawns lldb-dap on an available port (starting at 9123), retrying on the next port if it
+// loses the race for the one we picked. On Windows it speaks DAP over its stdio instead, since
+// some endpoint security software blocks loopback connections, and the port is None.
+pub fn spawn_lldb_dap(path: &Path) -> Result<(Option<u16>, Child), String> {
+    if cfg!(windows) {
+        processes::own_process_group(&mut Command::new(path))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map(|child| (None, child))
+            .map_err(|e| e.to_string())
+    } else {
+        spawn_on_available_port(9123, std::time::Duration::from_secs(1), |port| {
+            processes::own_process_group(&mut Command::new(path))
+                .arg("--port")
+                .arg(port.to_string())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+        })
+        .map(|(port, child)| (Some(port), child))
+    }
+}
+
+impl AdapterPool {
+    // Hands out the idle adapter if it was started from `path` and is still running, otherwise
+    // spawns one. Either way another is started in the background for the next launch.
+    pub fn take(self: &Arc<Self>, path: &Path) -> Result<(Option<u16>, Child), String> {
+        let warm = self.idle.lock().take();
+        self.refill(path.to_path_buf());
+        if let Some(mut warm) = warm {
+            if warm.path == path && matches!(warm.child.try_wait(), Ok(None)) {
+                tracing::debug!("Using warm lldb-dap (pid {})", warm.child.id());
+                return Ok((warm.port, warm.child));
+            }
+            processes::kill_tree(&mut warm.child);
+        }
+        spawn_lldb_dap(path)
+    }
+
+    // Replaces the idle adapter with one started from `path`, e.g. after the lldb-dap setting
+    // changed. None just empties the pool.
+    pub fn recycle(self: &Arc<Self>, path: Option<PathBuf>) {
+        if let Some(mut warm) = self.idle.lock().take() {
+            processes::kill_tree(&mut warm.child);
+        }
+        if let Some(path) = path {
+            self.refill(path);
+        }
+    }
+
+    fn refill(self: &Arc<Self>, path: PathBuf) {
+        let pool = Arc::clone(self);
+        thread::spawn(move || {
+            if pool.idle.lock().is_some() {
+                return;
+            }
+            let (port, mut child) = match spawn_lldb_dap(&path) {
+                Ok(spawned) => spawned,
+                Err(e) => {
+                    tracing::warn!("Could not start a warm lldb-dap: {}", e);
+                    return;
+                }
+            };
+            let mut idle = pool.idle.lock();
+            // Filled by another refill in the meantime
+            if idle.is_some() {
+                processes::kill_tree(&mut child);
+                return;
+            }
+            *idle = Some(WarmAdapter { path, port, child });
+        });
+    }
+}
@@ -0,0 +1,81 @@
+use serde::Serialize;
+
+// The adapter a session runs on and the version it reported at launch.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdapterVersion {
+    pub adapter: String,
+    // As printed by the adapter, e.g. "lldb version 19.1.7" or "1.8.0"
+    pub raw: Option<String>,
+    pub quirks: Quirks,
+}
+
+impl AdapterVersion {
+    pub fn new(adapter: &str, raw: Option<String>) -> Self {
+        let quirks = Quirks::for_adapter(adapter, raw.as_deref());
+        AdapterVersion {
+            adapter: adapter.to_string(),
+            raw,
+            quirks,
+        }
+    }
+}
+
+// Adapter behaviors that differ from the spec or between releases.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Quirks {
+    // The adapter exits on `terminate` without sending a `terminated` event, so the client has
+    // to report the session as terminated itself
+    pub no_terminated_event: bool,
+}
+
+struct QuirkEntry {
+    adapter: &'static str,
+    // Applies to versions below this one, and to versions that can't be determined
+    fixed_in: Option<&'static [u32]>,
+    apply: fn(&mut Quirks),
+}
+
+const QUIRK_TABLE: &[QuirkEntry] = &[QuirkEntry {
+    adapter: "lldb-dap",
+    fixed_in: Some(&[19]),
+    apply: |q| q.no_terminated_event = true,
+}];
+
+impl Quirks {
+    pub fn for_adapter(adapter: &str, raw_version: Option<&str>) -> Quirks {
+        let version = raw_version.and_then(parse_version);
+        let mut quirks = Quirks::default();
+        for entry in QUIRK_TABLE.iter().filter(|e| e.adapter == adapter) {
+            let applies = match (entry.fixed_in, &version) {
+                (Some(fixed_in), Some(version)) => version.as_slice() < fixed_in,
+                _ => true,
+            };
+            if applies {
+                (entry.apply)(&mut quirks);
+            }
+        }
+        quirks
+    }
+}
+
+// Pulls the upstream version number out of a version line. Apple's lldb reports its own build
+// numbers ("lldb-1600.0.36.3"), which don't line up with LLVM releases, so those stay unknown.
+fn parse_version(raw: &str) -> Option<Vec<u32>> {
+    if raw
+        .split("lldb-")
+        .skip(1)
+        .any(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+    {
+        return None;
+    }
+    let token = raw
+        .split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .find(|t| t.contains('.') && t.starts_with(|c: char| c.is_ascii_digit()))?;
+    let parts: Vec<u32> = token
+        .split('.')
+        .map_while(|part| part.parse().ok())
+        .collect();
+    (!parts.is_empty()).then_some(parts)
+}
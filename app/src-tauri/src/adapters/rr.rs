@@ -0,0 +1,225 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+use super::engine::{console_context, Connection, DebugAdapter, LaunchContext, PreparedLaunch};
+use super::lldb::{lldb_expression, locate_lldb_dap, resolve_program};
+use crate::debugger::client::DAPClient;
+use crate::debugger::port_manager::spawn_on_available_port;
+use crate::debugger::util::parse_lldb_result;
+use crate::launch_progress::CANCELLED_MESSAGE;
+use crate::processes;
+
+// Time-travel debugging: records the program under rr, then debugs the recording with lldb-dap
+// connected to rr's replay server. Reverse execution is available through step_back and
+// reverse_continue.
+pub struct RrReplay;
+
+impl DebugAdapter for RrReplay {
+    fn engine(&self) -> &str {
+        "rr"
+    }
+
+    fn adapter_name(&self) -> &str {
+        "lldb-dap"
+    }
+
+    fn prepare(
+        &self,
+        context: &LaunchContext,
+        _client: &mut DAPClient,
+    ) -> Result<PreparedLaunch, String> {
+        let app_handle = context.app_handle;
+        let debug_state = context.debug_state;
+        let resolved_path = resolve_program(context, context.script_path)?;
+        let lldb_dap_path = locate_lldb_dap(context)?;
+
+        // 1. Record the program to completion
+        context
+            .progress
+            .stage("record", &resolved_path.to_string_lossy())?;
+        let cwd = resolved_path
+            .parent()
+            .map(|p| p.to_path_buf())
+            .unwrap_or_else(|| PathBuf::from("."));
+        let trace = record(context, &resolved_path, &cwd).map_err(|e| match e.as_str() {
+            CANCELLED_MESSAGE => e,
+            _ => crate::launch_error(app_handle, "record_failed", "rr record failed", e),
+        })?;
+        let trace_dir = trace.dir();
+        info!("Recorded rr trace at {}", trace_dir.display());
+
+        // 2. Serve the recording over gdb-remote
+        context
+            .progress
+            .stage("replayServer", &trace_dir.to_string_lossy())?;
+        let (replay_port, mut replay_child) =
+            spawn_on_available_port(9300, std::time::Duration::from_secs(2), |port| {
+                spawn_replay_server(trace_dir, port)
+            })
+            .map_err(|e| {
+                crate::launch_error(
+                    app_handle,
+                    "spawn_failed",
+                    "Failed to start rr replay server",
+                    e,
+                )
+            })?;
+        info!("rr replay server listening on port {}", replay_port);
+        crate::forward_child_output(app_handle, debug_state, &mut replay_child, "rr replay");
+
+        // 3. Spawn lldb-dap, to be attached to the replay server. The server isn't the
+        // session's yet, so it goes if this fails.
+        let spawned = context.progress.stage("spawn", "lldb-dap").and_then(|()| {
+            spawn_on_available_port(9123, std::time::Duration::from_secs(1), |port| {
+                crate::processes::own_process_group(&mut Command::new(&lldb_dap_path))
+                    .arg("--port")
+                    .arg(port.to_string())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::piped())
+                    .spawn()
+            })
+            .map_err(|e| {
+                crate::launch_error(
+                    app_handle,
+                    "spawn_failed",
+                    "Failed to spawn lldb-dap process",
+                    e,
+                )
+            })
+        });
+        let (lldb_port, mut child) = match spawned {
+            Ok(spawned) => spawned,
+            Err(e) => {
+                crate::processes::kill_tree(&mut replay_child);
+                return Err(e);
+            }
+        };
+        info!("Using port {} for lldb-dap", lldb_port);
+        crate::forward_child_output(app_handle, debug_state, &mut child, "lldb-dap");
+        *debug_state.rr_trace.lock() = Some(trace);
+
+        Ok(PreparedLaunch {
+            process: Some(child),
+            helper_processes: vec![replay_child],
+            connection: Connection::Port(lldb_port),
+            request: "attach",
+            arguments: attach_arguments(&resolved_path, replay_port),
+            target: "rr replay".to_string(),
+            label: "rr replay".to_string(),
+            summary: "rr replay session launched successfully".to_string(),
+        })
+    }
+
+    fn console_evaluate(&self, expression: &str, paused: bool) -> (String, &'static str) {
+        (lldb_expression(expression), console_context(paused))
+    }
+
+    fn evaluate_result(&self, result: &str) -> Option<String> {
+        Some(parse_lldb_result(result))
+    }
+}
+
+// A recording's trace directory, deleted when dropped: with the session that replays it, or
+// with a launch that failed or was cancelled.
+pub struct RrTrace {
+    dir: PathBuf,
+}
+
+impl RrTrace {
+    fn new() -> Self {
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        RrTrace {
+            dir: std::env::temp_dir().join(format!("wayfind-rr-{}", stamp)),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl Drop for RrTrace {
+    fn drop(&mut self) {
+        match fs::remove_dir_all(&self.dir) {
+            Ok(()) => info!("Removed rr trace {}", self.dir.display()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => warn!("Failed to remove rr trace {}: {}", self.dir.display(), e),
+        }
+    }
+}
+
+// Records `program` with the launch config's args and env under rr into a fresh trace
+// directory, streaming its output to the console as it runs. The program's own exit status is
+// not treated as a failure, since recording a crash is the whole point. A cancelled launch
+// kills the recording.
+pub fn record(context: &LaunchContext, program: &Path, cwd: &Path) -> Result<RrTrace, String> {
+    let launch_config = context.launch_config;
+    let trace = RrTrace::new();
+    let mut child = processes::own_process_group(&mut Command::new("rr"))
+        .arg("record")
+        .arg("--output-trace-dir")
+        .arg(trace.dir())
+        .arg(program)
+        .args(&launch_config.args)
+        .envs(&launch_config.env)
+        .current_dir(cwd)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run rr record (is rr installed?): {}", e))?;
+    crate::forward_child_output(
+        context.app_handle,
+        context.debug_state,
+        &mut child,
+        "rr record",
+    );
+
+    // The recording can take as long as the program runs, so the runtime moves its other tasks
+    // off this worker while it's waited on
+    tokio::task::block_in_place(|| loop {
+        match child.try_wait() {
+            Ok(Some(_)) => return Ok(()),
+            Ok(None) => {}
+            Err(e) => return Err(format!("Failed to wait for rr record: {}", e)),
+        }
+        if context.progress.is_cancelled() {
+            processes::kill_tree(&mut child);
+            return Err(CANCELLED_MESSAGE.to_string());
+        }
+        thread::sleep(Duration::from_millis(100));
+    })?;
+
+    if !trace.dir().exists() {
+        return Err("rr record did not produce a trace; see its output in the console".to_string());
+    }
+    Ok(trace)
+}
+
+// Starts rr's gdb-remote server replaying `trace_dir` on `port`. The server keeps listening after
+// the debugger disconnects so the recording can be revisited.
+pub fn spawn_replay_server(trace_dir: &Path, port: u16) -> io::Result<Child> {
+    crate::processes::own_process_group(&mut Command::new("rr"))
+        .arg("replay")
+        .arg(format!("--dbgport={}", port))
+        .arg("--keep-listening")
+        .arg(trace_dir)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+}
+
+// lldb-dap attach arguments that connect to a replay server instead of a live process.
+pub fn attach_arguments(program: &Path, port: u16) -> serde_json::Value {
+    serde_json::json!({
+        "program": program.to_string_lossy(),
+        "attachCommands": [format!("gdb-remote 127.0.0.1:{}", port)],
+    })
+}
@@ -0,0 +1,344 @@
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::debugger::client::{BreakpointInput, Checksum, DAPClient, DAPMessage};
+
+// Bumped if the exported file layout changes incompatibly.
+const BREAKPOINT_FILE_VERSION: u32 = 1;
+
+// The breakpoints the user has set, by file. This outlives individual sessions so breakpoints
+// can be re-applied after a restart or relaunch.
+#[derive(Debug, Default, Clone)]
+pub struct BreakpointStore {
+    by_file: BTreeMap<String, Vec<BreakpointInput>>,
+    // Hits so far of breakpoints with disableAfterHits, by file and line
+    hits: HashMap<(String, u32), u32>,
+    // Breakpoints that reached their disableAfterHits; left out of what the adapter gets
+    disabled: BTreeSet<(String, u32)>,
+    // Adapter breakpoint ids from the latest setBreakpoints responses
+    adapter_ids: HashMap<i64, (String, u32)>,
+    // Files the editor has unsaved changes in, reported as sourceModified
+    modified: BTreeSet<String>,
+    // SHA256 of each file when the session first sent its breakpoints, standing in for the
+    // version of the source that is running
+    loaded: HashMap<String, String>,
+}
+
+// One breakpoint in an exported file. `path` is relative to the project root (with `/`
+// separators) when the source lives under it, so files can be shared between machines.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortableBreakpoint {
+    pub path: String,
+    #[serde(flatten)]
+    pub breakpoint: BreakpointInput,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakpointFile {
+    pub version: u32,
+    pub breakpoints: Vec<PortableBreakpoint>,
+}
+
+fn to_portable_path(file: &str, root: &Path) -> String {
+    match Path::new(file).strip_prefix(root) {
+        Ok(relative) => relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+        Err(_) => file.to_string(),
+    }
+}
+
+fn from_portable_path(path: &str, root: &Path) -> String {
+    let candidate = Path::new(path);
+    if candidate.is_absolute() {
+        return path.to_string();
+    }
+    let mut resolved = PathBuf::from(root);
+    for part in path.split('/') {
+        match Path::new(part).components().next() {
+            Some(Component::ParentDir) => {
+                resolved.pop();
+            }
+            Some(Component::Normal(_)) => resolved.push(part),
+            _ => {}
+        }
+    }
+    resolved.to_string_lossy().into_owned()
+}
+
+impl BreakpointStore {
+    // Replaces the breakpoints for `file`, mirroring setBreakpoints semantics.
+    // Setting a file's breakpoints again starts its hit counts over.
+    pub fn set(&mut self, file: &str, breakpoints: Vec<BreakpointInput>) {
+        self.hits.retain(|(f, _), _| f != file);
+        self.disabled.retain(|(f, _)| f != file);
+        self.adapter_ids.retain(|_, (f, _)| f != file);
+        if breakpoints.is_empty() {
+            self.by_file.remove(file);
+        } else {
+            self.by_file.insert(file.to_string(), breakpoints);
+        }
+    }
+
+    // Moves breakpoints after `from` was renamed to `to`. `from` may be a file or a directory,
+    // in which case every file under it moves. Hit counts and disabled breakpoints move along;
+    // adapter ids don't, since the breakpoints have to be sent again under the new path.
+    // Returns the (old, new) paths of the files that moved.
+    pub fn rename(&mut self, from: &str, to: &str) -> Vec<(String, String)> {
+        let renamed_path = |file: &str| -> Option<String> {
+            let rest = Path::new(file).strip_prefix(from).ok()?;
+            let renamed = if rest.as_os_str().is_empty() {
+                PathBuf::from(to)
+            } else {
+                Path::new(to).join(rest)
+            };
+            Some(renamed.to_string_lossy().into_owned())
+        };
+        let moves: Vec<(String, String)> = self
+            .by_file
+            .keys()
+            .filter_map(|file| renamed_path(file).map(|renamed| (file.clone(), renamed)))
+            .collect();
+
+        for (old, new) in &moves {
+            if let Some(breakpoints) = self.by_file.remove(old) {
+                self.by_file.insert(new.clone(), breakpoints);
+            }
+            self.adapter_ids.retain(|_, (f, _)| f != old);
+        }
+        let moved = |file: &String| {
+            moves
+                .iter()
+                .find(|(old, _)| old == file)
+                .map(|(_, new)| new.clone())
+        };
+        self.hits = std::mem::take(&mut self.hits)
+            .into_iter()
+            .map(|((file, line), hits)| ((moved(&file).unwrap_or(file), line), hits))
+            .collect();
+        self.disabled = std::mem::take(&mut self.disabled)
+            .into_iter()
+            .map(|(file, line)| (moved(&file).unwrap_or(file), line))
+            .collect();
+        self.modified = std::mem::take(&mut self.modified)
+            .into_iter()
+            .map(|file| moved(&file).unwrap_or(file))
+            .collect();
+        self.loaded = std::mem::take(&mut self.loaded)
+            .into_iter()
+            .map(|(file, hash)| (moved(&file).unwrap_or(file), hash))
+            .collect();
+        moves
+    }
+
+    // Records whether the editor has unsaved changes in `file`.
+    pub fn set_modified(&mut self, file: &str, modified: bool) {
+        if modified {
+            self.modified.insert(file.to_string());
+        } else {
+            self.modified.remove(file);
+        }
+    }
+
+    // Whether `file` on disk (hashed as `disk_hash`) differs from the version the session loaded.
+    // False for files the session hasn't sent breakpoints for yet.
+    pub fn changed_since_load(&self, file: &str, disk_hash: Option<&str>) -> bool {
+        match (self.loaded.get(file), disk_hash) {
+            (Some(loaded), Some(disk)) => loaded != disk,
+            _ => false,
+        }
+    }
+
+    // Forgets the loaded versions when a new session starts.
+    pub fn clear_loaded(&mut self) {
+        self.loaded.clear();
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<String, Vec<BreakpointInput>> {
+        self.by_file.clone()
+    }
+
+    // The breakpoints of `file` the adapter should have: all but the auto-disabled ones.
+    pub fn active(&self, file: &str) -> Vec<BreakpointInput> {
+        self.by_file
+            .get(file)
+            .map(|breakpoints| {
+                breakpoints
+                    .iter()
+                    .filter(|bp| !self.disabled.contains(&(file.to_string(), bp.line)))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // Lines of `file` switched off by disableAfterHits.
+    pub fn disabled_lines(&self, file: &str) -> Vec<u32> {
+        self.disabled
+            .iter()
+            .filter(|(f, _)| f == file)
+            .map(|(_, line)| *line)
+            .collect()
+    }
+
+    // Maps the ids in a setBreakpoints response body back to lines. The adapter answers in
+    // request order, so the response lines up with `active(file)`.
+    pub fn record_adapter_ids(&mut self, file: &str, body: &serde_json::Value) {
+        self.adapter_ids.retain(|_, (f, _)| f != file);
+        let Some(results) = body.get("breakpoints").and_then(|b| b.as_array()) else {
+            return;
+        };
+        for (breakpoint, result) in self.active(file).iter().zip(results) {
+            if let Some(id) = result.get("id").and_then(|id| id.as_i64()) {
+                self.adapter_ids
+                    .insert(id, (file.to_string(), breakpoint.line));
+            }
+        }
+    }
+
+    // Counts a stop at the given adapter breakpoint ids. Returns the files whose breakpoints
+    // must be re-sent because one of them just reached its disableAfterHits.
+    pub fn record_hits(&mut self, ids: &[i64]) -> BTreeSet<String> {
+        let mut changed = BTreeSet::new();
+        for id in ids {
+            let Some((file, line)) = self.adapter_ids.get(id).cloned() else {
+                continue;
+            };
+            let limit = self
+                .by_file
+                .get(&file)
+                .and_then(|bps| bps.iter().find(|bp| bp.line == line))
+                .and_then(|bp| bp.disable_after_hits);
+            let Some(limit) = limit else {
+                continue;
+            };
+
+            let key = (file.clone(), line);
+            let hits = self.hits.entry(key.clone()).or_insert(0);
+            *hits += 1;
+            if *hits >= limit && self.disabled.insert(key) {
+                changed.insert(file);
+            }
+        }
+        changed
+    }
+
+    pub fn export(&self, root: &Path) -> BreakpointFile {
+        let breakpoints = self
+            .by_file
+            .iter()
+            .flat_map(|(file, breakpoints)| {
+                let path = to_portable_path(file, root);
+                breakpoints
+                    .iter()
+                    .map(move |breakpoint| PortableBreakpoint {
+                        path: path.clone(),
+                        breakpoint: breakpoint.clone(),
+                    })
+            })
+            .collect();
+
+        BreakpointFile {
+            version: BREAKPOINT_FILE_VERSION,
+            breakpoints,
+        }
+    }
+
+    // Replaces the breakpoints of every file mentioned in `file` and returns the imported set,
+    // keyed by resolved path.
+    pub fn import(
+        &mut self,
+        file: BreakpointFile,
+        root: &Path,
+    ) -> Result<BTreeMap<String, Vec<BreakpointInput>>, String> {
+        if file.version > BREAKPOINT_FILE_VERSION {
+            return Err(format!(
+                "Unsupported breakpoint file version {} (expected {} or lower)",
+                file.version, BREAKPOINT_FILE_VERSION
+            ));
+        }
+
+        let mut imported: BTreeMap<String, Vec<BreakpointInput>> = BTreeMap::new();
+        for portable in file.breakpoints {
+            imported
+                .entry(from_portable_path(&portable.path, root))
+                .or_default()
+                .push(portable.breakpoint);
+        }
+        for (path, breakpoints) in &imported {
+            self.set(path, breakpoints.clone());
+        }
+        Ok(imported)
+    }
+}
+
+// Hex SHA256 of `file` as it is on disk, None if it can't be read. Editors report their
+// buffers' hashes in the same form.
+pub fn disk_hash(file: &str) -> Option<String> {
+    let content = fs::read(file).ok()?;
+    let digest = Sha256::digest(&content);
+    Some(digest.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+// The checksum of `file` as it is on disk. Empty if the file can't be read, in which case the
+// adapter has nothing to compare against.
+pub fn checksums(file: &str) -> Vec<Checksum> {
+    disk_hash(file)
+        .map(|checksum| Checksum {
+            algorithm: "SHA256".to_string(),
+            checksum,
+        })
+        .into_iter()
+        .collect()
+}
+
+// Sends the breakpoints the adapter should have for `file`, along with the file's checksum and
+// whether it has unsaved changes, so the adapter can warn when the running code doesn't match.
+pub async fn send(
+    client: &DAPClient,
+    store: &RwLock<BreakpointStore>,
+    file: &str,
+) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+    send_with_entry(client, store, file, None).await
+}
+
+// Like send, with a break-at-entry breakpoint on `entry` after the user's own, unless one of
+// theirs is already on that line. It comes last, so the response still lines up with the store.
+pub async fn send_with_entry(
+    client: &DAPClient,
+    store: &RwLock<BreakpointStore>,
+    file: &str,
+    entry: Option<u32>,
+) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+    let checksums = checksums(file);
+    let (mut active, source_modified) = {
+        let mut store = store.write();
+        if let Some(checksum) = checksums.first() {
+            store
+                .loaded
+                .entry(file.to_string())
+                .or_insert_with(|| checksum.checksum.clone());
+        }
+        (store.active(file), store.modified.contains(file))
+    };
+    if let Some(line) = entry {
+        if !active.iter().any(|bp| bp.line == line) {
+            active.push(BreakpointInput {
+                line,
+                condition: None,
+                hit_condition: None,
+                log_message: None,
+                disable_after_hits: None,
+            });
+        }
+    }
+    client
+        .set_breakpoints_checked(file.to_string(), active, checksums, source_modified)
+        .await
+}
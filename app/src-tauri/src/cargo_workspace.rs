@@ -0,0 +1,347 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+use crate::launch_progress::CANCELLED_MESSAGE;
+use crate::processes::{self, Wait};
+
+// How long a build may run before it's killed
+const BUILD_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceMember {
+    pub name: String,
+    pub manifest_path: String,
+    pub bins: Vec<String>,
+    pub examples: Vec<String>,
+    // Integration tests (tests/*.rs)
+    pub tests: Vec<String>,
+    pub default_run: Option<String>,
+}
+
+// Which kind of cargo target a Rust launch builds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CargoTargetKind {
+    #[default]
+    Bin,
+    Example,
+    Test,
+}
+
+impl CargoTargetKind {
+    fn flag(self) -> &'static str {
+        match self {
+            CargoTargetKind::Bin => "--bin",
+            CargoTargetKind::Example => "--example",
+            CargoTargetKind::Test => "--test",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceLayout {
+    pub workspace_root: String,
+    pub target_directory: String,
+    pub members: Vec<WorkspaceMember>,
+}
+
+// A compiler error or warning from a failed build, located so the editor can jump to it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildDiagnostic {
+    // "error", "warning", ...
+    pub level: String,
+    pub message: String,
+    // Absolute path of the primary span's file, when there is one
+    pub file: Option<String>,
+    pub line: Option<u64>,
+    pub column: Option<u64>,
+    // rustc's full human-readable rendering
+    pub rendered: Option<String>,
+}
+
+// Why build_binary failed. `diagnostics` holds the compiler errors when the build itself failed.
+#[derive(Debug, Clone)]
+pub struct BuildError {
+    pub message: String,
+    pub diagnostics: Vec<BuildDiagnostic>,
+}
+
+impl std::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl From<String> for BuildError {
+    fn from(message: String) -> Self {
+        BuildError {
+            message,
+            diagnostics: Vec::new(),
+        }
+    }
+}
+
+// Reads the workspace at `root` with `cargo metadata`, keeping only workspace members.
+pub fn workspace_layout(root: &Path) -> Result<WorkspaceLayout, String> {
+    let output = Command::new("cargo")
+        .args(["metadata", "--format-version", "1", "--no-deps"])
+        .current_dir(root)
+        .output()
+        .map_err(|e| format!("Failed to run cargo metadata: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "cargo metadata failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let metadata: Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Invalid cargo metadata output: {}", e))?;
+
+    let member_ids: Vec<&str> = metadata
+        .get("workspace_members")
+        .and_then(|m| m.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|id| id.as_str())
+        .collect();
+
+    let members = metadata
+        .get("packages")
+        .and_then(|p| p.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|pkg| {
+            pkg.get("id")
+                .and_then(|id| id.as_str())
+                .map(|id| member_ids.contains(&id))
+                .unwrap_or(false)
+        })
+        .map(|pkg| WorkspaceMember {
+            name: str_field(pkg, "name"),
+            manifest_path: str_field(pkg, "manifest_path"),
+            bins: target_names(pkg, "bin"),
+            examples: target_names(pkg, "example"),
+            tests: target_names(pkg, "test"),
+            default_run: pkg
+                .get("default_run")
+                .and_then(|d| d.as_str())
+                .map(String::from),
+        })
+        .collect();
+
+    Ok(WorkspaceLayout {
+        workspace_root: str_field(&metadata, "workspace_root"),
+        target_directory: str_field(&metadata, "target_directory"),
+        members,
+    })
+}
+
+// Builds `package`'s binary (or the example or integration test `bin` names) with cargo and
+// returns the path of the produced executable. Without an explicit `bin`, the package's
+// default-run or only binary is used. Blocks until cargo is done; once `cancelled` returns true,
+// cargo is killed and the build fails with CANCELLED_MESSAGE.
+pub fn build_binary(
+    root: &Path,
+    package: &str,
+    bin: Option<&str>,
+    kind: CargoTargetKind,
+    cancelled: impl Fn() -> bool,
+) -> Result<PathBuf, BuildError> {
+    let bin = match bin {
+        Some(bin) => bin.to_string(),
+        None if kind != CargoTargetKind::Bin => {
+            return Err(format!("Name the {:?} target of {} to build", kind, package).into())
+        }
+        None => {
+            let layout = workspace_layout(root)?;
+            let member = layout
+                .members
+                .into_iter()
+                .find(|m| m.name == package)
+                .ok_or_else(|| format!("Package {} is not a workspace member", package))?;
+            match (member.default_run, member.bins.as_slice()) {
+                (Some(default_run), _) => default_run,
+                (None, [only]) => only.clone(),
+                (None, []) => return Err(format!("Package {} has no binaries", package).into()),
+                (None, bins) => {
+                    return Err(format!(
+                        "Package {} has several binaries ({}); pick one",
+                        package,
+                        bins.join(", ")
+                    )
+                    .into())
+                }
+            }
+        }
+    };
+
+    tracing::info!("Building cargo package {} ({:?} {})", package, kind, bin);
+    let mut child = processes::own_process_group(&mut Command::new("cargo"))
+        .args(["build", "--message-format=json", "-p"])
+        .arg(package)
+        .arg(kind.flag())
+        .arg(&bin)
+        .current_dir(root)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run cargo build: {}", e))?;
+
+    // Both pipes are drained while cargo runs so neither can fill up and stall it
+    let stdout = read_in_background(child.stdout.take());
+    let stderr = read_in_background(child.stderr.take());
+    let wait = processes::wait_killable(&mut child, BUILD_TIMEOUT, cancelled)
+        .map_err(|e| format!("Failed to wait for cargo build: {}", e))?;
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+    let status = match wait {
+        Wait::Exited(status) => status,
+        Wait::Cancelled => return Err(CANCELLED_MESSAGE.to_string().into()),
+        Wait::TimedOut => {
+            return Err(format!(
+                "cargo build timed out after {} minutes",
+                BUILD_TIMEOUT.as_secs() / 60
+            )
+            .into())
+        }
+    };
+
+    let messages: Vec<Value> = stdout
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .collect();
+
+    if !status.success() {
+        let diagnostics: Vec<BuildDiagnostic> = messages
+            .iter()
+            .filter_map(parse_diagnostic)
+            .filter(|d| d.level == "error")
+            .collect();
+        // Without compiler errors the failure is cargo's own (bad manifest, missing package, ...)
+        let summary = if diagnostics.is_empty() {
+            stderr.trim().to_string()
+        } else {
+            diagnostics
+                .iter()
+                .filter_map(|d| d.rendered.as_deref())
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        return Err(BuildError {
+            message: format!("cargo build failed: {}", summary),
+            diagnostics,
+        });
+    }
+
+    messages
+        .into_iter()
+        .filter(|msg| msg.get("reason").and_then(|r| r.as_str()) == Some("compiler-artifact"))
+        .filter(|msg| {
+            msg.get("target")
+                .and_then(|t| t.get("name"))
+                .and_then(|n| n.as_str())
+                == Some(bin.as_str())
+        })
+        .find_map(|msg| {
+            msg.get("executable")
+                .and_then(|e| e.as_str())
+                .map(PathBuf::from)
+        })
+        .ok_or_else(|| format!("cargo build did not report an executable for {}", bin).into())
+}
+
+// Collects everything written to `pipe` on a separate thread.
+fn read_in_background(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut bytes);
+        }
+        String::from_utf8_lossy(&bytes).into_owned()
+    })
+}
+
+// Reads a `compiler-message` line of cargo's JSON output, locating it at its primary span.
+fn parse_diagnostic(msg: &Value) -> Option<BuildDiagnostic> {
+    if msg.get("reason").and_then(|r| r.as_str()) != Some("compiler-message") {
+        return None;
+    }
+    let message = msg.get("message")?;
+    let primary = message
+        .get("spans")
+        .and_then(|s| s.as_array())
+        .and_then(|spans| {
+            spans
+                .iter()
+                .find(|span| span.get("is_primary").and_then(|p| p.as_bool()) == Some(true))
+        });
+
+    // Span paths are relative to the package's manifest directory
+    let file = primary
+        .and_then(|span| span.get("file_name"))
+        .and_then(|f| f.as_str())
+        .map(|file| {
+            let manifest_dir = msg
+                .get("manifest_path")
+                .and_then(|m| m.as_str())
+                .and_then(|m| Path::new(m).parent());
+            match manifest_dir {
+                Some(dir) if Path::new(file).is_relative() => {
+                    dir.join(file).to_string_lossy().to_string()
+                }
+                _ => file.to_string(),
+            }
+        });
+
+    Some(BuildDiagnostic {
+        level: str_field(message, "level"),
+        message: str_field(message, "message"),
+        file,
+        line: primary
+            .and_then(|span| span.get("line_start"))
+            .and_then(|l| l.as_u64()),
+        column: primary
+            .and_then(|span| span.get("column_start"))
+            .and_then(|c| c.as_u64()),
+        rendered: message
+            .get("rendered")
+            .and_then(|r| r.as_str())
+            .map(String::from),
+    })
+}
+
+fn str_field(value: &Value, key: &str) -> String {
+    value
+        .get(key)
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn target_names(package: &Value, kind: &str) -> Vec<String> {
+    package
+        .get("targets")
+        .and_then(|t| t.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|target| {
+            target
+                .get("kind")
+                .and_then(|k| k.as_array())
+                .map(|kinds| kinds.iter().any(|k| k.as_str() == Some(kind)))
+                .unwrap_or(false)
+        })
+        .map(|target| str_field(target, "name"))
+        .collect()
+}
@@ -0,0 +1,142 @@
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Expressions kept per project
+const MAX_ENTRIES: usize = 500;
+// History of consoles used without an open workspace
+const NO_PROJECT: &str = "";
+
+// One project's history file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct ProjectHistory {
+    project: String,
+    entries: Vec<String>,
+}
+
+// Debug console input persisted per project in the app data directory, one JSON file per
+// project, so diagnostic expressions survive restarts.
+pub struct ConsoleHistoryStore {
+    dir: Option<PathBuf>,
+    projects: RwLock<HashMap<String, Vec<String>>>,
+}
+
+impl ConsoleHistoryStore {
+    // Without a directory the history only lives in memory.
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        ConsoleHistoryStore {
+            dir,
+            projects: RwLock::new(HashMap::new()),
+        }
+    }
+
+    // Appends `expression` to the project's history, moving it to the end if it was already
+    // there, and writes the history to disk.
+    pub fn record(&self, project: Option<&Path>, expression: &str) -> Result<(), String> {
+        let key = project_key(project);
+        self.ensure_loaded(&key);
+        let entries = {
+            let mut projects = self.projects.write();
+            let entries = projects.entry(key.clone()).or_default();
+            entries.retain(|e| e != expression);
+            entries.push(expression.to_string());
+            let excess = entries.len().saturating_sub(MAX_ENTRIES);
+            entries.drain(..excess);
+            entries.clone()
+        };
+        self.save(&key, entries)
+    }
+
+    // The project's history, oldest first. With `query`, only entries containing it
+    // (case-insensitively); with `limit`, only the most recent ones.
+    pub fn entries(
+        &self,
+        project: Option<&Path>,
+        query: Option<&str>,
+        limit: Option<usize>,
+    ) -> Vec<String> {
+        let key = project_key(project);
+        self.ensure_loaded(&key);
+        let query = query.map(str::to_lowercase).filter(|q| !q.is_empty());
+        let matching: Vec<String> = self
+            .projects
+            .read()
+            .get(&key)
+            .into_iter()
+            .flatten()
+            .filter(|e| match &query {
+                Some(query) => e.to_lowercase().contains(query),
+                None => true,
+            })
+            .cloned()
+            .collect();
+        let skip = limit.map_or(0, |limit| matching.len().saturating_sub(limit));
+        matching.into_iter().skip(skip).collect()
+    }
+
+    pub fn clear(&self, project: Option<&Path>) -> Result<(), String> {
+        let key = project_key(project);
+        self.projects.write().insert(key.clone(), Vec::new());
+        self.save(&key, Vec::new())
+    }
+
+    fn ensure_loaded(&self, key: &str) {
+        if self.projects.read().contains_key(key) {
+            return;
+        }
+        let entries = self
+            .file(key)
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(
+                |content| match serde_json::from_str::<ProjectHistory>(&content) {
+                    Ok(history) => Some(history.entries),
+                    Err(e) => {
+                        tracing::warn!("Ignoring invalid console history: {}", e);
+                        None
+                    }
+                },
+            )
+            .unwrap_or_default();
+        self.projects
+            .write()
+            .entry(key.to_string())
+            .or_insert(entries);
+    }
+
+    fn save(&self, key: &str, entries: Vec<String>) -> Result<(), String> {
+        let Some(path) = self.file(key) else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create console history directory: {}", e))?;
+        }
+        let history = ProjectHistory {
+            project: key.to_string(),
+            entries,
+        };
+        let json = serde_json::to_string_pretty(&history)
+            .map_err(|e| format!("Failed to serialize console history: {}", e))?;
+        fs::write(&path, json).map_err(|e| format!("Failed to write console history: {}", e))
+    }
+
+    // Files are named after an FNV-1a hash of the project path (stable across builds, unlike
+    // std's hasher), which is stored inside for reference.
+    fn file(&self, key: &str) -> Option<PathBuf> {
+        let hash = key.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+        self.dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{:016x}.json", hash)))
+    }
+}
+
+fn project_key(project: Option<&Path>) -> String {
+    project
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|| NO_PROJECT.to_string())
+}
@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::environment::decode_hex;
+
+// Python types get_data_preview understands, by the type name debugpy reports for variables.
+const PREVIEWABLE_TYPES: &[&str] = &["ndarray", "DataFrame", "Series"];
+
+// A shaped look at an array or data frame: its dimensions and element types plus the first rows,
+// instead of the `<DataFrame ...>` repr.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DataPreview {
+    // "ndarray", "dataframe", "series" or "unsupported"
+    pub kind: String,
+    // Fully qualified type of the value, e.g. "pandas.core.frame.DataFrame"
+    pub type_name: String,
+    pub shape: Vec<i64>,
+    // Element type of an array or series
+    pub dtype: Option<String>,
+    // Data frame columns and their types, up to the column limit
+    pub columns: Vec<String>,
+    pub dtypes: Vec<String>,
+    // Index labels of the previewed rows (frames and series)
+    pub index: Vec<String>,
+    // The first rows as nested lists; NaN and infinities come through as strings
+    pub rows: Value,
+    // Rows or columns were left out
+    pub truncated: bool,
+}
+
+pub fn is_previewable_type(type_name: &str) -> bool {
+    PREVIEWABLE_TYPES.contains(&type_name)
+}
+
+// debugpy: a single expression summarizing `expression` as hex-encoded JSON (see environment.rs
+// for why hex), keeping at most `rows` rows and `columns` columns.
+pub fn python_expression(expression: &str, rows: usize, columns: usize) -> String {
+    format!(
+        "(lambda v, json=__import__('json'), n={rows}, m={columns}, \
+         clean=lambda x, c: [c(i, c) for i in x] if isinstance(x, list) else \
+         (str(x) if isinstance(x, float) and (x != x or abs(x) == float('inf')) else x): \
+         json.dumps(dict(\
+         {{'kind': 'dataframe', 'shape': list(v.shape), \
+         'columns': [str(c) for c in v.columns[:m]], 'dtypes': [str(d) for d in v.dtypes[:m]], \
+         'index': [str(i) for i in v.index[:n]], \
+         'rows': clean(v.iloc[:n, :m].astype(object).values.tolist(), clean), \
+         'truncated': v.shape[0] > n or v.shape[1] > m}} \
+         if type(v).__name__ == 'DataFrame' else \
+         {{'kind': 'series', 'shape': list(v.shape), 'dtype': str(v.dtype), \
+         'columns': [str(v.name)], 'index': [str(i) for i in v.index[:n]], \
+         'rows': clean([[x] for x in v.iloc[:n].astype(object).tolist()], clean), \
+         'truncated': v.shape[0] > n}} \
+         if type(v).__name__ == 'Series' else \
+         {{'kind': 'ndarray', 'shape': list(v.shape), 'dtype': str(v.dtype), \
+         'rows': clean(v[(slice(n),) + (slice(m),) * (v.ndim - 1)].tolist() if v.ndim else [v.tolist()], clean), \
+         'truncated': v.ndim > 0 and (v.shape[0] > n or any(d > m for d in v.shape[1:]))}} \
+         if type(v).__name__ == 'ndarray' else {{'kind': 'unsupported'}}, \
+         typeName=type(v).__module__ + '.' + type(v).__qualname__), default=str).encode().hex())\
+         ({expression})",
+        rows = rows,
+        columns = columns,
+        expression = expression,
+    )
+}
+
+// Decodes the expression's result.
+pub fn parse(result: &str) -> Result<DataPreview, String> {
+    let hex = result.trim().trim_matches('\'');
+    let json = decode_hex(hex).ok_or_else(|| format!("Unexpected output: {}", result.trim()))?;
+    serde_json::from_slice(&json).map_err(|e| format!("Failed to parse data preview: {}", e))
+}
+
+// Flags DAP Variable objects get_data_preview can show, so the UI can offer it.
+pub fn mark_previewable(variables: &mut [Value]) {
+    for variable in variables.iter_mut() {
+        let previewable = variable
+            .get("type")
+            .and_then(|t| t.as_str())
+            .is_some_and(is_previewable_type);
+        if let (true, Value::Object(map)) = (previewable, variable) {
+            map.insert("hasDataPreview".to_string(), Value::Bool(true));
+        }
+    }
+}
@@ -1,11 +1,53 @@
-use parking_lot::RwLock;
-use std::process::Child;
-use std::sync::atomic::AtomicU64;
+use parking_lot::{Mutex as SyncMutex, RwLock};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::process::{Child, ExitStatus};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+use crate::adapters::quirks::AdapterVersion;
+use crate::adapters::rr::RrTrace;
+use crate::breakpoints::BreakpointStore;
 // Import your updated DAPClient from your debugger client module.
-use crate::debugger::client::DAPClient;
+use crate::debugger::client::{
+    Capabilities, DAPClient, DAPMessage, FunctionBreakpointInput, RequestCanceller,
+};
+use crate::debugger::metrics::DapMetrics;
+use crate::debugger::protocol::{self, ThreadsResponseBody};
+use crate::debugger::remote::AdapterEndpoint;
+use crate::exception_filters::ExceptionFilters;
+use crate::launch_config::LaunchConfig;
+use crate::launch_progress::LaunchCancel;
+use crate::pty::PtyConsole;
+use crate::sampler::PySpySampler;
+use crate::session_summary::{self, SessionSummary};
+use crate::sources::AdapterSource;
+use crate::stepping::SteppingController;
+use crate::stop_history::StopHistory;
+use crate::timeline::SessionTimeline;
+use crate::value_format::FormatLimits;
+use crate::value_watch::ValueWatch;
+use dap_client::DapEvent;
+
+// Functions a Rust panic passes through, in the order it reaches them:
+// - core's panic_fmt, where every formatted panic starts, including in panic = "abort" builds
+//   and in core or no_std code
+// - rust_begin_unwind, the panic handler (std's, or a no_std crate's #[panic_handler])
+// - rust_panic, where std starts unwinding once the panic hook has run; `panic_any` payloads
+//   get here directly
+// Stopping in any of them leaves the full stack of the panic site intact. A panic stops at the
+// first one it reaches and is continued past the rest.
+const RUST_PANIC_FUNCTIONS: &[&str] = &[
+    "core::panicking::panic_fmt",
+    "rust_begin_unwind",
+    "rust_panic",
+];
+
+// Console entries kept in memory per app run.
+const MAX_CONSOLE_HISTORY: usize = 500;
+// Program output lines kept per session for session reports.
+const MAX_CAPTURED_OUTPUT: usize = 1000;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum DebuggerState {
@@ -16,9 +58,112 @@ pub enum DebuggerState {
     Terminated,
 }
 
+impl DebuggerState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DebuggerState::NotStarted => "notStarted",
+            DebuggerState::Configuring => "configuring",
+            DebuggerState::Running => "running",
+            DebuggerState::Paused { .. } => "paused",
+            DebuggerState::Terminated => "terminated",
+        }
+    }
+}
+
+// How stop_session ends a session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StopMode {
+    // Terminate the debuggee through the adapter
+    #[default]
+    Stop,
+    // End debugging and leave the debuggee running
+    Detach,
+    // Kill the debuggee and the adapter without asking the adapter first, for hung sessions
+    ForceKill,
+}
+
+// The debuggee as reported by the adapter's "process" event.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebuggeeProcess {
+    pub name: String,
+    pub system_process_id: Option<i64>,
+    pub start_method: Option<String>,
+    pub is_local_process: Option<bool>,
+}
+
+// A line of program output as emitted on "console-line". stdout and stderr are read on separate
+// threads, so `seq` is what puts them back in the order they were captured.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsoleLine {
+    pub session: String,
+    pub seq: u64,
+    // Unix time in milliseconds the line was captured
+    pub timestamp: u64,
+    // "stdout" or "stderr"
+    pub stream: String,
+    pub text: String,
+}
+
+// A live debuggee thread, kept up to date from "thread" events and "threads" responses.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadInfo {
+    pub id: i64,
+    pub name: Option<String>,
+    // Suspended with freeze_thread; stays put when the rest of the program resumes
+    pub frozen: bool,
+    // Stopped and inspectable, per the adapter's stopped and continued reports
+    pub paused: bool,
+}
+
+// The arguments of the most recent launch_debug_session call, replayed by hot_restart.
+#[derive(Debug, Clone, Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchRequest {
+    pub script_path: String,
+    pub debug_engine: String,
+    pub launch_config: LaunchConfig,
+}
+
+// The frame the user had selected in the call stack, by position rather than frame id since
+// ids don't survive a restart.
+#[derive(Debug, Clone, Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameSelection {
+    pub name: String,
+    pub file: Option<String>,
+    pub line: i64,
+}
+
+// Snapshot of the session returned to the frontend by get_session_info.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInfo {
+    pub token: String,
+    // The session whose adapter asked for this one with startDebugging
+    pub parent: Option<String>,
+    pub debugger_type: Option<String>,
+    pub state: String,
+    pub current_thread_id: Option<i64>,
+    pub current_thread_name: Option<String>,
+    pub process: Option<DebuggeeProcess>,
+    pub adapter: Option<AdapterVersion>,
+}
+
 pub struct DebugSessionState {
+    // Identifies the session among concurrently running ones (see SessionManager)
+    pub token: String,
+    // Set on child sessions started by the adapter (see start_child_session)
+    pub parent: RwLock<Option<String>>,
     pub client: Mutex<Option<DAPClient>>,
+    // Cancels the client's requests without waiting for the client lock
+    pub canceller: SyncMutex<Option<RequestCanceller>>,
     pub process: Mutex<Option<Child>>,
+    // Other processes owned by the session (e.g. rr's replay server), killed on terminate
+    pub helper_processes: Mutex<Vec<Child>>,
     // Wrap in Arc
     pub status_seq: Arc<AtomicU64>,
     pub state: RwLock<DebuggerState>,
@@ -26,57 +171,538 @@ pub struct DebugSessionState {
     pub debugger_type: RwLock<Option<String>>,
     // Field to store the current thread id when stopped
     pub current_thread_id: RwLock<Option<i64>>,
+    // Instruction pointer of the top frame at the last stop, for the disassembly view
+    pub current_instruction_pointer: RwLock<Option<String>>,
+    // The debuggee process, once the adapter has reported it
+    pub debuggee_process: RwLock<Option<DebuggeeProcess>>,
+    // Live threads keyed by thread id
+    pub threads: RwLock<BTreeMap<i64, ThreadInfo>>,
+    // A threads request is scheduled for thread-started events; later ones wait for it
+    pub thread_refresh_pending: AtomicBool,
+    // Threads suspended by the user in lldb sessions
+    pub frozen_threads: RwLock<BTreeSet<i64>>,
+    // Threads stopped by the adapter and not resumed since. A stop without allThreadsStopped
+    // only pauses the reporting thread.
+    pub paused_threads: RwLock<BTreeSet<i64>>,
+    // The latest program output (stdout and stderr), oldest first
+    pub program_output: SyncMutex<VecDeque<String>>,
+    // Last ConsoleLine seq handed out; never reset so lines stay ordered across relaunches
+    console_seq: AtomicU64,
+    // Register values at the last get_registers call, to highlight what a step changed
+    pub register_snapshot: RwLock<HashMap<String, String>>,
+    // Sources fetched by sourceReference, so revisiting a frame doesn't refetch them
+    pub adapter_sources: RwLock<HashMap<i64, AdapterSource>>,
+    // Where the adapter listens when it's reached over TCP; child sessions connect there too
+    pub adapter_endpoint: RwLock<Option<AdapterEndpoint>>,
+    // What the adapter reported supporting in its initialize response
+    pub capabilities: RwLock<Capabilities>,
+    // The adapter version detected at launch and the quirks that come with it
+    pub adapter_version: RwLock<Option<AdapterVersion>>,
+    // Optional py-spy sampler for Python sessions
+    pub sampler: SyncMutex<Option<PySpySampler>>,
+    // The terminal a Python program runs in, unless it got plain pipes
+    pub pty: SyncMutex<Option<PtyConsole>>,
+    // The recording an rr session replays, deleted once the session ends
+    pub rr_trace: SyncMutex<Option<RrTrace>>,
+    // User toggle: stop at panics in Rust sessions. Kept across sessions.
+    pub break_on_panic: RwLock<bool>,
+    // Exceptions to stop at; the user's choices are kept across sessions
+    pub exception_filters: RwLock<ExceptionFilters>,
+    // Function to stop at when the session was launched with break-at-entry
+    pub entry_function: RwLock<Option<String>>,
+    // Python's counterpart: the script and line to stop at, set as a breakpoint once while the
+    // session is configured
+    pub entry_line: RwLock<Option<(String, u32)>>,
+    // Function names by adapter breakpoint id, from the latest setFunctionBreakpoints response
+    pub function_breakpoint_ids: RwLock<HashMap<i64, String>>,
+    // The thread of the latest stop at a panic function and the function's index in
+    // RUST_PANIC_FUNCTIONS, so a panic stops only once on its way through them
+    pub panic_stop: SyncMutex<Option<(i64, usize)>>,
+    // User breakpoints, kept across sessions
+    pub breakpoints: RwLock<BreakpointStore>,
+    // Watch expressions registered by the frontend
+    pub watch_expressions: RwLock<Vec<String>>,
+    // Expressions to break on when their value changes, kept across sessions
+    pub value_watches: RwLock<Vec<ValueWatch>>,
+    // Truncation limits and number format for rendered values, kept across sessions
+    pub format_limits: RwLock<FormatLimits>,
+    // Expressions evaluated in the debug console, oldest first
+    pub console_history: RwLock<Vec<String>>,
+    pub selected_frame: RwLock<Option<FrameSelection>>,
+    // Snapshots of the latest stops, for looking back without reverse execution
+    pub stop_history: RwLock<StopHistory>,
+    // Steps in flight and the ones waiting on them
+    pub stepping: SyncMutex<SteppingController>,
+    // From the adapter's "exited" event
+    pub exit_code: RwLock<Option<i64>>,
+    // Summary of the latest session to end, kept after a relaunch
+    pub last_summary: RwLock<Option<SessionSummary>>,
+    pub last_launch: RwLock<Option<LaunchRequest>>,
+    // Cancels the launch in progress, if any
+    pub launch_cancel: SyncMutex<Option<LaunchCancel>>,
+    pub timeline: RwLock<SessionTimeline>,
+    // Request latency of every DAP client, across sessions
+    pub dap_metrics: Arc<DapMetrics>,
 }
 
 impl DebugSessionState {
-    pub fn new() -> Self {
+    // `status_seq` is shared between sessions so the frontend sees one ordered status stream.
+    pub fn new(token: &str, status_seq: Arc<AtomicU64>) -> Self {
         DebugSessionState {
+            token: token.to_string(),
+            parent: RwLock::new(None),
             client: Mutex::new(None),
+            canceller: SyncMutex::new(None),
             process: Mutex::new(None),
-            // Initialize as Arc
-            status_seq: Arc::new(AtomicU64::new(0)),
+            helper_processes: Mutex::new(Vec::new()),
+            status_seq,
             state: RwLock::new(DebuggerState::NotStarted),
             // Initialize the new field
             debugger_type: RwLock::new(None),
             current_thread_id: RwLock::new(None),
+            current_instruction_pointer: RwLock::new(None),
+            debuggee_process: RwLock::new(None),
+            threads: RwLock::new(BTreeMap::new()),
+            thread_refresh_pending: AtomicBool::new(false),
+            frozen_threads: RwLock::new(BTreeSet::new()),
+            paused_threads: RwLock::new(BTreeSet::new()),
+            register_snapshot: RwLock::new(HashMap::new()),
+            adapter_sources: RwLock::new(HashMap::new()),
+            adapter_endpoint: RwLock::new(None),
+            capabilities: RwLock::new(Capabilities::default()),
+            adapter_version: RwLock::new(None),
+            program_output: SyncMutex::new(VecDeque::new()),
+            console_seq: AtomicU64::new(0),
+            sampler: SyncMutex::new(None),
+            pty: SyncMutex::new(None),
+            rr_trace: SyncMutex::new(None),
+            break_on_panic: RwLock::new(false),
+            exception_filters: RwLock::new(ExceptionFilters::default()),
+            entry_function: RwLock::new(None),
+            entry_line: RwLock::new(None),
+            function_breakpoint_ids: RwLock::new(HashMap::new()),
+            panic_stop: SyncMutex::new(None),
+            breakpoints: RwLock::new(BreakpointStore::default()),
+            watch_expressions: RwLock::new(Vec::new()),
+            value_watches: RwLock::new(Vec::new()),
+            format_limits: RwLock::new(FormatLimits::default()),
+            console_history: RwLock::new(Vec::new()),
+            selected_frame: RwLock::new(None),
+            stop_history: RwLock::new(StopHistory::default()),
+            stepping: SyncMutex::new(SteppingController::default()),
+            exit_code: RwLock::new(None),
+            last_summary: RwLock::new(None),
+            last_launch: RwLock::new(None),
+            launch_cancel: SyncMutex::new(None),
+            timeline: RwLock::new(SessionTimeline::new()),
+            dap_metrics: Arc::new(DapMetrics::default()),
         }
     }
 
-    pub fn handle_dap_event(&self, msg: &crate::debugger::client::DAPMessage) {
+    // Records what the adapter reported it supports in its initialize response.
+    pub fn set_capabilities(&self, capabilities: Option<&serde_json::Value>) {
+        self.exception_filters.write().set_available(capabilities);
+        *self.capabilities.write() = Capabilities::from_body(capabilities);
+    }
+
+    // Records the adapter the session launched with.
+    pub fn set_adapter_version(&self, adapter: &str, raw: Option<String>) {
+        let version = AdapterVersion::new(adapter, raw);
+        tracing::info!(
+            "Adapter {} version {:?}, quirks {:?}",
+            adapter,
+            version.raw,
+            version.quirks
+        );
+        *self.adapter_version.write() = Some(version);
+    }
+
+    // Registers a new launch for cancel_launch, replacing any earlier one.
+    pub fn begin_launch(&self) -> LaunchCancel {
+        let cancel = LaunchCancel::default();
+        *self.launch_cancel.lock() = Some(cancel.clone());
+        cancel
+    }
+
+    // Unregisters `cancel` once its launch finished, unless a newer launch replaced it.
+    pub fn end_launch(&self, cancel: &LaunchCancel) {
+        let mut current = self.launch_cancel.lock();
+        if current.as_ref().is_some_and(|c| c.same_as(cancel)) {
+            *current = None;
+        }
+    }
+
+    // Clears per-session data left over from a previous launch.
+    pub fn reset_session(&self) {
+        *self.debuggee_process.write() = None;
+        self.threads.write().clear();
+        self.frozen_threads.write().clear();
+        self.paused_threads.write().clear();
+        self.register_snapshot.write().clear();
+        *self.adapter_version.write() = None;
+        *self.adapter_endpoint.write() = None;
+        *self.capabilities.write() = Capabilities::default();
+        self.canceller.lock().take();
+        self.exception_filters.write().clear_available();
+        // References don't carry over to a new adapter session
+        self.adapter_sources.write().clear();
+        for watch in self.value_watches.write().iter_mut() {
+            watch.last_value = None;
+        }
+        self.program_output.lock().clear();
+        self.stop_history.write().clear();
+        self.stepping.lock().clear();
+        self.breakpoints.write().clear_loaded();
+        *self.exit_code.write() = None;
+        *self.entry_function.write() = None;
+        *self.entry_line.write() = None;
+        self.function_breakpoint_ids.write().clear();
+        *self.panic_stop.lock() = None;
+        // Dropping the sampler stops its thread
+        self.sampler.lock().take();
+        self.pty.lock().take();
+    }
+
+    // Forgets what belonged to an adapter connection that dropped mid-session. The program and
+    // the user's settings carry over to the next connection; thread ids and references don't.
+    pub fn reset_connection(&self) {
+        *self.state.write() = DebuggerState::Configuring;
+        self.threads.write().clear();
+        self.frozen_threads.write().clear();
+        self.paused_threads.write().clear();
+        self.stepping.lock().clear();
+        self.canceller.lock().take();
+        self.adapter_sources.write().clear();
+        self.breakpoints.write().clear_loaded();
+    }
+
+    pub fn handle_dap_event(&self, event: &DapEvent) {
         let mut guard = self.state.write();
-        if msg.message_type == crate::debugger::client::MessageType::Event {
-            if let Some(ref event_name) = msg.event {
-                match event_name.as_str() {
-                    "initialized" => {
-                        *guard = DebuggerState::Configuring;
-                    }
-                    "continued" => {
-                        *guard = DebuggerState::Running;
-                    }
-                    "stopped" => {
-                        if let Some(body) = &msg.body {
-                            let reason = body
-                                .get("reason")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("unknown")
-                                .to_string();
-                            let thread_id =
-                                body.get("threadId").and_then(|v| v.as_i64()).unwrap_or(1);
-                            *guard = DebuggerState::Paused { reason, thread_id };
-                            *self.current_thread_id.write() = Some(thread_id);
-                        }
+        match event {
+            DapEvent::Initialized => {
+                *guard = DebuggerState::Configuring;
+            }
+            // Without allThreadsContinued only the reported thread resumed
+            DapEvent::Continued(body) => {
+                let all_threads = body.all_threads_continued.unwrap_or(false);
+                self.resume_threads(&mut guard, body.thread_id, all_threads);
+                // Resumes from outside wayfind (e.g. the adapter's console) only show up here
+                self.timeline.write().record(
+                    "continued",
+                    serde_json::json!({
+                        "threadId": body.thread_id,
+                        "allThreadsContinued": all_threads,
+                    }),
+                );
+            }
+            DapEvent::Stopped(body) => {
+                let thread_id = body.thread_id.unwrap_or(1);
+                *guard = DebuggerState::Paused {
+                    reason: body.reason.clone(),
+                    thread_id,
+                };
+                *self.current_thread_id.write() = Some(thread_id);
+                let mut paused = self.paused_threads.write();
+                if body.all_threads_stopped.unwrap_or(false) {
+                    paused.extend(self.threads.read().keys().copied());
+                }
+                paused.insert(thread_id);
+            }
+            DapEvent::Terminated(body) => {
+                *guard = DebuggerState::Terminated;
+                self.threads.write().clear();
+                self.frozen_threads.write().clear();
+                self.paused_threads.write().clear();
+                self.stepping.lock().clear();
+                self.timeline.write().record(
+                    "terminated",
+                    body.clone().unwrap_or(serde_json::Value::Null),
+                );
+            }
+            DapEvent::Thread(body) => {
+                let mut threads = self.threads.write();
+                match body.reason.as_str() {
+                    "started" => {
+                        threads.entry(body.thread_id).or_insert(ThreadInfo {
+                            id: body.thread_id,
+                            name: None,
+                            frozen: false,
+                            paused: false,
+                        });
                     }
-                    "terminated" => {
-                        *guard = DebuggerState::Terminated;
+                    "exited" => {
+                        threads.remove(&body.thread_id);
+                        self.frozen_threads.write().remove(&body.thread_id);
+                        self.paused_threads.write().remove(&body.thread_id);
                     }
                     _ => {}
                 }
             }
+            DapEvent::Exited(body) => {
+                *self.exit_code.write() = Some(body.exit_code);
+            }
+            DapEvent::Process(body) => {
+                *self.debuggee_process.write() = Some(DebuggeeProcess {
+                    name: body.name.clone(),
+                    system_process_id: body.system_process_id,
+                    start_method: body.start_method.clone(),
+                    is_local_process: body.is_local_process,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    // Whether `thread_id` is still stopped, i.e. no "continued" came in since it stopped.
+    pub fn is_thread_paused(&self, thread_id: i64) -> bool {
+        self.paused_threads.read().contains(&thread_id)
+    }
+
+    // Records threads resumed outside of a "continued" event, e.g. by a continue response.
+    pub fn mark_continued(&self, thread_id: i64, all_threads: bool) {
+        let mut guard = self.state.write();
+        self.resume_threads(&mut guard, thread_id, all_threads);
+    }
+
+    // The session only counts as running once no thread is left paused. While some still are,
+    // the current thread moves to one of them if it was the one resumed.
+    fn resume_threads(&self, state: &mut DebuggerState, thread_id: i64, all_threads: bool) {
+        let mut paused = self.paused_threads.write();
+        if all_threads {
+            paused.clear();
+        } else {
+            paused.remove(&thread_id);
+        }
+
+        match paused.iter().next() {
+            None => {
+                *state = DebuggerState::Running;
+                *self.current_instruction_pointer.write() = None;
+            }
+            Some(&next) => {
+                if let DebuggerState::Paused { thread_id, .. } = state {
+                    if !paused.contains(thread_id) {
+                        *thread_id = next;
+                        *self.current_thread_id.write() = Some(next);
+                        *self.current_instruction_pointer.write() = None;
+                    }
+                }
+            }
+        }
+    }
+
+    // Sums up the session once it has ended. Returns None if this launch was already summed up,
+    // since several paths can report the same end (e.g. a terminate request and the adapter's
+    // "terminated" event). `status` is the debuggee's own exit status when the session owns it.
+    pub fn summarize(&self, ended_by: &str, status: Option<ExitStatus>) -> Option<SessionSummary> {
+        let started_at = self.timeline.read().started_at;
+        let mut last = self.last_summary.write();
+        if last.as_ref().is_some_and(|s| s.started_at == started_at) {
+            return None;
+        }
+
+        let (status_code, signal) = status
+            .map(session_summary::exit_details)
+            .unwrap_or_default();
+        let exit_code = (*self.exit_code.read()).or(status_code);
+        // An adapter reporting the end of a program nobody asked to stop means it exited
+        let requested = self
+            .timeline
+            .read()
+            .entries
+            .iter()
+            .any(|entry| entry.kind == "terminateRequested");
+        let ended_by = match ended_by {
+            "terminated" if !requested && (exit_code.is_some() || signal.is_some()) => "exited",
+            _ => ended_by,
+        };
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        let summary = SessionSummary {
+            session: self.token.clone(),
+            ended_by: ended_by.to_string(),
+            exit_code,
+            signal,
+            started_at,
+            duration_ms: now.saturating_sub(started_at),
+            stops: self.stop_history.read().recorded(),
+        };
+        *last = Some(summary.clone());
+        Some(summary)
+    }
+
+    // Captures a line of program output. It's numbered and timestamped under the output lock so
+    // seq order matches the order lines were read in across both streams.
+    pub fn record_output(&self, stream: &str, line: &str) -> ConsoleLine {
+        let mut output = self.program_output.lock();
+        if output.len() == MAX_CAPTURED_OUTPUT {
+            output.pop_front();
+        }
+        output.push_back(line.to_string());
+        ConsoleLine {
+            session: self.token.clone(),
+            seq: self.console_seq.fetch_add(1, Ordering::Relaxed) + 1,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            stream: stream.to_string(),
+            text: line.to_string(),
         }
     }
 
+    pub fn record_console_input(&self, expression: &str) {
+        let mut history = self.console_history.write();
+        if history.last().map(|last| last.as_str()) != Some(expression) {
+            history.push(expression.to_string());
+        }
+        let excess = history.len().saturating_sub(MAX_CONSOLE_HISTORY);
+        history.drain(..excess);
+    }
+
+    // Whether the session is driven by lldb-dap (native Rust, or an rr replay)
+    pub fn is_lldb_session(&self) -> bool {
+        matches!(
+            self.debugger_type.read().as_deref(),
+            Some("rust") | Some("rr")
+        )
+    }
+
+    // The full set of function breakpoints the adapter should have, since
+    // setFunctionBreakpoints replaces everything previously set.
+    pub fn function_breakpoints(&self) -> Vec<FunctionBreakpointInput> {
+        let mut breakpoints = Vec::new();
+        if *self.break_on_panic.read() && self.is_lldb_session() {
+            breakpoints.extend(
+                RUST_PANIC_FUNCTIONS
+                    .iter()
+                    .map(|name| FunctionBreakpointInput {
+                        name: name.to_string(),
+                        condition: None,
+                    }),
+            );
+        }
+        if let Some(entry) = self.entry_function.read().as_ref() {
+            breakpoints.push(FunctionBreakpointInput {
+                name: entry.clone(),
+                condition: None,
+            });
+        }
+        breakpoints
+    }
+
+    // Maps the ids in a setFunctionBreakpoints response body back to function names. The
+    // adapter answers in request order, so the response lines up with function_breakpoints().
+    pub fn record_function_breakpoint_ids(&self, body: Option<&serde_json::Value>) {
+        let mut ids = self.function_breakpoint_ids.write();
+        ids.clear();
+        let Some(results) = body
+            .and_then(|body| body.get("breakpoints"))
+            .and_then(|b| b.as_array())
+        else {
+            return;
+        };
+        for (breakpoint, result) in self.function_breakpoints().iter().zip(results) {
+            if let Some(id) = result.get("id").and_then(|id| id.as_i64()) {
+                ids.insert(id, breakpoint.name.clone());
+            }
+        }
+    }
+
+    // Where among RUST_PANIC_FUNCTIONS a stop at these breakpoint ids is, if it's at one.
+    pub fn panic_function_hit(&self, hit_breakpoint_ids: &[i64]) -> Option<usize> {
+        let ids = self.function_breakpoint_ids.read();
+        hit_breakpoint_ids
+            .iter()
+            .filter_map(|id| ids.get(id))
+            .filter_map(|name| RUST_PANIC_FUNCTIONS.iter().position(|f| f == name))
+            .min()
+    }
+
+    pub fn thread_list(&self) -> Vec<ThreadInfo> {
+        let frozen = self.frozen_threads.read().clone();
+        let paused = self.paused_threads.read().clone();
+        self.threads
+            .read()
+            .values()
+            .map(|thread| ThreadInfo {
+                frozen: frozen.contains(&thread.id),
+                paused: paused.contains(&thread.id),
+                ..thread.clone()
+            })
+            .collect()
+    }
+
+    // The adapter's name for a thread: Python's threading names under debugpy, the OS thread
+    // name (or lldb's "Thread N") for Rust sessions.
+    pub fn thread_name(&self, thread_id: i64) -> Option<String> {
+        self.threads
+            .read()
+            .get(&thread_id)
+            .and_then(|thread| thread.name.clone())
+    }
+
+    // Replaces the thread list with the one in a threads response. A failed response leaves it
+    // as it was.
+    pub fn update_threads(&self, response: &DAPMessage) {
+        let Ok(body) = protocol::body::<ThreadsResponseBody>(response) else {
+            return;
+        };
+        let mut threads = self.threads.write();
+        threads.clear();
+        for thread in body.threads {
+            threads.insert(
+                thread.id,
+                ThreadInfo {
+                    id: thread.id,
+                    name: thread.name,
+                    frozen: false,
+                    paused: false,
+                },
+            );
+        }
+    }
+
+    pub fn session_info(&self) -> SessionInfo {
+        SessionInfo {
+            token: self.token.clone(),
+            parent: self.parent.read().clone(),
+            debugger_type: self.debugger_type.read().clone(),
+            state: self.state.read().as_str().to_string(),
+            current_thread_id: *self.current_thread_id.read(),
+            current_thread_name: self
+                .current_thread_id
+                .read()
+                .and_then(|id| self.thread_name(id)),
+            process: self.debuggee_process.read().clone(),
+            adapter: self.adapter_version.read().clone(),
+        }
+    }
+
+    // Extra status payload fields identifying this session's events.
+    pub fn status_extra(&self) -> serde_json::Map<String, serde_json::Value> {
+        let mut extra = serde_json::Map::new();
+        extra.insert("session".to_string(), serde_json::json!(self.token));
+        if let Some(parent) = self.parent.read().as_ref() {
+            extra.insert("parentSession".to_string(), serde_json::json!(parent));
+        }
+        extra.insert(
+            "pausedThreads".to_string(),
+            serde_json::json!(*self.paused_threads.read()),
+        );
+        extra
+    }
+
+    // configurationDone starts the program, unless it already stopped (e.g. at entry) or ended
     pub fn handle_configuration_done(&self) {
         let mut guard = self.state.write();
-        *guard = DebuggerState::Running;
+        if matches!(
+            *guard,
+            DebuggerState::NotStarted | DebuggerState::Configuring
+        ) {
+            *guard = DebuggerState::Running;
+        }
     }
 }
@@ -0,0 +1,192 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use crate::cargo_workspace::{self, CargoTargetKind};
+use crate::launch_config::{LaunchConfig, PythonTemplate};
+
+// How deep and how wide the scan for Python scripts goes, so opening a huge tree stays quick.
+const MAX_DEPTH: usize = 6;
+const MAX_FILES: usize = 5000;
+// Enough of a script to find its `__main__` guard
+const SCRIPT_PREFIX_BYTES: u64 = 64 * 1024;
+
+// Directories that hold dependencies, build output or caches rather than the user's code
+const SKIPPED_DIRS: &[&str] = &[
+    "node_modules",
+    "target",
+    "venv",
+    "env",
+    "site-packages",
+    "__pycache__",
+    "build",
+    "dist",
+];
+
+// Something runnable in the workspace, with what launch_debug_session needs to start it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugTarget {
+    // Shown in the picker, e.g. "tools/migrate.py" or "server (bin)"
+    pub label: String,
+    // "python" or "rust"; None for things wayfind can't debug directly (e.g. a node script)
+    pub engine: Option<String>,
+    // The script, or the workspace root for cargo targets
+    pub path: String,
+    pub launch_config: LaunchConfig,
+    // What a package.json script runs
+    pub command: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugTargetIndex {
+    pub python_scripts: Vec<DebugTarget>,
+    pub cargo_targets: Vec<DebugTarget>,
+    pub package_scripts: Vec<DebugTarget>,
+}
+
+// Scans `root` for Python entry scripts, the Cargo workspace's bins, examples and integration
+// tests, and package.json scripts.
+pub fn index(root: &Path) -> DebugTargetIndex {
+    let mut index = DebugTargetIndex::default();
+    let mut scanned = 0;
+    find_python_scripts(root, root, 0, &mut scanned, &mut index.python_scripts);
+    index.python_scripts.sort_by(|a, b| a.label.cmp(&b.label));
+
+    if root.join("Cargo.toml").is_file() {
+        match cargo_workspace::workspace_layout(root) {
+            Ok(layout) => index.cargo_targets = cargo_targets(root, &layout),
+            Err(e) => tracing::warn!("Skipping cargo targets: {}", e),
+        }
+    }
+
+    index.package_scripts = package_scripts(root);
+    index
+}
+
+fn find_python_scripts(
+    root: &Path,
+    dir: &Path,
+    depth: usize,
+    scanned: &mut usize,
+    out: &mut Vec<DebugTarget>,
+) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if *scanned >= MAX_FILES {
+            return;
+        }
+        *scanned += 1;
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            if depth < MAX_DEPTH && !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str())
+            {
+                find_python_scripts(root, &path, depth + 1, scanned, out);
+            }
+            continue;
+        }
+        if !name.ends_with(".py") {
+            continue;
+        }
+
+        let template = (name == "manage.py").then_some(PythonTemplate::Django);
+        if template.is_none() && name != "__main__.py" && !has_main_guard(&path) {
+            continue;
+        }
+        let relative = path.strip_prefix(root).unwrap_or(&path);
+        out.push(DebugTarget {
+            label: relative.to_string_lossy().into_owned(),
+            engine: Some("python".to_string()),
+            path: path.to_string_lossy().into_owned(),
+            launch_config: LaunchConfig {
+                python_template: template,
+                ..LaunchConfig::default()
+            },
+            command: None,
+        });
+    }
+}
+
+fn has_main_guard(path: &Path) -> bool {
+    let mut content = String::new();
+    let read = fs::File::open(path)
+        .and_then(|file| file.take(SCRIPT_PREFIX_BYTES).read_to_string(&mut content));
+    read.is_ok()
+        && (content.contains("__name__ == \"__main__\"")
+            || content.contains("__name__ == '__main__'"))
+}
+
+fn cargo_targets(root: &Path, layout: &cargo_workspace::WorkspaceLayout) -> Vec<DebugTarget> {
+    let mut targets = Vec::new();
+    for member in &layout.members {
+        let kinds = [
+            (CargoTargetKind::Bin, &member.bins, "bin"),
+            (CargoTargetKind::Example, &member.examples, "example"),
+            (CargoTargetKind::Test, &member.tests, "test"),
+        ];
+        for (kind, names, label) in kinds {
+            for name in names {
+                targets.push(DebugTarget {
+                    label: format!("{}: {} ({})", member.name, name, label),
+                    engine: Some("rust".to_string()),
+                    path: root.to_string_lossy().into_owned(),
+                    launch_config: LaunchConfig {
+                        cargo_package: Some(member.name.clone()),
+                        cargo_bin: Some(name.clone()),
+                        cargo_target_kind: kind,
+                        ..LaunchConfig::default()
+                    },
+                    command: None,
+                });
+            }
+        }
+    }
+    targets
+}
+
+// The engine a package.json script would be debugged with, guessed from its command.
+fn script_engine(command: &str) -> Option<&'static str> {
+    let program = command.split_whitespace().next()?;
+    match program {
+        "python" | "python3" => Some("python"),
+        _ => None,
+    }
+}
+
+fn package_scripts(root: &Path) -> Vec<DebugTarget> {
+    let Ok(content) = fs::read_to_string(root.join("package.json")) else {
+        return Vec::new();
+    };
+    let package: Value = match serde_json::from_str(&content) {
+        Ok(package) => package,
+        Err(e) => {
+            tracing::warn!("Skipping package.json scripts: {}", e);
+            return Vec::new();
+        }
+    };
+    package
+        .get("scripts")
+        .and_then(|s| s.as_object())
+        .into_iter()
+        .flatten()
+        .filter_map(|(name, command)| {
+            let command = command.as_str()?;
+            Some(DebugTarget {
+                label: format!("npm run {}", name),
+                engine: script_engine(command).map(String::from),
+                path: root.to_string_lossy().into_owned(),
+                launch_config: LaunchConfig::default(),
+                command: Some(command.to_string()),
+            })
+        })
+        .collect()
+}
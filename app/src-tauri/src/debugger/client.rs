@@ -1,51 +1,45 @@
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::io::{BufReader, ErrorKind, Read, Write};
-use std::net::TcpStream;
+use serde::Serialize;
+use std::ops::{Deref, DerefMut};
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::{Arc, Mutex};
-use std::thread;
-use std::time::{Duration, Instant};
+use std::sync::Arc;
+use std::time::Duration;
 use tauri::async_runtime;
 use tauri::AppHandle;
 use tauri::Emitter;
+use tauri::Manager;
 use tokio::sync::mpsc;
-
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
-#[serde(rename_all = "lowercase")] // This tells serde to use lowercase strings.
-pub enum MessageType {
-    Request,
-    Response,
-    Event,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct DAPMessage {
-    pub seq: i32,
-    #[serde(rename = "type")]
-    pub message_type: MessageType,
-    pub command: Option<String>,
-    pub request_seq: Option<i32>,
-    pub success: Option<bool>,
-    pub body: Option<serde_json::Value>,
-    pub event: Option<String>,
-    pub arguments: Option<serde_json::Value>,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct BreakpointInput {
-    pub line: u32,
-}
-
-// Function to emit status updates with sequence numbers
-// Now includes file path and line number for paused status
-pub fn emit_status_update(
+use tracing::{debug, warn};
+
+use crate::debugger::metrics::DapMetrics;
+use crate::debugger::protocol::{
+    self, ContinueResponseBody, ErrorResponseBody, EvaluateResponseBody, StackTraceResponseBody,
+    StartDebuggingRequestArguments,
+};
+use crate::debugger::stop::{self, Stop};
+use crate::session_history::{self, SessionHistoryStore};
+use crate::settings::SettingsStore;
+use crate::skip_list::SkipList;
+use crate::value_watch::ValueChange;
+use dap_client::DapEvent;
+pub use dap_client::{
+    BreakpointInput, Capabilities, Checksum, DAPMessage, FunctionBreakpointInput, InFlightRequest,
+    RequestCanceller, RetryPolicy,
+};
+
+// How long thread-started events are collected before the thread names are fetched.
+const THREAD_REFRESH_DELAY: Duration = Duration::from_millis(100);
+
+// Emits a status update with a sequence number. Paused updates include the file path and line;
+// `extra` is merged into the payload (e.g. the session token, the instruction pointer for the
+// disassembly view).
+pub fn emit_status_update_with_extra(
     app_handle: &AppHandle,
     status_seq: &AtomicU64,
     status: &str,
     thread_id: Option<i64>,
     file_path: Option<&str>,
     line: Option<i64>,
+    extra: serde_json::Map<String, serde_json::Value>,
 ) -> Result<(), String> {
     let seq = status_seq.fetch_add(1, Ordering::SeqCst);
 
@@ -54,7 +48,7 @@ pub fn emit_status_update(
         "seq": seq
     });
 
-    println!("Emitting status update: status={}, seq={}", status, seq);
+    debug!(status, seq, "Emitting status update");
 
     if let serde_json::Value::Object(ref mut map) = payload {
         // Add thread ID if available
@@ -68,12 +62,11 @@ pub fn emit_status_update(
 
             if let Some(ln) = line {
                 map.insert("line".to_string(), serde_json::json!(ln));
-                println!(
-                    "Including debug location in status: file={}, line={}",
-                    path, ln
-                );
+                debug!(file = path, line = ln, "Including debug location in status");
             }
         }
+
+        map.extend(extra);
     }
 
     app_handle
@@ -81,665 +74,515 @@ pub fn emit_status_update(
         .map_err(|e| format!("Failed to emit status update: {}", e))
 }
 
-pub struct DAPClient {
-    // The writer is used to send messages.
-    writer: Option<Arc<Mutex<TcpStream>>>,
-    // The reader (wrapped in a BufReader) is used in our receiver loop.
-    reader: Option<Arc<Mutex<BufReader<TcpStream>>>>,
-    // next_seq generates unique sequence numbers for requests.
-    next_seq: Arc<Mutex<i32>>,
-    // responses: when we receive a Response message, we store it here by its request_seq.
-    responses: Arc<Mutex<HashMap<i32, DAPMessage>>>,
-    // events: when we receive an Event (e.g. "initialized", "terminated"), we store them here.
-    events: Arc<Mutex<HashMap<String, Vec<DAPMessage>>>>,
-    // receiver_handle: the join handle for the receiver thread.
-    receiver_handle: Option<thread::JoinHandle<()>>,
-    // event_sender: an optional channel sender that you can use if you want to propagate messages externally.
-    event_sender: mpsc::UnboundedSender<DAPMessage>,
-    // app_handle: the Tauri AppHandle used to emit IPC events.
-    pub app_handle: AppHandle,
-    // status_seq: counter for status update sequence numbers
-    pub status_seq: Arc<AtomicU64>,
-    // NEW: Optional reference to the debug state.
-    pub debug_state: Option<Arc<crate::debug_state::DebugSessionState>>,
+// Reports resumed threads: a "running" status once no thread is left paused, and the thread
+// list with its paused flags either way.
+pub fn emit_threads_resumed(
+    app_handle: &AppHandle,
+    status_seq: &AtomicU64,
+    debug_state: &crate::debug_state::DebugSessionState,
+    thread_id: i64,
+) {
+    if *debug_state.state.read() == crate::debug_state::DebuggerState::Running {
+        let _ = emit_status_update_with_extra(
+            app_handle,
+            status_seq,
+            "running",
+            Some(thread_id),
+            None,
+            None,
+            debug_state.status_extra(),
+        );
+    }
+    let _ = app_handle.emit("threads-changed", debug_state.thread_list());
 }
 
-impl DAPClient {
-    // Create a new client along with an mpsc receiver for external subscribers.
-    // This version requires an AppHandle and a DebugSessionState to be provided.
-    pub fn new(
-        app_handle: AppHandle,
-        debug_state: Arc<crate::debug_state::DebugSessionState>,
-    ) -> (Self, mpsc::UnboundedReceiver<DAPMessage>) {
-        let (tx, rx) = mpsc::unbounded_channel();
-
-        let client = Self {
-            writer: None,
-            reader: None,
-            next_seq: Arc::new(Mutex::new(1)),
-            responses: Arc::new(Mutex::new(HashMap::new())),
-            events: Arc::new(Mutex::new(HashMap::new())),
-            receiver_handle: None,
-            event_sender: tx,
-            app_handle,
-            status_seq: Arc::new(AtomicU64::new(0)),
-            debug_state: Some(debug_state),
-        };
+// Records a line of program output and emits it on "console-line".
+pub fn emit_console_line(
+    app_handle: &AppHandle,
+    debug_state: &crate::debug_state::DebugSessionState,
+    stream: &str,
+    line: &str,
+) {
+    let console_line = debug_state.record_output(stream, line);
+    let _ = app_handle.emit("console-line", console_line);
+}
 
-        (client, rx)
+// Emits the summary of a session that just ended on "session-summary", once per launch, and
+// adds the session to the history. The debuggee's exit status is picked up when the session owns
+// the program (Python) and it has already exited.
+pub fn emit_session_summary(
+    app_handle: &AppHandle,
+    debug_state: &crate::debug_state::DebugSessionState,
+    ended_by: &str,
+) {
+    let status = debug_state
+        .process
+        .try_lock()
+        .ok()
+        .filter(|_| debug_state.debugger_type.read().as_deref() == Some("python"))
+        .and_then(|mut process| process.as_mut()?.try_wait().ok().flatten());
+    if let Some(summary) = debug_state.summarize(ended_by, status) {
+        debug_state
+            .timeline
+            .write()
+            .record("summary", serde_json::json!(summary));
+        let breakpoints = session_history::breakpoint_hits(&debug_state.timeline.read());
+        let launch = debug_state.last_launch.read().clone();
+        if let Err(e) = app_handle.state::<Arc<SessionHistoryStore>>().record(
+            &summary,
+            launch.as_ref(),
+            &breakpoints,
+        ) {
+            warn!("{}", e);
+        }
+        let _ = app_handle.emit("session-summary", summary);
     }
+}
 
-    // Connect: clone the stream so that one instance is used for writing and one for reading.
-    pub fn connect(&mut self, host: &str, port: u16) -> std::io::Result<()> {
-        let stream = TcpStream::connect((host, port))?;
-        self.writer = Some(Arc::new(Mutex::new(stream.try_clone()?)));
-        self.reader = Some(Arc::new(Mutex::new(BufReader::new(stream))));
-        Ok(())
-    }
+// Structured error payload emitted on the "debug-error" channel so the UI can show
+// actionable messages instead of parsing command result strings.
+#[derive(Debug, Serialize, Clone)]
+pub struct DebugError {
+    pub code: String,
+    pub message: String,
+    pub detail: Option<String>,
+    pub recoverable: bool,
+}
 
-    // Get a reference to the status sequence counter
-    #[allow(dead_code)]
-    pub fn get_status_seq(&self) -> &Arc<AtomicU64> {
-        &self.status_seq
-    }
+pub fn emit_debug_error(
+    app_handle: &AppHandle,
+    code: &str,
+    message: &str,
+    detail: Option<&str>,
+    recoverable: bool,
+) -> Result<(), String> {
+    let payload = DebugError {
+        code: code.to_string(),
+        message: message.to_string(),
+        detail: detail.map(|d| d.to_string()),
+        recoverable,
+    };
 
-    // send_message: assigns a sequence number, serializes the message along with a header, and writes it to the stream.
-    // Returns the assigned sequence number.
-    pub fn send_message(&self, mut message: DAPMessage) -> std::io::Result<i32> {
-        let seq = {
-            let mut seq_lock = self.next_seq.lock().unwrap();
-            let current = *seq_lock;
-            *seq_lock += 1;
-            current
-        };
+    warn!(code, "Emitting debug error: {}", message);
 
-        message.seq = seq;
-        let json = serde_json::to_string(&message)?;
-        let header = format!("Content-Length: {}\r\n\r\n", json.len());
+    app_handle
+        .emit("debug-error", payload)
+        .map_err(|e| format!("Failed to emit debug error: {}", e))
+}
 
-        println!(
-            "--> Sending message (seq={}):\nHeader: {}\nPayload: {}",
-            seq, header, json
-        );
+// Non-fatal problem the user should know about (e.g. a binary without debug info), emitted on
+// the "debug-warning" channel.
+#[derive(Debug, Serialize, Clone)]
+pub struct DebugWarning {
+    pub code: String,
+    pub message: String,
+    pub detail: Option<String>,
+}
 
-        if let Some(ref writer) = self.writer {
-            let mut guard = writer.lock().unwrap();
-            guard.write_all(header.as_bytes())?;
-            guard.write_all(json.as_bytes())?;
-            guard.flush()?;
-        } else {
-            panic!("Stream is not connected");
-        }
+pub fn emit_debug_warning(
+    app_handle: &AppHandle,
+    code: &str,
+    message: &str,
+    detail: Option<&str>,
+) -> Result<(), String> {
+    let payload = DebugWarning {
+        code: code.to_string(),
+        message: message.to_string(),
+        detail: detail.map(|d| d.to_string()),
+    };
+
+    warn!(code, "Emitting debug warning: {}", message);
+
+    app_handle
+        .emit("debug-warning", payload)
+        .map_err(|e| format!("Failed to emit debug warning: {}", e))
+}
 
-        Ok(seq)
+// Routes what the shared DAP client receives into the app: session state, status and error
+// events for the frontend, latency metrics and the external subscriber channel.
+struct TauriEventSink {
+    app_handle: AppHandle,
+    status_seq: Arc<AtomicU64>,
+    debug_state: Option<Arc<crate::debug_state::DebugSessionState>>,
+    metrics: Arc<DapMetrics>,
+    event_sender: mpsc::UnboundedSender<DAPMessage>,
+}
+
+impl TauriEventSink {
+    // Tags status updates with the session they belong to.
+    fn session_extra(&self) -> serde_json::Map<String, serde_json::Value> {
+        self.debug_state
+            .as_ref()
+            .map(|debug_state| debug_state.status_extra())
+            .unwrap_or_default()
     }
+}
 
-    // start_receiver: spawns a dedicated thread to continuously read incoming messages.
-    pub fn start_receiver(&mut self, external_status_seq: Option<Arc<AtomicU64>>) {
-        let reader_arc = Arc::clone(self.reader.as_ref().expect("Reader not set"));
-        let responses_arc = Arc::clone(&self.responses);
-        let events_arc = Arc::clone(&self.events);
-        let event_sender = self.event_sender.clone();
-        // Clone the app_handle so it can be moved into the thread.
-        let app_handle = self.app_handle.clone();
-        // Use external status sequence counter if provided, otherwise use the one from the client
-        let status_seq = match external_status_seq {
-            Some(seq) => seq,
-            None => Arc::clone(&self.status_seq),
-        };
-        let debug_state_arc = self.debug_state.clone();
-
-        self.receiver_handle = Some(thread::spawn(move || loop {
-            // Read header until we find the "\r\n\r\n" sequence.
-            let header = {
-                let mut reader = reader_arc.lock().unwrap();
-                let mut header_bytes = Vec::new();
-                // Read one byte at a time until the header terminator is found.
-                loop {
-                    let mut buf = [0u8; 1];
-                    match reader.read_exact(&mut buf) {
-                        Ok(()) => {
-                            header_bytes.push(buf[0]);
-                            if header_bytes.ends_with(b"\r\n\r\n") {
-                                break;
-                            }
-                        }
-                        Err(ref e) if e.kind() == ErrorKind::UnexpectedEof => {
-                            // Connection closed.
-                            return;
-                        }
-                        Err(e) => {
-                            eprintln!("Error reading header: {}", e);
-                            return;
-                        }
-                    }
-                }
-                String::from_utf8_lossy(&header_bytes).to_string()
-            };
-
-            // Parse Content-Length from header.
-            let content_length = header
-                .lines()
-                .find(|line| line.to_lowercase().starts_with("content-length:"))
-                .and_then(|line| line[15..].trim().parse::<usize>().ok());
-
-            if let Some(len) = content_length {
-                // Now, read the body.
-                let mut body_bytes = vec![0; len];
-                {
-                    let mut reader = reader_arc.lock().unwrap();
-                    if let Err(e) = reader.read_exact(&mut body_bytes) {
-                        eprintln!("Error reading body: {}", e);
-                        return;
-                    }
-                }
-                let message_str = match String::from_utf8(body_bytes) {
-                    Ok(s) => s,
-                    Err(e) => {
-                        eprintln!("Invalid UTF-8 body: {}", e);
-                        continue;
-                    }
-                };
+impl dap_client::EventSink for TauriEventSink {
+    fn on_message(&self, msg: &DAPMessage) {
+        // Send the message to any external subscribers
+        let _ = self.event_sender.send(msg.clone());
+    }
 
-                println!("<-- Received: {}", message_str);
+    fn on_event(&self, event: &DapEvent, _msg: &DAPMessage) {
+        let app_handle = &self.app_handle;
+        let status_seq = &self.status_seq;
 
-                if let Ok(msg) = serde_json::from_str::<DAPMessage>(&message_str) {
-                    if let Some(ds) = &debug_state_arc {
-                        ds.handle_dap_event(&msg);
-                    }
+        if let Some(ds) = &self.debug_state {
+            ds.handle_dap_event(event);
+        }
 
-                    // Handle events that require special processing
-                    if msg.message_type == MessageType::Event {
-                        if let Some(ref evt) = msg.event {
-                            if evt == "terminated" {
-                                println!("Processing 'terminated' event");
-                                let _ = emit_status_update(
-                                    &app_handle,
-                                    &status_seq,
-                                    "terminated",
-                                    None,
-                                    None,
-                                    None,
-                                );
-                            } else if evt == "stopped" {
-                                // Handle the stopped event - extract thread ID and emit
-                                if let Some(ref body) = msg.body {
-                                    println!("Processing 'stopped' event: {:?}", body);
-
-                                    // Get thread ID if available
-                                    if let Some(thread_id) =
-                                        body.get("threadId").and_then(|v| v.as_i64())
-                                    {
-                                        // Then get more detailed location information if we have a debug state
-                                        if let Some(debug_state) = &debug_state_arc {
-                                            // Clone references needed for the async task
-                                            let app_handle_clone = app_handle.clone();
-                                            let status_seq_clone = Arc::clone(&status_seq);
-                                            let debug_state_clone = debug_state.clone();
-                                            let thread_id_clone = thread_id;
-
-                                            // Use tauri's async runtime instead of tokio directly
-                                            async_runtime::spawn(async move {
-                                                let mut location_found = false;
-
-                                                let client_guard =
-                                                    debug_state_clone.client.lock().await;
-                                                if let Some(client) = client_guard.as_ref() {
-                                                    if let Ok(stack_resp) =
-                                                        client.stack_trace(thread_id_clone).await
-                                                    {
-                                                        if let Some(stack_body) = stack_resp.body {
-                                                            if let Some(frames) = stack_body
-                                                                .get("stackFrames")
-                                                                .and_then(|sf| sf.as_array())
-                                                            {
-                                                                if let Some(frame) = frames.first()
-                                                                {
-                                                                    // Extract source file and line
-                                                                    let source =
-                                                                        frame.get("source");
-                                                                    let line = frame
-                                                                        .get("line")
-                                                                        .and_then(|l| l.as_i64());
-                                                                    if let (
-                                                                        Some(source),
-                                                                        Some(line),
-                                                                    ) = (source, line)
-                                                                    {
-                                                                        let file_path = source
-                                                                            .get("path")
-                                                                            .and_then(|p| {
-                                                                                p.as_str()
-                                                                            });
-                                                                        if let Some(file_path) =
-                                                                            file_path
-                                                                        {
-                                                                            // Emit updated status with location info
-                                                                            let _ = emit_status_update(
-                                                                                &app_handle_clone,
-                                                                                &status_seq_clone,
-                                                                                "paused",
-                                                                                Some(thread_id_clone),
-                                                                                Some(file_path),
-                                                                                Some(line),
-                                                                            );
-                                                                            location_found = true;
-                                                                        }
-                                                                    }
-                                                                }
-                                                            }
-                                                        }
-                                                    }
-                                                }
-
-                                                if !location_found {
-                                                    let _ = emit_status_update(
-                                                        &app_handle_clone,
-                                                        &status_seq_clone,
-                                                        "paused",
-                                                        Some(thread_id),
-                                                        None,
-                                                        None,
-                                                    );
-                                                }
-                                            });
-                                        }
-                                    } else {
-                                        // No thread ID, just emit paused status
-                                        let _ = emit_status_update(
-                                            &app_handle,
-                                            &status_seq,
-                                            "paused",
-                                            None,
-                                            None,
-                                            None,
-                                        );
-                                    }
-                                }
-                            } else if evt == "output" {
-                                // Handle output events from Rust debugger
-                                if let Some(ref body) = msg.body {
-                                    if let Some(category) =
-                                        body.get("category").and_then(|c| c.as_str())
-                                    {
-                                        if category == "stdout" || category == "stderr" {
-                                            if let Some(output) =
-                                                body.get("output").and_then(|o| o.as_str())
-                                            {
-                                                // Forward to UI using the same events as Python output
-                                                let event_name = if category == "stderr" {
-                                                    "program-error"
-                                                } else {
-                                                    "program-output"
-                                                };
-                                                let _ =
-                                                    app_handle.emit(event_name, output.to_string());
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
+        // Handle events that require special processing
+        match event {
+            DapEvent::Terminated(_) => {
+                debug!("Processing 'terminated' event");
+                let _ = emit_status_update_with_extra(
+                    app_handle,
+                    status_seq,
+                    "terminated",
+                    None,
+                    None,
+                    None,
+                    self.session_extra(),
+                );
+                if let Some(debug_state) = &self.debug_state {
+                    emit_session_summary(app_handle, debug_state, "terminated");
+                }
+            }
+            DapEvent::Stopped(body) => {
+                debug!("Processing 'stopped' event: {:?}", body);
+
+                match (body.thread_id, &self.debug_state) {
+                    // Get more detailed location information from the top frame
+                    (Some(thread_id), Some(debug_state)) => {
+                        let mut extra = debug_state.status_extra();
+                        extra.insert(
+                            "allThreadsStopped".to_string(),
+                            serde_json::json!(body.all_threads_stopped.unwrap_or(false)),
+                        );
+                        let stop = Stop {
+                            thread_id,
+                            reason: body.reason.clone(),
+                            hit_breakpoint_ids: body.hit_breakpoint_ids.clone().unwrap_or_default(),
+                            extra,
+                        };
+                        let skip_list = SkipList::new(
+                            &app_handle.state::<Arc<SettingsStore>>().get().never_stop_in,
+                        );
+                        // Use tauri's async runtime instead of tokio directly
+                        async_runtime::spawn(stop::handle_stop(
+                            app_handle.clone(),
+                            Arc::clone(status_seq),
+                            Arc::clone(debug_state),
+                            skip_list,
+                            stop,
+                        ));
                     }
-
-                    // Handle responses and events as before
-                    match msg.message_type {
-                        MessageType::Response => {
-                            if let Some(req_seq) = msg.request_seq {
-                                responses_arc.lock().unwrap().insert(req_seq, msg.clone());
-                            }
-                        }
-                        MessageType::Event => {
-                            if let Some(ref evt) = msg.event {
-                                events_arc
-                                    .lock()
-                                    .unwrap()
-                                    .entry(evt.clone())
-                                    .or_insert_with(Vec::new)
-                                    .push(msg.clone());
+                    (Some(_), None) => {}
+                    // No thread ID, just emit paused status
+                    (None, _) => {
+                        let _ = emit_status_update_with_extra(
+                            app_handle,
+                            status_seq,
+                            "paused",
+                            None,
+                            None,
+                            None,
+                            self.session_extra(),
+                        );
+                    }
+                }
+            }
+            DapEvent::Continued(body) => {
+                if let Some(debug_state) = &self.debug_state {
+                    emit_threads_resumed(app_handle, status_seq, debug_state, body.thread_id);
+                }
+            }
+            DapEvent::Thread(body) => {
+                // The thread map was updated by handle_dap_event above
+                if let Some(debug_state) = &self.debug_state {
+                    let _ = app_handle.emit("threads-changed", debug_state.thread_list());
+
+                    // Thread events carry no names, so refresh them from the adapter. A burst
+                    // of starts shares one threads request, sent once the burst settles.
+                    if body.reason == "started"
+                        && !debug_state
+                            .thread_refresh_pending
+                            .swap(true, Ordering::AcqRel)
+                    {
+                        let app_handle_clone = app_handle.clone();
+                        let debug_state_clone = debug_state.clone();
+                        async_runtime::spawn(async move {
+                            tokio::time::sleep(THREAD_REFRESH_DELAY).await;
+                            // Starts from here on need a request of their own
+                            debug_state_clone
+                                .thread_refresh_pending
+                                .store(false, Ordering::Release);
+                            let client_guard = debug_state_clone.client.lock().await;
+                            if let Some(client) = client_guard.as_ref() {
+                                if let Ok(resp) = client.threads().await {
+                                    debug_state_clone.update_threads(&resp);
+                                    let _ = app_handle_clone
+                                        .emit("threads-changed", debug_state_clone.thread_list());
+                                }
                             }
-                        }
-                        _ => {}
+                        });
                     }
-
-                    // Send the message to any external subscribers
-                    let _ = event_sender.send(msg);
-                } else {
-                    eprintln!("Error parsing message: {}", message_str);
                 }
-            } else {
-                eprintln!("No Content-Length found in header: {}", header);
             }
-
-            // Don't busy‐spin.
-            thread::sleep(Duration::from_millis(10));
-        }));
-    }
-
-    // wait_for_response: polls the internal responses HashMap until the response with the given sequence is available,
-    // or the timeout expires.
-    pub async fn wait_for_response(&self, seq: i32, timeout_secs: f64) -> Option<DAPMessage> {
-        let start = Instant::now();
-        while start.elapsed().as_secs_f64() < timeout_secs {
-            if let Some(resp) = self.responses.lock().unwrap().remove(&seq) {
-                return Some(resp);
+            DapEvent::Output(body) => {
+                // Forward program output to the UI the same way as Python output
+                let stream = match body.category.as_deref() {
+                    Some(stream @ ("stdout" | "stderr")) => stream,
+                    _ => return,
+                };
+                if let Some(debug_state) = &self.debug_state {
+                    emit_console_line(
+                        app_handle,
+                        debug_state,
+                        stream,
+                        body.output.trim_end_matches('\n'),
+                    );
+                }
+            }
+            // debugpy's autoReload reports reloaded modules as changed
+            DapEvent::Module(body) if body.reason == "changed" => {
+                if let Some(debug_state) = &self.debug_state {
+                    debug_state
+                        .timeline
+                        .write()
+                        .record("moduleReloaded", body.module.clone());
+                }
+                let mut payload = self.session_extra();
+                payload.insert("module".to_string(), body.module.clone());
+                let _ = app_handle.emit("module-reloaded", payload);
             }
-            thread::sleep(Duration::from_millis(50));
+            _ => {}
         }
-        None
     }
 
-    // wait_for_event: polls for an event by its name until it arrives or the timeout expires.
-    #[allow(dead_code)]
-    pub fn wait_for_event(&self, name: &str, timeout_secs: f64) -> Option<DAPMessage> {
-        let start = Instant::now();
-        while start.elapsed().as_secs_f64() < timeout_secs {
-            if let Some(mut events) = self.events.lock().ok() {
-                if let Some(list) = events.get_mut(name) {
-                    if !list.is_empty() {
-                        return Some(list.remove(0));
-                    }
+    fn on_response(&self, command: &str, elapsed: Duration, response: &DAPMessage) {
+        self.metrics
+            .record_response(command, elapsed, response.success != Some(false));
+
+        // Not every adapter follows a continue with a "continued" event. Handled here rather
+        // than by the caller so a stop that comes right after isn't overwritten. The response
+        // doesn't name the thread; it's the current one unless all threads resumed.
+        if command == "continue" && response.success != Some(false) {
+            if let Some(debug_state) = &self.debug_state {
+                let all_threads = protocol::body::<ContinueResponseBody>(response)
+                    .ok()
+                    .and_then(|b| b.all_threads_continued)
+                    .unwrap_or(true);
+                let current_thread = *debug_state.current_thread_id.read();
+                if let Some(thread_id) = current_thread {
+                    debug_state.mark_continued(thread_id, all_threads);
+                    emit_threads_resumed(
+                        &self.app_handle,
+                        &self.status_seq,
+                        debug_state,
+                        thread_id,
+                    );
                 }
             }
-            thread::sleep(Duration::from_millis(50));
         }
-        None
-    }
 
-    // initialize: sends an "initialize" request and then waits for its response.
-    pub async fn initialize(&self) -> Result<DAPMessage, Box<dyn std::error::Error>> {
-        let seq = self.send_message(DAPMessage {
-            seq: -1,
-            message_type: MessageType::Request,
-            command: Some("initialize".to_string()),
-            request_seq: None,
-            success: None,
-            arguments: Some(serde_json::json!({
-                "adapterID": "python",
-                "clientID": "dap_test_client",
-                "clientName": "DAP Test",
-                "linesStartAt1": true,
-                "columnsStartAt1": true,
-                "pathFormat": "path",
-                "supportsVariableType": true,
-                "supportsEvaluateForHovers": true
-            })),
-            body: None,
-            event: None,
-        })?;
-        if let Some(response) = self.wait_for_response(seq, 10.0).await {
-            Ok(response)
-        } else {
-            Err("Timeout waiting for initialize response".into())
+        // Surface adapter-reported failures to the UI
+        if response.success == Some(false) {
+            let error_message = ErrorResponseBody::message_of(response)
+                .or_else(|| response.message.clone())
+                .unwrap_or_else(|| "Request failed".to_string());
+            let _ = emit_debug_error(
+                &self.app_handle,
+                "adapter_error",
+                &error_message,
+                Some(&format!("'{}' request failed", command)),
+                true,
+            );
         }
     }
 
-    // attach: sends an "attach" request.
-    pub async fn attach(&self, host: &str, port: u16) -> Result<(), Box<dyn std::error::Error>> {
-        self.send_message(DAPMessage {
-            seq: -1,
-            message_type: MessageType::Request,
-            command: Some("attach".to_string()),
-            request_seq: None,
-            success: None,
-            body: None,
-            event: None,
-            arguments: Some(serde_json::json!({
-                "host": host,
-                "port": port,
-            })),
-        })?;
-        // Give the target a moment to process attach.
-        tokio::time::sleep(Duration::from_millis(700)).await;
-        Ok(())
-    }
-
-    // configuration_done: sends a "configurationDone" request and waits for its response.
-    pub async fn configuration_done(&self) -> Result<DAPMessage, Box<dyn std::error::Error>> {
-        let seq = self.send_message(DAPMessage {
-            seq: -1,
-            message_type: MessageType::Request,
-            command: Some("configurationDone".to_string()),
-            request_seq: None,
-            success: None,
-            arguments: Some(serde_json::json!({})),
-            body: None,
-            event: None,
-        })?;
-        if let Some(response) = self.wait_for_response(seq, 10.0).await {
-            Ok(response)
-        } else {
-            Err("Timeout waiting for configurationDone response".into())
+    // startDebugging: the adapter wants another session (a subprocess, a worker). It's started in
+    // the background; the adapter only needs to know the request was accepted.
+    fn on_request(&self, request: &DAPMessage) -> Result<Option<serde_json::Value>, String> {
+        let command = request.command.as_deref().unwrap_or("");
+        if command != "startDebugging" {
+            return Err(format!("Unsupported request {}", command));
         }
+        let parent = self
+            .debug_state
+            .as_ref()
+            .ok_or("startDebugging outside a debug session")?;
+        let arguments: StartDebuggingRequestArguments =
+            serde_json::from_value(request.arguments.clone().unwrap_or_default())
+                .map_err(|e| format!("Malformed startDebugging arguments: {}", e))?;
+        if !matches!(arguments.request.as_str(), "launch" | "attach") {
+            return Err(format!(
+                "startDebugging needs a request of launch or attach, not {}",
+                arguments.request
+            ));
+        }
+        async_runtime::spawn(crate::start_child_session(
+            self.app_handle.clone(),
+            Arc::clone(parent),
+            arguments,
+        ));
+        Ok(None)
     }
 
-    // set_breakpoints: sends a "setBreakpoints" request and waits for its response.
-    pub async fn set_breakpoints(
-        &self,
-        file_path: String,
-        breakpoints: Vec<BreakpointInput>,
-    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
-        let req = DAPMessage {
-            seq: -1,
-            message_type: MessageType::Request,
-            command: Some("setBreakpoints".to_string()),
-            request_seq: None,
-            success: None,
-            arguments: Some(serde_json::json!({
-                "source": {
-                    "path": file_path,
-                    "name": file_path.split('/').last().unwrap_or("unknown")
-                },
-                "breakpoints": breakpoints,
-                "sourceModified": false
-            })),
-            body: None,
-            event: None,
-        };
-        let seq = self.send_message(req)?;
-        if let Some(response) = self.wait_for_response(seq, 10.0).await {
-            Ok(response)
-        } else {
-            Err("Timeout waiting for setBreakpoints response".into())
-        }
+    fn on_timeout(&self, command: &str, seq: i32, timeout_secs: f64) {
+        self.metrics.record_timeout(command);
+        let _ = emit_debug_error(
+            &self.app_handle,
+            "timeout",
+            &format!("Timed out waiting for '{}' response", command),
+            Some(&format!("request seq={}, timeout={}s", seq, timeout_secs)),
+            true,
+        );
     }
 
-    // stack_trace: sends a "stackTrace" request and waits for its response.
-    pub async fn stack_trace(
-        &self,
-        thread_id: i64,
-    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
-        let seq = self.send_message(DAPMessage {
-            seq: -1,
-            message_type: MessageType::Request,
-            command: Some("stackTrace".to_string()),
-            request_seq: None,
-            success: None,
-            arguments: Some(serde_json::json!({
-                "threadId": thread_id,
-                "startFrame": 0,
-                "levels": 1
-            })),
-            body: None,
-            event: None,
-        })?;
-
-        if let Some(response) = self.wait_for_response(seq, 10.0).await {
-            Ok(response)
-        } else {
-            Err("Timeout waiting for stackTrace response".into())
-        }
+    // The frontend hears about it through on_connection_lost, unless the session was ending anyway
+    fn on_transport_error(&self, error: &std::io::Error) {
+        warn!("DAP transport error: {}", error);
     }
 
-    pub async fn continue_execution(
-        &self,
-        thread_id: i64,
-    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
-        let seq = self.send_message(DAPMessage {
-            seq: -1,
-            message_type: MessageType::Request,
-            command: Some("continue".to_string()),
-            request_seq: None,
-            success: None,
-            arguments: Some(serde_json::json!({
-                "threadId": thread_id
-            })),
-            body: None,
-            event: None,
-        })?;
-
-        if let Some(response) = self.wait_for_response(seq, 10.0).await {
-            Ok(response)
-        } else {
-            Err("Timeout waiting for continue response".into())
+    // The adapter went away mid-session; reconnecting is tried in the background.
+    fn on_connection_lost(&self) {
+        if let Some(debug_state) = &self.debug_state {
+            async_runtime::spawn(crate::reconnect_session(
+                self.app_handle.clone(),
+                Arc::clone(debug_state),
+            ));
         }
     }
+}
 
-    pub async fn step_in(
-        &self,
-        thread_id: i64,
-        granularity: Option<&str>,
-    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
-        let mut args = serde_json::json!({
-            "threadId": thread_id
-        });
-
-        // Add granularity if provided
-        if let Some(g) = granularity {
-            if let serde_json::Value::Object(ref mut map) = args {
-                map.insert("granularity".to_string(), serde_json::json!(g));
-            }
-        }
+// The app's DAP client: the shared dap_client implementation wired to Tauri through
+// TauriEventSink. Requests are available through Deref.
+pub struct DAPClient {
+    inner: dap_client::DAPClient,
+    // event_sender: an optional channel sender that you can use if you want to propagate messages externally.
+    event_sender: mpsc::UnboundedSender<DAPMessage>,
+    // app_handle: the Tauri AppHandle used to emit IPC events.
+    pub app_handle: AppHandle,
+    // status_seq: counter for status update sequence numbers
+    pub status_seq: Arc<AtomicU64>,
+    pub debug_state: Option<Arc<crate::debug_state::DebugSessionState>>,
+}
 
-        let seq = self.send_message(DAPMessage {
-            seq: -1,
-            message_type: MessageType::Request,
-            command: Some("stepIn".to_string()),
-            request_seq: None,
-            success: None,
-            arguments: Some(args),
-            body: None,
-            event: None,
-        })?;
-
-        if let Some(response) = self.wait_for_response(seq, 10.0).await {
-            Ok(response)
-        } else {
-            Err("Timeout waiting for stepIn response".into())
-        }
-    }
+impl DAPClient {
+    // Create a new client along with an mpsc receiver for external subscribers.
+    // This version requires an AppHandle and a DebugSessionState to be provided.
+    pub fn new(
+        app_handle: AppHandle,
+        debug_state: Arc<crate::debug_state::DebugSessionState>,
+    ) -> (Self, mpsc::UnboundedReceiver<DAPMessage>) {
+        let (tx, rx) = mpsc::unbounded_channel();
 
-    pub async fn next(&self, thread_id: i64) -> Result<DAPMessage, Box<dyn std::error::Error>> {
-        let seq = self.send_message(DAPMessage {
-            seq: -1,
-            message_type: MessageType::Request,
-            command: Some("next".to_string()),
-            request_seq: None,
-            success: None,
-            arguments: Some(serde_json::json!({
-                "threadId": thread_id
-            })),
-            body: None,
-            event: None,
-        })?;
-
-        if let Some(response) = self.wait_for_response(seq, 10.0).await {
-            Ok(response)
-        } else {
-            Err("Timeout waiting for next response".into())
+        let mut inner = dap_client::DAPClient::new();
+        if app_handle
+            .state::<Arc<SettingsStore>>()
+            .get()
+            .validate_dap_messages
+        {
+            inner.enable_schema_validation();
         }
+        let client = Self {
+            inner,
+            event_sender: tx,
+            app_handle,
+            status_seq: Arc::new(AtomicU64::new(0)),
+            debug_state: Some(debug_state),
+        };
+
+        (client, rx)
     }
 
-    pub async fn step_out(
-        &self,
-        thread_id: i64,
-        granularity: Option<&str>,
-    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
-        let mut args = serde_json::json!({
-            "threadId": thread_id
-        });
-
-        // Add granularity if provided
-        if let Some(g) = granularity {
-            if let serde_json::Value::Object(ref mut map) = args {
-                map.insert("granularity".to_string(), serde_json::json!(g));
-            }
-        }
+    // Get a reference to the status sequence counter
+    #[allow(dead_code)]
+    pub fn get_status_seq(&self) -> &Arc<AtomicU64> {
+        &self.status_seq
+    }
 
-        let seq = self.send_message(DAPMessage {
-            seq: -1,
-            message_type: MessageType::Request,
-            command: Some("stepOut".to_string()),
-            request_seq: None,
-            success: None,
-            arguments: Some(args),
-            body: None,
-            event: None,
-        })?;
-
-        if let Some(response) = self.wait_for_response(seq, 10.0).await {
-            Ok(response)
-        } else {
-            Err("Timeout waiting for stepOut response".into())
+    // start_receiver: spawns the receiver thread, reporting into the app.
+    pub fn start_receiver(
+        &mut self,
+        external_status_seq: Option<Arc<AtomicU64>>,
+    ) -> std::io::Result<()> {
+        // Use external status sequence counter if provided, otherwise use the one from the client
+        let status_seq = match external_status_seq {
+            Some(seq) => seq,
+            None => Arc::clone(&self.status_seq),
+        };
+        let metrics = match &self.debug_state {
+            Some(debug_state) => Arc::clone(&debug_state.dap_metrics),
+            None => Arc::new(DapMetrics::default()),
+        };
+        let sink = TauriEventSink {
+            app_handle: self.app_handle.clone(),
+            status_seq,
+            debug_state: self.debug_state.clone(),
+            metrics,
+            event_sender: self.event_sender.clone(),
+        };
+        // Everything the receiver thread logs is tagged with the session
+        let span = match &self.debug_state {
+            Some(debug_state) => tracing::info_span!("session", token = %debug_state.token),
+            None => tracing::info_span!("session"),
+        };
+        if let Some(debug_state) = &self.debug_state {
+            *debug_state.canceller.lock() = self.inner.canceller();
         }
+        span.in_scope(|| self.inner.start_receiver(Arc::new(sink)))
     }
+}
 
-    pub async fn evaluate(
-        &self,
-        expression: &str,
-        frame_id: Option<i32>,
-    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
-        // Build arguments according to DAP spec.
-        // Default context is "repl"; if a frame id is provided we override context to "hover".
-        let mut args_json = serde_json::json!({
-            "expression": expression,
-            "context": "repl"
-        });
-
-        if let Some(fid) = frame_id {
-            if let serde_json::Value::Object(ref mut map) = args_json {
-                map.insert("frameId".to_string(), serde_json::json!(fid));
-                map.insert("context".to_string(), serde_json::json!("hover"));
-            }
-        }
+impl Deref for DAPClient {
+    type Target = dap_client::DAPClient;
 
-        let req = DAPMessage {
-            seq: -1,
-            message_type: MessageType::Request,
-            command: Some("evaluate".to_string()),
-            request_seq: None,
-            success: None,
-            arguments: Some(args_json),
-            body: None,
-            event: None,
-        };
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
 
-        let seq = self.send_message(req)?;
-        if let Some(response) = self.wait_for_response(seq, 10.0).await {
-            Ok(response)
-        } else {
-            Err("Timeout waiting for evaluate response".into())
-        }
+impl DerefMut for DAPClient {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
     }
+}
 
-    pub async fn terminate(&self) -> Result<DAPMessage, Box<dyn std::error::Error>> {
-        let seq = self.send_message(DAPMessage {
-            seq: -1,
-            message_type: MessageType::Request,
-            command: Some("terminate".to_string()),
-            request_seq: None,
-            success: None,
-            arguments: Some(serde_json::json!({
-                "restart": false
-            })),
-            body: None,
-            event: None,
-        })?;
-
-        if let Some(response) = self.wait_for_response(seq, 10.0).await {
-            Ok(response)
-        } else {
-            Err("Timeout waiting for terminate response".into())
+// Evaluates every value watch in the top frame of `thread_id` and returns the ones that changed.
+pub async fn check_value_watches(
+    client: &DAPClient,
+    debug_state: &crate::debug_state::DebugSessionState,
+    thread_id: i64,
+) -> Vec<ValueChange> {
+    let frame_id = match client.stack_trace(thread_id).await {
+        Ok(resp) => protocol::body::<StackTraceResponseBody>(&resp)
+            .ok()
+            .and_then(|body| body.stack_frames.first().map(|f| f.id as i32)),
+        Err(_) => None,
+    };
+
+    let expressions: Vec<String> = debug_state
+        .value_watches
+        .read()
+        .iter()
+        .map(|w| w.expression.clone())
+        .collect();
+    let mut changes = Vec::new();
+    for expression in expressions {
+        let value = match client.evaluate(&expression, frame_id).await {
+            Ok(resp) if resp.success != Some(false) => {
+                EvaluateResponseBody::result_of(&resp).unwrap_or_default()
+            }
+            Ok(resp) => format!("<error: {}>", resp.message.unwrap_or_default()),
+            Err(e) => format!("<error: {}>", e),
+        };
+        let mut watches = debug_state.value_watches.write();
+        if let Some(watch) = watches.iter_mut().find(|w| w.expression == expression) {
+            changes.extend(watch.observe(value));
         }
     }
+    changes
 }
@@ -0,0 +1,125 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::debugger::util::lock_or_recover;
+
+// Upper bounds (inclusive, in milliseconds) of the latency histogram buckets. Anything slower
+// than the last bound lands in a final overflow bucket.
+const BUCKET_BOUNDS_MS: &[u64] = &[10, 25, 50, 100, 250, 500, 1000, 2500, 5000, 10000];
+
+#[derive(Debug, Default, Clone)]
+struct CommandStats {
+    count: u64,
+    failures: u64,
+    timeouts: u64,
+    total_ms: f64,
+    max_ms: f64,
+    // One slot per bound plus the overflow bucket
+    buckets: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandLatency {
+    pub count: u64,
+    pub failures: u64,
+    pub timeouts: u64,
+    pub mean_ms: f64,
+    pub max_ms: f64,
+    // Upper bound of the bucket containing the 50th/95th percentile; None when it falls in the
+    // overflow bucket or nothing has completed yet
+    pub p50_ms: Option<u64>,
+    pub p95_ms: Option<u64>,
+    pub buckets: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DapMetricsSnapshot {
+    pub bucket_bounds_ms: Vec<u64>,
+    pub commands: BTreeMap<String, CommandLatency>,
+}
+
+// Per-command request latency, recorded by the DAP client from send to response. Shared
+// through DebugSessionState so the numbers accumulate across sessions.
+#[derive(Debug, Default)]
+pub struct DapMetrics {
+    commands: Mutex<BTreeMap<String, CommandStats>>,
+}
+
+impl DapMetrics {
+    pub fn record_response(&self, command: &str, elapsed: Duration, success: bool) {
+        let elapsed_ms = elapsed.as_secs_f64() * 1000.0;
+        let bucket = BUCKET_BOUNDS_MS
+            .iter()
+            .position(|bound| elapsed_ms <= *bound as f64)
+            .unwrap_or(BUCKET_BOUNDS_MS.len());
+
+        let mut commands = lock_or_recover(&self.commands);
+        let stats = commands.entry(command.to_string()).or_default();
+        if stats.buckets.is_empty() {
+            stats.buckets = vec![0; BUCKET_BOUNDS_MS.len() + 1];
+        }
+        stats.count += 1;
+        if !success {
+            stats.failures += 1;
+        }
+        stats.total_ms += elapsed_ms;
+        stats.max_ms = stats.max_ms.max(elapsed_ms);
+        stats.buckets[bucket] += 1;
+    }
+
+    pub fn record_timeout(&self, command: &str) {
+        let mut commands = lock_or_recover(&self.commands);
+        commands.entry(command.to_string()).or_default().timeouts += 1;
+    }
+
+    pub fn snapshot(&self) -> DapMetricsSnapshot {
+        let commands = lock_or_recover(&self.commands)
+            .iter()
+            .map(|(command, stats)| {
+                let latency = CommandLatency {
+                    count: stats.count,
+                    failures: stats.failures,
+                    timeouts: stats.timeouts,
+                    mean_ms: if stats.count > 0 {
+                        stats.total_ms / stats.count as f64
+                    } else {
+                        0.0
+                    },
+                    max_ms: stats.max_ms,
+                    p50_ms: percentile_bound(&stats.buckets, stats.count, 0.50),
+                    p95_ms: percentile_bound(&stats.buckets, stats.count, 0.95),
+                    buckets: stats.buckets.clone(),
+                };
+                (command.clone(), latency)
+            })
+            .collect();
+
+        DapMetricsSnapshot {
+            bucket_bounds_ms: BUCKET_BOUNDS_MS.to_vec(),
+            commands,
+        }
+    }
+
+    pub fn reset(&self) {
+        lock_or_recover(&self.commands).clear();
+    }
+}
+
+fn percentile_bound(buckets: &[u64], count: u64, quantile: f64) -> Option<u64> {
+    if count == 0 {
+        return None;
+    }
+    let target = (count as f64 * quantile).ceil() as u64;
+    let mut seen = 0;
+    for (index, bucket_count) in buckets.iter().enumerate() {
+        seen += bucket_count;
+        if seen >= target {
+            return BUCKET_BOUNDS_MS.get(index).copied();
+        }
+    }
+    None
+}
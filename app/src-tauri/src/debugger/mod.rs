@@ -1,2 +1,7 @@
 pub mod client;
+pub mod metrics;
+pub mod port_manager;
+pub mod protocol;
+pub mod remote;
+pub mod stop;
 pub mod util;
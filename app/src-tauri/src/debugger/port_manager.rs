@@ -0,0 +1,140 @@
+use socket2::{Domain, Protocol, Socket, Type};
+use std::io::{self, Read};
+use std::net::{IpAddr, Ipv4Addr, SocketAddrV4};
+use std::process::Child;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// How many ports we try before giving up on bringing an adapter up.
+const MAX_LAUNCH_ATTEMPTS: u16 = 10;
+
+pub fn find_available_port(start_port: u16) -> io::Result<u16> {
+    let mut port = start_port;
+    loop {
+        if port_is_free(port)? {
+            return Ok(port);
+        }
+        port = port.saturating_add(1);
+    }
+}
+
+// Whether `port` can be bound on localhost right now.
+fn port_is_free(port: u16) -> io::Result<bool> {
+    // Create an IPv4 TCP socket.
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, Some(Protocol::TCP))?;
+    // Disable address reuse so lingering CLOSE_WAIT connections block new binds.
+    socket.set_reuse_address(false)?;
+
+    let addr = SocketAddrV4::new(Ipv4Addr::LOCALHOST, port);
+    // Convert to a socket2 SockAddr.
+    let sock_addr = socket2::SockAddr::from(addr);
+    Ok(socket.bind(&sock_addr).is_ok())
+}
+
+// DAP over TCP is plaintext and unauthenticated: whoever can reach the socket can drive the
+// debuggee, and everything it sends can be read on the way. Plain connections to adapters the
+// app didn't spawn are held to loopback; a remote adapter is reached over TLS (see
+// remote::TlsOptions) or through a forwarded port (e.g. over SSH).
+pub fn require_loopback(host: &str) -> Result<(), String> {
+    let address = host.trim_start_matches('[').trim_end_matches(']');
+    let loopback = address.eq_ignore_ascii_case("localhost")
+        || address
+            .parse::<IpAddr>()
+            .is_ok_and(|address| address.is_loopback());
+    if loopback {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} is not a loopback address; connect over TLS or forward the port to localhost",
+            host
+        ))
+    }
+}
+
+// Spawns an adapter that binds its own listening port (debugpy --listen, lldb-dap --port).
+// The probe socket from find_available_port is released before the adapter binds, so another
+// process can grab the port in between. When that happens the adapter exits during startup,
+// and we retry with the next free port instead of failing the whole launch. Any other early exit
+// (a missing module, a crash) is reported right away.
+pub fn spawn_on_available_port<F>(
+    start_port: u16,
+    startup_grace: Duration,
+    mut spawn: F,
+) -> Result<(u16, Child), String>
+where
+    F: FnMut(u16) -> io::Result<Child>,
+{
+    let mut next_port = start_port;
+    let mut last_error = String::new();
+
+    for attempt in 1..=MAX_LAUNCH_ATTEMPTS {
+        let port = find_available_port(next_port)
+            .map_err(|e| format!("Could not find available port: {}", e))?;
+
+        let mut child = spawn(port).map_err(|e| e.to_string())?;
+
+        match wait_for_startup(&mut child, startup_grace) {
+            Ok(()) => return Ok((port, child)),
+            Err(e) if !lost_port(port, &e) => {
+                return Err(format!("Adapter exited during startup: {}", e));
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Adapter failed to start on port {} (attempt {}/{}): {}",
+                    port,
+                    attempt,
+                    MAX_LAUNCH_ATTEMPTS,
+                    e
+                );
+                last_error = e;
+                next_port = port.saturating_add(1);
+            }
+        }
+    }
+
+    Err(format!(
+        "Adapter failed to start after {} attempts: {}",
+        MAX_LAUNCH_ATTEMPTS, last_error
+    ))
+}
+
+// Whether an adapter that exited during startup lost `port` to another process: it said the
+// address was in use, or the port is bound now. Adapters on a terminal have no stderr to go by.
+fn lost_port(port: u16, exit_output: &str) -> bool {
+    let output = exit_output.to_lowercase();
+    let address_in_use = [
+        "address already in use",
+        "address in use",
+        "eaddrinuse",
+        // Windows' WSAEADDRINUSE
+        "only one usage of each socket address",
+    ]
+    .iter()
+    .any(|needle| output.contains(needle));
+    address_in_use || !port_is_free(port).unwrap_or(true)
+}
+
+// Waits out the adapter's startup period, returning an error if the process exits before then.
+fn wait_for_startup(child: &mut Child, grace: Duration) -> Result<(), String> {
+    let start = Instant::now();
+    while start.elapsed() < grace {
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                // The process is gone, so its stderr can be drained without blocking.
+                let mut stderr_output = String::new();
+                if let Some(mut stderr) = child.stderr.take() {
+                    let _ = stderr.read_to_string(&mut stderr_output);
+                }
+                return Err(format!(
+                    "process exited with {} ({})",
+                    status,
+                    stderr_output.trim()
+                ));
+            }
+            Ok(None) => {}
+            Err(e) => return Err(format!("could not poll process: {}", e)),
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    Ok(())
+}
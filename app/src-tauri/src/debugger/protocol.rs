@@ -0,0 +1,340 @@
+// Typed bodies of the DAP responses and requests the app reads; event bodies come typed in
+// dap_client's DapEvent. Fields the app doesn't use are left out, and the ones adapters commonly
+// omit, even where the spec requires them, default.
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::client::DAPMessage;
+
+// The body of a successful response, parsed as T. A failed response's error message, or a
+// missing or malformed body, is the error.
+pub fn body<T: DeserializeOwned>(response: &DAPMessage) -> Result<T, String> {
+    let command = response.command.as_deref().unwrap_or("request");
+    if response.success == Some(false) {
+        return Err(format!(
+            "{} failed: {}",
+            command,
+            response.message.as_deref().unwrap_or("unknown error")
+        ));
+    }
+    let body = response
+        .body
+        .clone()
+        .ok_or_else(|| format!("No body in {} response", command))?;
+    serde_json::from_value(body).map_err(|e| format!("Malformed {} response: {}", command, e))
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Source {
+    pub path: Option<String>,
+    // Non-zero when the content has to be fetched with a source request
+    #[serde(default)]
+    pub source_reference: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StackFrame {
+    pub id: i64,
+    #[serde(default)]
+    pub name: String,
+    pub source: Option<Source>,
+    #[serde(default)]
+    pub line: i64,
+    pub column: Option<i64>,
+    pub instruction_pointer_reference: Option<String>,
+}
+
+impl StackFrame {
+    pub fn path(&self) -> Option<&str> {
+        self.source.as_ref().and_then(|s| s.path.as_deref())
+    }
+
+    // The name to show, for adapters that leave it empty.
+    pub fn display_name(&self) -> String {
+        if self.name.is_empty() {
+            "<unknown>".to_string()
+        } else {
+            self.name.clone()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StackTraceResponseBody {
+    #[serde(default)]
+    pub stack_frames: Vec<StackFrame>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Scope {
+    pub name: String,
+    // e.g. "arguments", "locals" or "registers"
+    pub presentation_hint: Option<String>,
+    pub variables_reference: i64,
+    #[serde(default)]
+    pub expensive: bool,
+}
+
+// Generic so scopes passed through to the frontend can be kept as the adapter sent them.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScopesResponseBody<S = Scope> {
+    #[serde(default = "Vec::new")]
+    pub scopes: Vec<S>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Variable {
+    pub name: String,
+    #[serde(default)]
+    pub value: String,
+    #[serde(rename = "type")]
+    pub type_name: Option<String>,
+    pub evaluate_name: Option<String>,
+    // Non-zero when the variable has children
+    #[serde(default)]
+    pub variables_reference: i64,
+    pub indexed_variables: Option<i64>,
+}
+
+impl Variable {
+    // Parses one of the variables passed through to the frontend as JSON.
+    pub fn from_value(value: &Value) -> Option<Self> {
+        Self::deserialize(value).ok()
+    }
+}
+
+// Generic like ScopesResponseBody, for variables passed through to the frontend.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariablesResponseBody<V = Variable> {
+    #[serde(default = "Vec::new")]
+    pub variables: Vec<V>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluateResponseBody {
+    pub result: String,
+    #[serde(rename = "type")]
+    pub type_name: Option<String>,
+    #[serde(default)]
+    pub variables_reference: i64,
+}
+
+impl EvaluateResponseBody {
+    // The body of an evaluate response, if it has one. Success isn't checked: lldb puts the
+    // output of a failed command in the result too.
+    pub fn from_response(response: &DAPMessage) -> Option<Self> {
+        serde_json::from_value(response.body.clone()?).ok()
+    }
+
+    // Just the result text, as from_response.
+    pub fn result_of(response: &DAPMessage) -> Option<String> {
+        Self::from_response(response).map(|b| b.result)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Thread {
+    pub id: i64,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadsResponseBody {
+    #[serde(default)]
+    pub threads: Vec<Thread>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContinueResponseBody {
+    // Missing means true
+    pub all_threads_continued: Option<bool>,
+}
+
+// The body of a failed response, when the adapter gives details beyond the message.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorResponseBody {
+    pub error: Option<ErrorMessage>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorMessage {
+    // The user-facing text, with {placeholders} for variables
+    pub format: String,
+}
+
+impl ErrorResponseBody {
+    // The adapter's description of why `response` failed, if it gave one beyond the message.
+    pub fn message_of(response: &DAPMessage) -> Option<String> {
+        let body: ErrorResponseBody = serde_json::from_value(response.body.clone()?).ok()?;
+        body.error.map(|e| e.format)
+    }
+}
+
+// A place execution can jump to, from gotoTargets, or a call to step into, from stepInTargets.
+// Passed on to the frontend as the adapter sent it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Target {
+    pub id: i64,
+    #[serde(default)]
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_column: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instruction_pointer_reference: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TargetsResponseBody {
+    #[serde(default)]
+    pub targets: Vec<Target>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadedSourcesResponseBody {
+    #[serde(default)]
+    pub sources: Vec<Value>,
+}
+
+// The arguments of the adapter's startDebugging reverse request.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartDebuggingRequestArguments {
+    // "launch" or "attach"
+    pub request: String,
+    // Sent back to the adapter as the arguments of `request`
+    pub configuration: serde_json::Map<String, Value>,
+}
+
+// The parts of a startDebugging configuration the app reads; the rest is the adapter's business.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChildConfiguration {
+    pub name: Option<String>,
+    // Where to connect, when it isn't the parent's adapter (debugpy's subprocess attach)
+    pub connect: Option<ConnectTarget>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectTarget {
+    #[serde(default = "localhost")]
+    pub host: String,
+    pub port: u16,
+}
+
+fn localhost() -> String {
+    "127.0.0.1".to_string()
+}
+
+impl StartDebuggingRequestArguments {
+    pub fn child(&self) -> ChildConfiguration {
+        ChildConfiguration::deserialize(Value::Object(self.configuration.clone()))
+            .unwrap_or_default()
+    }
+}
+
+// Why a stopped event was sent. The reasons the spec names are spelled out, so code deciding
+// what to do with a stop has to handle each one; anything adapter-specific is kept as sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StopReason {
+    Step,
+    Breakpoint,
+    Exception,
+    Pause,
+    Entry,
+    Goto,
+    FunctionBreakpoint,
+    DataBreakpoint,
+    InstructionBreakpoint,
+    // After restartFrame, for adapters that report it this way
+    Restart,
+    Other(String),
+}
+
+impl StopReason {
+    pub fn parse(reason: &str) -> Self {
+        match reason {
+            "step" => Self::Step,
+            "breakpoint" => Self::Breakpoint,
+            "exception" => Self::Exception,
+            "pause" => Self::Pause,
+            "entry" => Self::Entry,
+            "goto" => Self::Goto,
+            "function breakpoint" => Self::FunctionBreakpoint,
+            "data breakpoint" => Self::DataBreakpoint,
+            "instruction breakpoint" => Self::InstructionBreakpoint,
+            "restart" => Self::Restart,
+            other => Self::Other(other.to_string()),
+        }
+    }
+
+    // Whether break-on-value-change may resume past this stop when no watched value changed:
+    // only where the program ran into a step or a line or instruction breakpoint. Function
+    // breakpoints (break on panic, break at entry) and data breakpoints, which are the adapter's
+    // own change detection, always stop, as do exceptions, pauses, jumps, frame restarts and
+    // reasons we don't know.
+    pub fn resumable_by_value_watch(&self) -> bool {
+        match self {
+            Self::Step | Self::Breakpoint | Self::InstructionBreakpoint => true,
+            Self::Exception
+            | Self::Pause
+            | Self::Entry
+            | Self::Goto
+            | Self::FunctionBreakpoint
+            | Self::DataBreakpoint
+            | Self::Restart
+            | Self::Other(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stop_reasons_parse_to_their_variants() {
+        assert_eq!(
+            StopReason::parse("function breakpoint"),
+            StopReason::FunctionBreakpoint
+        );
+        assert_eq!(StopReason::parse("goto"), StopReason::Goto);
+        assert_eq!(
+            StopReason::parse("signal"),
+            StopReason::Other("signal".to_string())
+        );
+    }
+
+    #[test]
+    fn value_watches_only_resume_past_steps_and_breakpoints() {
+        assert!(StopReason::parse("step").resumable_by_value_watch());
+        assert!(StopReason::parse("breakpoint").resumable_by_value_watch());
+        assert!(!StopReason::parse("function breakpoint").resumable_by_value_watch());
+        assert!(!StopReason::parse("data breakpoint").resumable_by_value_watch());
+        assert!(!StopReason::parse("exception").resumable_by_value_watch());
+        assert!(!StopReason::parse("signal").resumable_by_value_watch());
+    }
+}
@@ -0,0 +1,344 @@
+use dap_client::RetryPolicy;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use rustls::{ClientConfig, RootCertStore};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufReader, ErrorKind};
+use std::net::{Ipv4Addr, TcpStream as StdTcpStream};
+use std::sync::Arc;
+use tauri::async_runtime;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::TlsConnector;
+use tracing::debug;
+
+// Where a session's adapter listens, kept so child sessions and re-attach can connect again.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdapterEndpoint {
+    pub host: String,
+    pub port: u16,
+    pub tls: Option<TlsOptions>,
+}
+
+impl AdapterEndpoint {
+    // An adapter the app spawned itself
+    pub fn local(port: u16) -> Self {
+        AdapterEndpoint {
+            host: "127.0.0.1".to_string(),
+            port,
+            tls: None,
+        }
+    }
+
+    pub fn address(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+}
+
+// TLS for an adapter on another machine. debugpy and lldb-dap only speak plain DAP, so the remote
+// end is a TLS terminator in front of the adapter (stunnel, an ingress, ...). Having it require
+// the client certificate is what keeps others from driving the debuggee: debugpy's access token
+// only authenticates debuggees connecting to an adapter, never a client connecting to one.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TlsOptions {
+    // PEM bundle of certificate authorities to trust instead of the public web roots, e.g. a
+    // self-signed terminator's own certificate
+    pub ca_file: Option<String>,
+    // Name the server certificate has to be issued for; defaults to the host
+    pub server_name: Option<String>,
+    // PEM certificate chain and private key presented to a terminator that asks for them
+    pub client_cert_file: Option<String>,
+    pub client_key_file: Option<String>,
+}
+
+impl TlsOptions {
+    fn client_config(&self) -> io::Result<ClientConfig> {
+        let mut roots = RootCertStore::empty();
+        match &self.ca_file {
+            Some(ca_file) => {
+                for cert in read_certs(ca_file)? {
+                    roots.add(cert).map_err(|e| invalid(ca_file, e))?;
+                }
+            }
+            None => roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()),
+        }
+        let builder = ClientConfig::builder().with_root_certificates(roots);
+
+        match (&self.client_cert_file, &self.client_key_file) {
+            (Some(cert_file), Some(key_file)) => {
+                let key = PrivateKeyDer::from_pem_file(expand(key_file))
+                    .map_err(|e| invalid(key_file, e))?;
+                builder
+                    .with_client_auth_cert(read_certs(cert_file)?, key)
+                    .map_err(|e| invalid(cert_file, e))
+            }
+            (None, None) => Ok(builder.with_no_client_auth()),
+            _ => Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "clientCertFile and clientKeyFile go together",
+            )),
+        }
+    }
+}
+
+fn expand(path: &str) -> String {
+    shellexpand::tilde(path).into_owned()
+}
+
+fn invalid(path: &str, error: impl std::fmt::Display) -> io::Error {
+    io::Error::new(ErrorKind::InvalidInput, format!("{}: {}", path, error))
+}
+
+fn read_certs(path: &str) -> io::Result<Vec<CertificateDer<'static>>> {
+    let file = File::open(expand(path)).map_err(|e| invalid(path, e))?;
+    let certs = CertificateDer::pem_reader_iter(BufReader::new(file))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| invalid(path, e))?;
+    if certs.is_empty() {
+        return Err(invalid(path, "no certificates found"));
+    }
+    Ok(certs)
+}
+
+// Opens a TLS connection to `endpoint`. DAPClient reads and writes its transport from separate
+// threads, which a TLS session can't be split for, so the session is relayed through a loopback
+// socket pair and the client gets the plain end.
+pub async fn connect_tls(endpoint: &AdapterEndpoint, tls: &TlsOptions) -> io::Result<StdTcpStream> {
+    let config = tls.client_config()?;
+    let name = tls.server_name.as_deref().unwrap_or(&endpoint.host);
+    let name = ServerName::try_from(name.trim_start_matches('[').trim_end_matches(']'))
+        .map_err(|e| invalid(name, e))?
+        .to_owned();
+    let socket = TcpStream::connect((endpoint.host.as_str(), endpoint.port)).await?;
+    let mut session = TlsConnector::from(Arc::new(config))
+        .connect(name, socket)
+        .await?;
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+    let (local, (mut relay, peer)) = tokio::try_join!(
+        TcpStream::connect(listener.local_addr()?),
+        listener.accept()
+    )?;
+    if peer != local.local_addr()? {
+        return Err(io::Error::new(
+            ErrorKind::PermissionDenied,
+            "Another process connected to the TLS relay",
+        ));
+    }
+    let address = endpoint.address();
+    async_runtime::spawn(async move {
+        if let Err(e) = tokio::io::copy_bidirectional(&mut relay, &mut session).await {
+            debug!("TLS relay to {} closed: {}", address, e);
+        }
+    });
+
+    let local = local.into_std()?;
+    local.set_nonblocking(false)?;
+    Ok(local)
+}
+
+// Connects `client` to `endpoint`, over TLS when it has options for it.
+pub async fn connect(
+    client: &mut dap_client::DAPClient,
+    endpoint: &AdapterEndpoint,
+) -> io::Result<()> {
+    match &endpoint.tls {
+        Some(tls) => {
+            let stream = connect_tls(endpoint, tls).await?;
+            let reader = BufReader::new(stream.try_clone()?);
+            client.connect_streams(reader, stream);
+            Ok(())
+        }
+        None => client.connect(&endpoint.host, endpoint.port),
+    }
+}
+
+// Like connect, retrying with backoff while nothing is listening yet. `on_retry` is told about
+// each failed attempt that will be retried.
+pub async fn connect_with_retry(
+    client: &mut dap_client::DAPClient,
+    endpoint: &AdapterEndpoint,
+    policy: &RetryPolicy,
+    mut on_retry: impl FnMut(u32, &io::Error),
+) -> io::Result<()> {
+    let mut attempt = 1;
+    loop {
+        match connect(client, endpoint).await {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt >= policy.attempts => return Err(e),
+            Err(e) => {
+                on_retry(attempt, &e);
+                tokio::time::sleep(policy.delay(attempt)).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rcgen::{BasicConstraints, CertificateParams, CertifiedIssuer, IsCa, KeyPair};
+    use rustls::server::WebPkiClientVerifier;
+    use rustls::ServerConfig;
+    use std::io::{Read, Write};
+    use std::path::PathBuf;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio_rustls::TlsAcceptor;
+
+    // A CA with a server certificate for localhost and a client certificate, the CA and client
+    // files written to a temp dir for TlsOptions.
+    struct Pki {
+        dir: PathBuf,
+        ca: CertificateDer<'static>,
+        server_cert: CertificateDer<'static>,
+        server_key: PrivateKeyDer<'static>,
+    }
+
+    impl Pki {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "wayfind-remote-{}-{}",
+                std::process::id(),
+                name
+            ));
+            std::fs::create_dir_all(&dir).unwrap();
+
+            let mut ca_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+            ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+            let ca = CertifiedIssuer::self_signed(ca_params, KeyPair::generate().unwrap()).unwrap();
+            let server_key = KeyPair::generate().unwrap();
+            let server_cert = CertificateParams::new(vec!["localhost".to_string()])
+                .unwrap()
+                .signed_by(&server_key, &ca)
+                .unwrap();
+            let client_key = KeyPair::generate().unwrap();
+            let client_cert = CertificateParams::new(vec!["wayfind".to_string()])
+                .unwrap()
+                .signed_by(&client_key, &ca)
+                .unwrap();
+
+            std::fs::write(dir.join("ca.pem"), ca.pem()).unwrap();
+            std::fs::write(dir.join("client.pem"), client_cert.pem()).unwrap();
+            std::fs::write(dir.join("client.key"), client_key.serialize_pem()).unwrap();
+            Pki {
+                dir,
+                ca: ca.der().clone(),
+                server_cert: server_cert.der().clone(),
+                server_key: PrivateKeyDer::try_from(server_key.serialize_der()).unwrap(),
+            }
+        }
+
+        fn file(&self, name: &str) -> Option<String> {
+            Some(self.dir.join(name).to_string_lossy().into_owned())
+        }
+
+        fn client_options(&self) -> TlsOptions {
+            TlsOptions {
+                ca_file: self.file("ca.pem"),
+                server_name: Some("localhost".to_string()),
+                client_cert_file: self.file("client.pem"),
+                client_key_file: self.file("client.key"),
+            }
+        }
+
+        // A terminator that requires a client certificate from the CA and answers "ping" with
+        // "pong".
+        async fn serve(&self) -> AdapterEndpoint {
+            let mut roots = RootCertStore::empty();
+            roots.add(self.ca.clone()).unwrap();
+            let verifier = WebPkiClientVerifier::builder(Arc::new(roots))
+                .build()
+                .unwrap();
+            let config = ServerConfig::builder()
+                .with_client_cert_verifier(verifier)
+                .with_single_cert(vec![self.server_cert.clone()], self.server_key.clone_key())
+                .unwrap();
+            let acceptor = TlsAcceptor::from(Arc::new(config));
+            let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+            let port = listener.local_addr().unwrap().port();
+            tokio::spawn(async move {
+                let (socket, _) = listener.accept().await.unwrap();
+                let Ok(mut stream) = acceptor.accept(socket).await else {
+                    return;
+                };
+                let mut request = [0; 4];
+                if stream.read_exact(&mut request).await.is_ok() && &request == b"ping" {
+                    let _ = stream.write_all(b"pong").await;
+                    let _ = stream.flush().await;
+                }
+            });
+            AdapterEndpoint {
+                host: "127.0.0.1".to_string(),
+                port,
+                tls: None,
+            }
+        }
+    }
+
+    impl Drop for Pki {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    // Sends "ping" over the relayed stream and returns what comes back.
+    async fn ping(mut stream: StdTcpStream) -> io::Result<Vec<u8>> {
+        tokio::task::spawn_blocking(move || {
+            stream.write_all(b"ping")?;
+            let mut reply = Vec::new();
+            stream.read_to_end(&mut reply)?;
+            Ok(reply)
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn relays_over_mutually_authenticated_tls() {
+        let pki = Pki::new("mutual");
+        let endpoint = pki.serve().await;
+        let stream = connect_tls(&endpoint, &pki.client_options()).await.unwrap();
+        assert_eq!(ping(stream).await.unwrap(), b"pong");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn terminator_turns_away_clients_without_a_certificate() {
+        let pki = Pki::new("anonymous");
+        let endpoint = pki.serve().await;
+        let options = TlsOptions {
+            client_cert_file: None,
+            client_key_file: None,
+            ..pki.client_options()
+        };
+        // TLS 1.3 reports the rejected client only after the handshake
+        match connect_tls(&endpoint, &options).await {
+            Ok(stream) => assert_ne!(ping(stream).await.unwrap_or_default(), b"pong"),
+            Err(e) => assert_eq!(e.kind(), ErrorKind::InvalidData),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn refuses_an_untrusted_server() {
+        let pki = Pki::new("untrusted");
+        let other = Pki::new("other-ca");
+        let endpoint = pki.serve().await;
+        let error = connect_tls(&endpoint, &other.client_options())
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn client_cert_needs_its_key() {
+        let options = TlsOptions {
+            client_cert_file: Some("client.pem".to_string()),
+            ..TlsOptions::default()
+        };
+        assert_eq!(
+            options.client_config().unwrap_err().kind(),
+            ErrorKind::InvalidInput
+        );
+    }
+}
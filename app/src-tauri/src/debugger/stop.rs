@@ -0,0 +1,540 @@
+// What happens when a thread stops. Each concern (the stepping queue, thread names,
+// disableAfterHits, value watches, the never-stop list, the paused location, watch expressions,
+// the stop history) is its own function. The ones that may resume the thread instead of
+// reporting the stop return a StopDecision, and handle_stop acts on the first that asks to.
+use serde_json::{json, Map, Value};
+use std::sync::atomic::AtomicU64;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+use tracing::warn;
+
+use crate::debug_state::DebugSessionState;
+use crate::debugger::client::{check_value_watches, emit_status_update_with_extra, DAPClient};
+use crate::debugger::protocol::{
+    self, EvaluateResponseBody, StackFrame, StackTraceResponseBody, StopReason,
+};
+use crate::skip_list::{SkipAction, SkipList};
+use crate::stepping::Step;
+use crate::stop_history::{self, WatchValue};
+use crate::value_watch::ValueChange;
+
+// A thread's stopped event, filled in as the handlers learn more about it.
+pub struct Stop {
+    pub thread_id: i64,
+    pub reason: String,
+    pub hit_breakpoint_ids: Vec<i64>,
+    // Payload of the "paused" status update
+    pub extra: Map<String, Value>,
+}
+
+// How to get the thread going again.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Resume {
+    Continue,
+    Next,
+    StepOut,
+    // The step that was waiting for this stop
+    Step(Step),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StopDecision {
+    Report,
+    // Resume rather than report; the timeline entry is recorded once the thread runs again
+    Resume {
+        how: Resume,
+        timeline: Option<(&'static str, Value)>,
+    },
+}
+
+pub async fn handle_stop(
+    app_handle: AppHandle,
+    status_seq: Arc<AtomicU64>,
+    debug_state: Arc<DebugSessionState>,
+    skip_list: SkipList,
+    mut stop: Stop,
+) {
+    let mut location = None;
+    let mut snapshot_frames = Vec::new();
+    let mut watch_values = Vec::new();
+
+    // A step that lands while another is waiting isn't reported; the waiting one goes out instead
+    let next_step = debug_state
+        .stepping
+        .lock()
+        .stopped(stop.thread_id, &stop.reason);
+
+    let client_guard = debug_state.client.lock().await;
+    if let Some(client) = client_guard.as_ref() {
+        if let Some(step) = next_step {
+            if resumed(client, &debug_state, &stop, queued_step(&stop, step)).await {
+                return;
+            }
+        }
+
+        if resumed(
+            client,
+            &debug_state,
+            &stop,
+            panic_decision(&debug_state, &stop),
+        )
+        .await
+        {
+            return;
+        }
+
+        name_thread(&app_handle, &debug_state, client, &mut stop).await;
+        disable_exhausted_breakpoints(&app_handle, &debug_state, client, &stop).await;
+
+        let decision = check_value_watches_at_stop(&debug_state, client, &mut stop).await;
+        if resumed(client, &debug_state, &stop, decision).await {
+            return;
+        }
+
+        let frames = top_frames(client, stop.thread_id).await;
+        if resumed(
+            client,
+            &debug_state,
+            &stop,
+            skip_decision(&skip_list, &stop, &frames),
+        )
+        .await
+        {
+            return;
+        }
+
+        snapshot_frames = stop_history::frames_from_stack(&frames);
+        if let Some(frame) = frames.first() {
+            location = report_location(&app_handle, &status_seq, &debug_state, &mut stop, frame);
+        }
+        let frame_id = frames.first().map(|frame| frame.id as i32);
+        watch_values = evaluate_watch_expressions(&debug_state, client, frame_id).await;
+    }
+    drop(client_guard);
+
+    record_stop(
+        &debug_state,
+        &stop,
+        location.as_ref(),
+        snapshot_frames,
+        watch_values,
+    );
+    if location.is_none() && debug_state.is_thread_paused(stop.thread_id) {
+        let _ = emit_status_update_with_extra(
+            &app_handle,
+            &status_seq,
+            "paused",
+            Some(stop.thread_id),
+            None,
+            None,
+            stop.extra,
+        );
+    }
+}
+
+// Carries out a decision to resume. False when the stop is to be reported, including when
+// resuming failed.
+async fn resumed(
+    client: &DAPClient,
+    debug_state: &DebugSessionState,
+    stop: &Stop,
+    decision: StopDecision,
+) -> bool {
+    let StopDecision::Resume { how, timeline } = decision else {
+        return false;
+    };
+    let thread_id = stop.thread_id;
+    let result = match &how {
+        Resume::Continue => client
+            .continue_execution(thread_id, false)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string()),
+        Resume::Next => client
+            .next(thread_id, None, false)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string()),
+        Resume::StepOut => client
+            .step_out(thread_id, None, false)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string()),
+        Resume::Step(step) => step.send(client, thread_id).await,
+    };
+    match result {
+        Ok(()) => {
+            if let Some((name, data)) = timeline {
+                debug_state.timeline.write().record(name, data);
+            }
+            true
+        }
+        Err(e) => {
+            warn!("Failed to resume thread {} past its stop: {}", thread_id, e);
+            if let Resume::Step(_) = how {
+                debug_state.stepping.lock().cancel(thread_id);
+            }
+            false
+        }
+    }
+}
+
+fn queued_step(stop: &Stop, step: Step) -> StopDecision {
+    let timeline = json!({
+        "threadId": stop.thread_id,
+        "granularity": step.granularity,
+        "singleThread": step.single_thread,
+        "queued": true,
+    });
+    StopDecision::Resume {
+        timeline: Some((step.kind.name(), timeline)),
+        how: Resume::Step(step),
+    }
+}
+
+// A panic passes through several of RUST_PANIC_FUNCTIONS and only stops at the first it reaches.
+fn panic_decision(debug_state: &DebugSessionState, stop: &Stop) -> StopDecision {
+    let position = debug_state.panic_function_hit(&stop.hit_breakpoint_ids);
+    let mut panic_stop = debug_state.panic_stop.lock();
+    let previous = *panic_stop;
+    let Some(position) = position else {
+        // Any other stop of the thread ends its panic
+        if previous.is_some_and(|(thread, _)| thread == stop.thread_id) {
+            *panic_stop = None;
+        }
+        return StopDecision::Report;
+    };
+    *panic_stop = Some((stop.thread_id, position));
+    if continues_panic(previous, stop.thread_id, position) {
+        StopDecision::Resume {
+            how: Resume::Continue,
+            timeline: None,
+        }
+    } else {
+        StopDecision::Report
+    }
+}
+
+// Whether a stop at the panic function at `position` is the panic `previous` stopped for, which
+// it is when the same thread got there further down RUST_PANIC_FUNCTIONS. A new panic starts
+// over at panic_fmt, or at rust_panic for `panic_any`.
+fn continues_panic(previous: Option<(i64, usize)>, thread_id: i64, position: usize) -> bool {
+    previous.is_some_and(|(thread, earlier)| thread == thread_id && position > earlier)
+}
+
+// Threads that started before we were listening have no name yet.
+async fn name_thread(
+    app_handle: &AppHandle,
+    debug_state: &DebugSessionState,
+    client: &DAPClient,
+    stop: &mut Stop,
+) {
+    if debug_state.thread_name(stop.thread_id).is_none() {
+        if let Ok(response) = client.threads().await {
+            debug_state.update_threads(&response);
+        }
+    }
+    if let Some(name) = debug_state.thread_name(stop.thread_id) {
+        stop.extra.insert("threadName".to_string(), json!(name));
+    }
+    let _ = app_handle.emit("threads-changed", debug_state.thread_list());
+}
+
+// Switches off breakpoints that just reached their disableAfterHits.
+async fn disable_exhausted_breakpoints(
+    app_handle: &AppHandle,
+    debug_state: &DebugSessionState,
+    client: &DAPClient,
+    stop: &Stop,
+) {
+    let exhausted = debug_state
+        .breakpoints
+        .write()
+        .record_hits(&stop.hit_breakpoint_ids);
+    for file in exhausted {
+        if let Ok(Some(body)) = crate::breakpoints::send(client, &debug_state.breakpoints, &file)
+            .await
+            .map(|response| response.body)
+        {
+            debug_state
+                .breakpoints
+                .write()
+                .record_adapter_ids(&file, &body);
+        }
+        let mut payload = debug_state.status_extra();
+        payload.insert(
+            "lines".to_string(),
+            json!(debug_state.breakpoints.read().disabled_lines(&file)),
+        );
+        payload.insert("file".to_string(), json!(file));
+        let _ = app_handle.emit("breakpoints-auto-disabled", payload);
+    }
+}
+
+// Break-on-value-change fallback. A change turns the stop into a "valueChanged" one.
+async fn check_value_watches_at_stop(
+    debug_state: &DebugSessionState,
+    client: &DAPClient,
+    stop: &mut Stop,
+) -> StopDecision {
+    let watching = !debug_state.value_watches.read().is_empty();
+    if !watching || !StopReason::parse(&stop.reason).resumable_by_value_watch() {
+        return StopDecision::Report;
+    }
+    let changes = check_value_watches(client, debug_state, stop.thread_id).await;
+    let step = debug_state.value_watches.read().iter().any(|w| w.step);
+    if !changes.is_empty() {
+        stop.reason = "valueChanged".to_string();
+        stop.extra.insert("reason".to_string(), json!(stop.reason));
+        stop.extra
+            .insert("valueChanges".to_string(), json!(changes));
+        debug_state
+            .timeline
+            .write()
+            .record("valueChanged", json!(changes));
+    }
+    value_watch_decision(&changes, step)
+}
+
+// Stops where no watched value changed are resumed: line by line when a watch asked to be
+// checked on every line, otherwise up to the next stop.
+fn value_watch_decision(changes: &[ValueChange], step: bool) -> StopDecision {
+    if !changes.is_empty() {
+        return StopDecision::Report;
+    }
+    let how = if step { Resume::Next } else { Resume::Continue };
+    StopDecision::Resume {
+        how,
+        timeline: None,
+    }
+}
+
+// The top few frames of the stopped thread, which the stop history keeps.
+async fn top_frames(client: &DAPClient, thread_id: i64) -> Vec<StackFrame> {
+    // Bound first: the error isn't Send
+    let response = client
+        .stack_trace_page(thread_id, 0, stop_history::SNAPSHOT_FRAMES)
+        .await
+        .ok();
+    response
+        .and_then(|response| protocol::body::<StackTraceResponseBody>(&response).ok())
+        .map(|body| body.stack_frames)
+        .unwrap_or_default()
+}
+
+// Stray stops in code the user never wants to stop in are stepped out of or continued past.
+fn skip_decision(skip_list: &SkipList, stop: &Stop, frames: &[StackFrame]) -> StopDecision {
+    let Some(path) = frames.first().and_then(|frame| frame.path()) else {
+        return StopDecision::Report;
+    };
+    let Some(action) = skip_list.action(&stop.reason, path) else {
+        return StopDecision::Report;
+    };
+    let how = match action {
+        SkipAction::StepOut => Resume::StepOut,
+        SkipAction::Continue => Resume::Continue,
+    };
+    let timeline = json!({
+        "reason": stop.reason,
+        "threadId": stop.thread_id,
+        "file": path,
+    });
+    StopDecision::Resume {
+        how,
+        timeline: Some(("stopSkipped", timeline)),
+    }
+}
+
+// Reports the paused location, unless a resume already reported "running", and tracks the
+// instruction pointer so the disassembly view follows each step. Returns the location.
+fn report_location(
+    app_handle: &AppHandle,
+    status_seq: &AtomicU64,
+    debug_state: &DebugSessionState,
+    stop: &mut Stop,
+    frame: &StackFrame,
+) -> Option<(String, i64)> {
+    let instruction_pointer = frame.instruction_pointer_reference.clone();
+    if let Some(ip) = &instruction_pointer {
+        stop.extra
+            .insert("instructionPointerReference".to_string(), json!(ip));
+    }
+    *debug_state.current_instruction_pointer.write() = instruction_pointer;
+
+    let file_path = frame.path()?;
+    if debug_state.is_thread_paused(stop.thread_id) {
+        let _ = emit_status_update_with_extra(
+            app_handle,
+            status_seq,
+            "paused",
+            Some(stop.thread_id),
+            Some(file_path),
+            Some(frame.line),
+            stop.extra.clone(),
+        );
+    }
+    Some((file_path.to_string(), frame.line))
+}
+
+// The watch expressions' values in the top frame, for the stop history.
+async fn evaluate_watch_expressions(
+    debug_state: &DebugSessionState,
+    client: &DAPClient,
+    frame_id: Option<i32>,
+) -> Vec<WatchValue> {
+    let expressions = debug_state.watch_expressions.read().clone();
+    let mut values = Vec::new();
+    for expression in expressions {
+        let value = match client.evaluate(&expression, frame_id).await {
+            Ok(response) if response.success != Some(false) => {
+                EvaluateResponseBody::result_of(&response).unwrap_or_default()
+            }
+            Ok(response) => format!("<error: {}>", response.message.unwrap_or_default()),
+            Err(e) => format!("<error: {}>", e),
+        };
+        values.push(WatchValue { expression, value });
+    }
+    values
+}
+
+fn record_stop(
+    debug_state: &DebugSessionState,
+    stop: &Stop,
+    location: Option<&(String, i64)>,
+    snapshot_frames: Vec<stop_history::SnapshotFrame>,
+    watch_values: Vec<WatchValue>,
+) {
+    let thread_name = debug_state.thread_name(stop.thread_id);
+    debug_state.stop_history.write().record(
+        &stop.reason,
+        stop.thread_id,
+        thread_name.clone(),
+        snapshot_frames,
+        watch_values,
+    );
+    debug_state.timeline.write().record(
+        "stopped",
+        json!({
+            "reason": stop.reason,
+            "threadId": stop.thread_id,
+            "threadName": thread_name,
+            "file": location.map(|(file, _)| file),
+            "line": location.map(|(_, line)| line),
+        }),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::debugger::protocol::Source;
+    use crate::stepping::StepKind;
+
+    fn stop(reason: &str) -> Stop {
+        Stop {
+            thread_id: 1,
+            reason: reason.to_string(),
+            hit_breakpoint_ids: Vec::new(),
+            extra: Map::new(),
+        }
+    }
+
+    fn frame_in(path: &str) -> StackFrame {
+        StackFrame {
+            id: 1000,
+            name: "handler".to_string(),
+            source: Some(Source {
+                path: Some(path.to_string()),
+                source_reference: 0,
+            }),
+            line: 12,
+            column: None,
+            instruction_pointer_reference: None,
+        }
+    }
+
+    #[test]
+    fn queued_step_resumes_with_the_waiting_step() {
+        let step = Step {
+            kind: StepKind::Over,
+            granularity: None,
+            single_thread: false,
+            target_id: None,
+        };
+        match queued_step(&stop("step"), step.clone()) {
+            StopDecision::Resume { how, timeline } => {
+                assert_eq!(how, Resume::Step(step));
+                assert_eq!(timeline.map(|(name, _)| name), Some("stepOver"));
+            }
+            StopDecision::Report => panic!("a queued step should resume"),
+        }
+    }
+
+    #[test]
+    fn a_panic_stops_once_on_its_way_through_the_panic_functions() {
+        // Stopped at panic_fmt, then reaching rust_begin_unwind and rust_panic
+        assert!(continues_panic(Some((1, 0)), 1, 1));
+        assert!(continues_panic(Some((1, 1)), 1, 2));
+        // A new panic on the same thread starts over
+        assert!(!continues_panic(Some((1, 2)), 1, 0));
+        assert!(!continues_panic(Some((1, 2)), 1, 2));
+        // Other threads panic on their own
+        assert!(!continues_panic(Some((1, 0)), 2, 1));
+        assert!(!continues_panic(None, 1, 2));
+    }
+
+    #[test]
+    fn unchanged_value_watches_resume() {
+        assert_eq!(
+            value_watch_decision(&[], false),
+            StopDecision::Resume {
+                how: Resume::Continue,
+                timeline: None
+            }
+        );
+        assert_eq!(
+            value_watch_decision(&[], true),
+            StopDecision::Resume {
+                how: Resume::Next,
+                timeline: None
+            }
+        );
+    }
+
+    #[test]
+    fn changed_value_watches_report() {
+        let change = ValueChange {
+            expression: "total".to_string(),
+            old_value: "1".to_string(),
+            new_value: "2".to_string(),
+        };
+        assert_eq!(value_watch_decision(&[change], true), StopDecision::Report);
+    }
+
+    #[test]
+    fn steps_into_skipped_code_step_out() {
+        let skip_list = SkipList::new(&["site-packages".to_string()]);
+        let frames = [frame_in("/venv/lib/site-packages/flask/app.py")];
+        match skip_decision(&skip_list, &stop("step"), &frames) {
+            StopDecision::Resume { how, timeline } => {
+                assert_eq!(how, Resume::StepOut);
+                assert_eq!(timeline.map(|(name, _)| name), Some("stopSkipped"));
+            }
+            StopDecision::Report => panic!("a step into skipped code should step out"),
+        }
+    }
+
+    #[test]
+    fn breakpoints_in_skipped_code_still_stop() {
+        let skip_list = SkipList::new(&["site-packages".to_string()]);
+        let frames = [frame_in("/venv/lib/site-packages/flask/app.py")];
+        assert_eq!(
+            skip_decision(&skip_list, &stop("breakpoint"), &frames),
+            StopDecision::Report
+        );
+        assert_eq!(
+            skip_decision(&skip_list, &stop("step"), &[frame_in("/src/app.py")]),
+            StopDecision::Report
+        );
+    }
+}
@@ -1,24 +1,4 @@
-use socket2::{Domain, Protocol, Socket, Type};
-use std::io;
-use std::net::{Ipv4Addr, SocketAddrV4};
-
-pub fn find_available_port(start_port: u16) -> io::Result<u16> {
-    let mut port = start_port;
-    loop {
-        // Create an IPv4 TCP socket.
-        let socket = Socket::new(Domain::IPV4, Type::STREAM, Some(Protocol::TCP))?;
-        // Disable address reuse so lingering CLOSE_WAIT connections block new binds.
-        socket.set_reuse_address(false)?;
-
-        let addr = SocketAddrV4::new(Ipv4Addr::LOCALHOST, port);
-        // Convert to a socket2 SockAddr.
-        let sock_addr = socket2::SockAddr::from(addr);
-        if socket.bind(&sock_addr).is_ok() {
-            return Ok(port);
-        }
-        port = port.saturating_add(1);
-    }
-}
+pub use dap_client::lock_or_recover;
 
 pub fn parse_lldb_result(result: &str) -> String {
     use regex::Regex;
@@ -40,3 +20,11 @@ pub fn parse_lldb_result(result: &str) -> String {
     // If no patterns match, return the original result
     result.trim().to_string()
 }
+
+// Strips the value history label from gdb's `print` output, e.g. "$1 = 42" -> "42".
+pub fn parse_gdb_result(result: &str) -> String {
+    use regex::Regex;
+
+    let re = Regex::new(r"^\$\d+\s+=\s+").unwrap();
+    re.replace(result.trim(), "").to_string()
+}
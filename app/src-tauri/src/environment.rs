@@ -0,0 +1,72 @@
+use serde::Serialize;
+
+// One variable of the debuggee's environment.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvironmentVariable {
+    pub name: String,
+    pub value: String,
+}
+
+// Both adapters print the environment as hex-encoded JSON: values can contain quotes, newlines
+// and non-UTF-8 bytes that the adapters' repr/console output would otherwise mangle.
+
+// debugpy: `os.environ` of the paused process, as a JSON object.
+pub fn python_expression() -> &'static str {
+    "__import__('json').dumps(dict(__import__('os').environ)).encode().hex()"
+}
+
+// lldb-dap: walks the C `environ` array with the SB API, as a JSON list of "NAME=value".
+pub fn lldb_command() -> &'static str {
+    "`script import json, itertools; \
+     p = lldb.process; err = lldb.SBError(); size = p.GetAddressByteSize(); \
+     base = lldb.target.EvaluateExpression('(char **)environ').GetValueAsUnsigned(); \
+     ptrs = itertools.takewhile(bool, (p.ReadPointerFromMemory(base + i * size, err) \
+     for i in itertools.count())) if base else []; \
+     print(json.dumps([p.ReadCStringFromMemory(a, 1 << 16, err) for a in ptrs]).encode().hex())"
+}
+
+// Decodes either adapter's output into variables sorted by name.
+pub fn parse(result: &str) -> Result<Vec<EnvironmentVariable>, String> {
+    let hex = result.trim().trim_matches('\'');
+    let json = decode_hex(hex).ok_or_else(|| format!("Unexpected output: {}", result.trim()))?;
+    let value: serde_json::Value =
+        serde_json::from_slice(&json).map_err(|e| format!("Failed to parse environment: {}", e))?;
+
+    let mut variables: Vec<EnvironmentVariable> = match value {
+        serde_json::Value::Object(map) => map
+            .into_iter()
+            .map(|(name, value)| EnvironmentVariable {
+                name,
+                value: value.as_str().unwrap_or_default().to_string(),
+            })
+            .collect(),
+        serde_json::Value::Array(entries) => entries
+            .iter()
+            .filter_map(|entry| entry.as_str())
+            .map(|entry| match entry.split_once('=') {
+                Some((name, value)) => EnvironmentVariable {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                },
+                None => EnvironmentVariable {
+                    name: entry.to_string(),
+                    value: String::new(),
+                },
+            })
+            .collect(),
+        _ => return Err("Unexpected environment format".into()),
+    };
+    variables.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(variables)
+}
+
+pub fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+// One kind of exception the adapter can break on, from the exceptionBreakpointFilters
+// capability: e.g. debugpy's "raised" and "uncaught", lldb-dap's "cpp_throw".
+#[derive(Debug, Clone, Deserialize)]
+struct AdapterFilter {
+    filter: String,
+    label: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    default: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExceptionFilter {
+    pub filter: String,
+    pub label: String,
+    pub description: Option<String>,
+    pub enabled: bool,
+}
+
+// The exception filters the current adapter offers and the ones the user turned on. Choices are
+// kept across sessions; until the user makes one, the adapter's defaults apply.
+#[derive(Debug, Clone, Default)]
+pub struct ExceptionFilters {
+    available: Vec<AdapterFilter>,
+    chosen: Option<BTreeSet<String>>,
+}
+
+impl ExceptionFilters {
+    // Takes the filters from the body of an initialize response.
+    pub fn set_available(&mut self, capabilities: Option<&Value>) {
+        self.available = capabilities
+            .and_then(|c| c.get("exceptionBreakpointFilters"))
+            .and_then(|f| serde_json::from_value(f.clone()).ok())
+            .unwrap_or_default();
+    }
+
+    pub fn clear_available(&mut self) {
+        self.available.clear();
+    }
+
+    // Turns on exactly `filters` among the current adapter's. Choices for filters of other
+    // adapters (another engine's) are left alone.
+    pub fn choose(&mut self, filters: &[String]) {
+        let mut chosen = self.chosen.take().unwrap_or_else(|| self.defaults());
+        chosen.retain(|f| !self.available.iter().any(|a| &a.filter == f));
+        chosen.extend(filters.iter().cloned());
+        self.chosen = Some(chosen);
+    }
+
+    fn defaults(&self) -> BTreeSet<String> {
+        self.available
+            .iter()
+            .filter(|f| f.default)
+            .map(|f| f.filter.clone())
+            .collect()
+    }
+
+    fn is_enabled(&self, filter: &AdapterFilter) -> bool {
+        match &self.chosen {
+            Some(chosen) => chosen.contains(&filter.filter),
+            None => filter.default,
+        }
+    }
+
+    pub fn list(&self) -> Vec<ExceptionFilter> {
+        self.available
+            .iter()
+            .map(|f| ExceptionFilter {
+                filter: f.filter.clone(),
+                label: f.label.clone(),
+                description: f.description.clone(),
+                enabled: self.is_enabled(f),
+            })
+            .collect()
+    }
+
+    // What setExceptionBreakpoints should carry, or None if the adapter has no exception
+    // filters and the request shouldn't be sent.
+    pub fn to_send(&self) -> Option<Vec<String>> {
+        if self.available.is_empty() {
+            return None;
+        }
+        Some(
+            self.available
+                .iter()
+                .filter(|f| self.is_enabled(f))
+                .map(|f| f.filter.clone())
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,54 @@
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlineValue {
+    pub line: i64,
+    // 1-based column of the identifier the value belongs to
+    pub column: usize,
+    pub name: String,
+    pub value: String,
+}
+
+// Finds the identifiers in lines `first_line..=last_line` (1-based) of `source` that name one of
+// `variables`, reporting each name once per line at its first occurrence.
+pub fn match_identifiers(
+    source: &str,
+    first_line: i64,
+    last_line: i64,
+    variables: &HashMap<String, String>,
+) -> Vec<InlineValue> {
+    let identifier = Regex::new(r"[A-Za-z_][A-Za-z0-9_]*").unwrap();
+    let mut values = Vec::new();
+
+    for (index, text) in source.lines().enumerate() {
+        let line = index as i64 + 1;
+        if line < first_line {
+            continue;
+        }
+        if line > last_line {
+            break;
+        }
+
+        let mut seen = Vec::new();
+        for m in identifier.find_iter(text) {
+            let name = m.as_str();
+            if seen.contains(&name) {
+                continue;
+            }
+            if let Some(value) = variables.get(name) {
+                seen.push(name);
+                values.push(InlineValue {
+                    line,
+                    column: m.start() + 1,
+                    name: name.to_string(),
+                    value: value.clone(),
+                });
+            }
+        }
+    }
+
+    values
+}
@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::adapters::discovery::find_in_path;
+use crate::settings::Settings;
+
+// Where a project pins its Python interpreter, relative to the workspace root.
+const PROJECT_FILE: &str = ".wayfind/python.json";
+
+// Where set_python_interpreter stores the interpreter.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InterpreterScope {
+    // The open project only, e.g. its virtualenv
+    Project,
+    // Every project without one of its own
+    Global,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ProjectPython {
+    interpreter: Option<String>,
+}
+
+fn project_path(project: &Path) -> PathBuf {
+    project.join(PROJECT_FILE)
+}
+
+// The project's own interpreter, if it set one. An unreadable file counts as none.
+pub fn project_interpreter(project: &Path) -> Option<String> {
+    let content = fs::read_to_string(project_path(project)).ok()?;
+    let file: ProjectPython = serde_json::from_str(&content).ok()?;
+    file.interpreter.filter(|i| !i.trim().is_empty())
+}
+
+// Pins `interpreter` for the project, or unpins it with None.
+pub fn set_project_interpreter(project: &Path, interpreter: Option<String>) -> Result<(), String> {
+    let path = project_path(project);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let content = serde_json::to_string_pretty(&ProjectPython { interpreter })
+        .map_err(|e| format!("Failed to serialize {}: {}", path.display(), e))?;
+    fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+// The interpreter Python sessions run debugpy with: the project's, then the one in settings,
+// then python3 on PATH.
+pub fn resolve(project: Option<&Path>, settings: &Settings) -> Result<String, String> {
+    let configured = project
+        .and_then(project_interpreter)
+        .or_else(|| {
+            settings
+                .python_interpreter
+                .clone()
+                .filter(|i| !i.trim().is_empty())
+        })
+        .map(|i| shellexpand::tilde(&i).into_owned());
+    if let Some(interpreter) = configured {
+        return Ok(interpreter);
+    }
+    find_in_path("python3")
+        .map(|p| p.to_string_lossy().to_string())
+        .ok_or_else(|| {
+            "No Python interpreter found: python3 is not on PATH. Set one with set_python_interpreter."
+                .to_string()
+        })
+}
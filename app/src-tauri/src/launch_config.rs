@@ -0,0 +1,311 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::cargo_workspace::CargoTargetKind;
+use crate::debugger::remote::{AdapterEndpoint, TlsOptions};
+use crate::tasks::PreLaunchTask;
+
+// Web framework presets for Python sessions. Each one runs the framework's dev server in a
+// single process without its reloader, which would otherwise restart the app in a child process
+// debugpy isn't attached to.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PythonTemplate {
+    // `manage.py runserver --noreload`; the script path is the project's manage.py
+    Django,
+    // `python -m flask run --no-debugger --no-reload`; the script path is the Flask app module
+    Flask,
+}
+
+// Which process lldb keeps debugging when the Rust debuggee forks. LLDB implements this for
+// Linux and FreeBSD targets; debugserver on macOS always stays with the parent.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FollowForkMode {
+    Parent,
+    // Detach from the parent and debug the child, so breakpoints hit in spawned subprocesses
+    Child,
+}
+
+impl FollowForkMode {
+    fn as_str(self) -> &'static str {
+        match self {
+            FollowForkMode::Parent => "parent",
+            FollowForkMode::Child => "child",
+        }
+    }
+}
+
+// How lldb treats a signal, as with `process handle`. Unset fields keep lldb's default.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignalHandling {
+    // e.g. "SIGPIPE"
+    pub signal: String,
+    // Deliver the signal to the program
+    pub pass: Option<bool>,
+    // Stop the program when it receives the signal
+    pub stop: Option<bool>,
+    // Report the signal in the console
+    pub notify: Option<bool>,
+}
+
+impl SignalHandling {
+    // The `process handle` command for this disposition. The signal name is checked so it can't
+    // smuggle in another command.
+    pub fn lldb_command(&self) -> Result<String, String> {
+        if self.signal.is_empty() || !self.signal.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(format!("Invalid signal name: {}", self.signal));
+        }
+        let mut command = format!("process handle {}", self.signal);
+        for (flag, value) in [("-p", self.pass), ("-s", self.stop), ("-n", self.notify)] {
+            if let Some(value) = value {
+                command.push_str(&format!(" {} {}", flag, value));
+            }
+        }
+        Ok(command)
+    }
+}
+
+// debugpy's autoReload: reloads changed modules in the running process so long-running
+// servers pick up edits without restarting the session. Empty lists use debugpy's defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AutoReload {
+    pub enable: bool,
+    // Directories to watch (defaults to the program's directory)
+    pub watch_directories: Vec<String>,
+    // Glob patterns of files that trigger a reload
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    // Seconds between polls when native file watching isn't available
+    pub polling_interval: Option<f64>,
+}
+
+impl Default for AutoReload {
+    fn default() -> Self {
+        AutoReload {
+            enable: true,
+            watch_directories: Vec::new(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            polling_interval: None,
+        }
+    }
+}
+
+// Template description returned to the launch dialog.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateInfo {
+    pub id: PythonTemplate,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub script_hint: &'static str,
+}
+
+pub fn python_templates() -> Vec<TemplateInfo> {
+    vec![
+        TemplateInfo {
+            id: PythonTemplate::Django,
+            name: "Django",
+            description: "Runs `manage.py runserver --noreload` under debugpy",
+            script_hint: "Path to the project's manage.py",
+        },
+        TemplateInfo {
+            id: PythonTemplate::Flask,
+            name: "Flask",
+            description: "Runs `flask run --no-debugger --no-reload` under debugpy",
+            script_hint: "Path to the Flask application file",
+        },
+    ]
+}
+
+// A program the user started themselves with `debugpy --listen <host>:<port>`, as is common for
+// servers, notebooks and containers. The endpoint is retried until it appears, so the session can
+// be started before the program is. A host other than loopback is only reached over TLS; see
+// require_loopback.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachEndpoint {
+    #[serde(default = "default_attach_host")]
+    pub host: String,
+    pub port: u16,
+    // Give up after this many seconds; without it the launch waits until it's cancelled
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    // Connect through a TLS terminator in front of a remote debugpy
+    #[serde(default)]
+    pub tls: Option<TlsOptions>,
+}
+
+impl AttachEndpoint {
+    pub fn adapter_endpoint(&self) -> AdapterEndpoint {
+        AdapterEndpoint {
+            host: self.host.clone(),
+            port: self.port,
+            tls: self.tls.clone(),
+        }
+    }
+}
+
+fn default_attach_host() -> String {
+    "127.0.0.1".to_string()
+}
+
+// What debugpy runs after its own flags, plus the process environment to run it in.
+#[derive(Debug, Clone)]
+pub struct PythonTarget {
+    pub args: Vec<String>,
+    pub env: HashMap<String, String>,
+    pub cwd: Option<PathBuf>,
+}
+
+// Optional per-launch settings sent by the frontend alongside the program path.
+// Fields that don't apply to the selected engine are ignored.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LaunchConfig {
+    // LLDB commands run before the target is created (e.g. `settings set target.source-map ...`)
+    pub init_commands: Vec<String>,
+    // LLDB commands run after the target is created, before it starts running
+    pub pre_run_commands: Vec<String>,
+    // LLDB commands run right after the target is launched
+    pub post_run_commands: Vec<String>,
+    // Arguments passed to the program (or appended to the template's command)
+    pub args: Vec<String>,
+    // Extra environment variables for the debuggee
+    pub env: HashMap<String, String>,
+    // Python: run this module (`-m <module>`) instead of the script path
+    pub module: Option<String>,
+    // Python: web framework preset
+    pub python_template: Option<PythonTemplate>,
+    // Pause at the top of the program: a breakpoint on the script's first line for Python, on
+    // the crate's `main` for Rust
+    pub break_at_entry: bool,
+    // Rust: build and debug this workspace package's binary instead of a prebuilt artifact.
+    // The script path is then the workspace root.
+    pub cargo_package: Option<String>,
+    // Rust: binary within `cargo_package` (defaults to its default-run or only binary)
+    pub cargo_bin: Option<String>,
+    // Rust: build `cargo_bin` as an example or integration test instead of a binary
+    pub cargo_target_kind: CargoTargetKind,
+    // GDB: attach to this running process instead of launching the program
+    pub attach_pid: Option<u32>,
+    // Rust: process to follow across fork/exec (lldb's default is the parent)
+    pub follow_fork: Option<FollowForkMode>,
+    // Python: reload edited modules in place
+    pub auto_reload: Option<AutoReload>,
+    // Rust: signal dispositions, e.g. pass SIGPIPE through without stopping
+    pub signals: Vec<SignalHandling>,
+    // Commands run in order before launching (e.g. `cargo build`); any failure aborts the launch
+    pub pre_launch_tasks: Vec<PreLaunchTask>,
+    // Python: attach to a program already started under debugpy instead of launching the script
+    pub wait_for_attach: Option<AttachEndpoint>,
+    // Python: give the program plain pipes instead of a terminal, e.g. to keep stdout and
+    // stderr apart
+    pub pipes: bool,
+}
+
+impl LaunchConfig {
+    // lldb-dap launch arguments for the configured command hooks. Empty lists are left out so
+    // the adapter's defaults apply.
+    pub fn lldb_command_arguments(&self) -> Map<String, Value> {
+        let mut init_commands = Vec::new();
+        if let Some(mode) = self.follow_fork {
+            init_commands.push(format!(
+                "settings set target.process.follow-fork-mode {}",
+                mode.as_str()
+            ));
+            // Don't stop when the followed child execs; breakpoints are re-resolved in the new
+            // image either way
+            init_commands.push("settings set target.process.stop-on-exec false".to_string());
+        }
+        // User commands go last so they can override the above
+        init_commands.extend(self.init_commands.iter().cloned());
+
+        // Signal dispositions need the process, so they run right after launch, before the
+        // program is resumed. Entries with invalid signal names are skipped.
+        let mut post_run_commands: Vec<String> = self
+            .signals
+            .iter()
+            .filter_map(|signal| signal.lldb_command().ok())
+            .collect();
+        post_run_commands.extend(self.post_run_commands.iter().cloned());
+
+        let mut args = Map::new();
+        for (key, commands) in [
+            ("initCommands", &init_commands),
+            ("preRunCommands", &self.pre_run_commands),
+            ("postRunCommands", &post_run_commands),
+        ] {
+            if !commands.is_empty() {
+                args.insert(key.to_string(), Value::from(commands.clone()));
+            }
+        }
+        args
+    }
+
+    // debugpy attach arguments beyond host and port.
+    pub fn python_attach_arguments(&self) -> Map<String, Value> {
+        let mut args = Map::new();
+        let Some(auto_reload) = &self.auto_reload else {
+            return args;
+        };
+
+        let mut reload = Map::new();
+        reload.insert("enable".to_string(), Value::from(auto_reload.enable));
+        for (key, values) in [
+            ("watchDirectories", &auto_reload.watch_directories),
+            ("include", &auto_reload.include),
+            ("exclude", &auto_reload.exclude),
+        ] {
+            if !values.is_empty() {
+                reload.insert(key.to_string(), Value::from(values.clone()));
+            }
+        }
+        if let Some(interval) = auto_reload.polling_interval {
+            reload.insert("pollingInterval".to_string(), Value::from(interval));
+        }
+        args.insert("autoReload".to_string(), Value::Object(reload));
+        args
+    }
+
+    // Resolves what debugpy should run for `script_path` under this config.
+    pub fn python_target(&self, script_path: &str) -> PythonTarget {
+        let script_dir = Path::new(script_path).parent().map(|p| p.to_path_buf());
+        let mut env = self.env.clone();
+
+        let mut args: Vec<String> = match (self.python_template, &self.module) {
+            (Some(PythonTemplate::Django), _) => vec![
+                script_path.to_string(),
+                "runserver".to_string(),
+                "--noreload".to_string(),
+            ],
+            (Some(PythonTemplate::Flask), _) => {
+                env.entry("FLASK_APP".to_string())
+                    .or_insert_with(|| script_path.to_string());
+                vec![
+                    "-m".to_string(),
+                    "flask".to_string(),
+                    "run".to_string(),
+                    "--no-debugger".to_string(),
+                    "--no-reload".to_string(),
+                ]
+            }
+            (None, Some(module)) => vec!["-m".to_string(), module.clone()],
+            (None, None) => vec![script_path.to_string()],
+        };
+        args.extend(self.args.iter().cloned());
+
+        // Frameworks resolve settings and app modules relative to the project directory
+        let cwd = match self.python_template {
+            Some(_) => script_dir,
+            None => None,
+        };
+
+        PythonTarget { args, env, cwd }
+    }
+}
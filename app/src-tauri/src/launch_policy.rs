@@ -0,0 +1,79 @@
+use serde::Serialize;
+use std::path::Path;
+
+// Directories holding system binaries; launching these under a debugger is rarely intended.
+const SYSTEM_DIRS: &[&str] = &[
+    "/bin",
+    "/sbin",
+    "/usr/bin",
+    "/usr/sbin",
+    "/usr/lib",
+    "/usr/libexec",
+    "/System",
+    "/Library/Apple",
+    "C:\\Windows",
+];
+
+// Why a launch needs explicit confirmation from the user.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchConcern {
+    pub code: String,
+    pub message: String,
+}
+
+impl LaunchConcern {
+    fn new(code: &str, message: String) -> Self {
+        LaunchConcern {
+            code: code.to_string(),
+            message,
+        }
+    }
+}
+
+// Flags launch targets outside the workspace, in system directories, or with setuid/setgid set.
+// `program` must already be canonical. An empty result means the launch can go ahead.
+pub fn assess(program: &Path, workspace_root: Option<&Path>) -> Vec<LaunchConcern> {
+    let mut concerns = Vec::new();
+
+    match workspace_root {
+        Some(root) if !program.starts_with(root) => concerns.push(LaunchConcern::new(
+            "outside_workspace",
+            format!(
+                "{} is outside the workspace {}",
+                program.display(),
+                root.display()
+            ),
+        )),
+        None => concerns.push(LaunchConcern::new(
+            "no_workspace",
+            format!("No workspace is open to launch {} from", program.display()),
+        )),
+        _ => {}
+    }
+
+    if SYSTEM_DIRS.iter().any(|dir| program.starts_with(dir)) {
+        concerns.push(LaunchConcern::new(
+            "system_binary",
+            format!("{} is a system binary", program.display()),
+        ));
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Ok(metadata) = std::fs::metadata(program) {
+            if metadata.is_file() && metadata.permissions().mode() & 0o6000 != 0 {
+                concerns.push(LaunchConcern::new(
+                    "setuid_binary",
+                    format!(
+                        "{} runs with elevated privileges (setuid/setgid)",
+                        program.display()
+                    ),
+                ));
+            }
+        }
+    }
+
+    concerns
+}
@@ -0,0 +1,101 @@
+use serde_json::json;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tauri::Emitter;
+use tokio::sync::Notify;
+
+use crate::debug_state::DebugSessionState;
+
+// Set by cancel_launch; checked at every stage boundary and raced against the stages that wait
+// on the adapter. Blocking stages (pre-launch tasks, cargo build, rr record) poll it and kill
+// their process when it's set.
+#[derive(Clone, Default)]
+pub struct LaunchCancel {
+    cancelled: Arc<AtomicBool>,
+    notify: Arc<Notify>,
+}
+
+impl LaunchCancel {
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    pub fn same_as(&self, other: &LaunchCancel) -> bool {
+        Arc::ptr_eq(&self.cancelled, &other.cancelled)
+    }
+
+    async fn cancelled(&self) {
+        loop {
+            // Registered before checking so a cancel between the two isn't missed
+            let notified = self.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+// Reports the stages of one launch on "launch-progress". The launch stays cancelable until
+// this is dropped.
+pub struct LaunchProgress {
+    app_handle: tauri::AppHandle,
+    debug_state: Arc<DebugSessionState>,
+    cancel: LaunchCancel,
+}
+
+pub const CANCELLED_MESSAGE: &str = "Launch cancelled";
+
+impl LaunchProgress {
+    pub fn new(app_handle: &tauri::AppHandle, debug_state: &Arc<DebugSessionState>) -> Self {
+        LaunchProgress {
+            app_handle: app_handle.clone(),
+            debug_state: Arc::clone(debug_state),
+            cancel: debug_state.begin_launch(),
+        }
+    }
+
+    // Announces `stage` (e.g. "build", "spawn", "connect"), failing instead if the launch was
+    // cancelled in the meantime.
+    pub fn stage(&self, stage: &str, detail: &str) -> Result<(), String> {
+        if self.cancel.is_cancelled() {
+            return Err(CANCELLED_MESSAGE.to_string());
+        }
+        tracing::debug!("Launch stage {}: {}", stage, detail);
+        let _ = self.app_handle.emit(
+            "launch-progress",
+            json!({ "session": self.debug_state.token, "stage": stage, "detail": detail }),
+        );
+        Ok(())
+    }
+
+    // For blocking stages that poll for a cancel themselves.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    // The same, for blocking stages moved to another thread.
+    pub fn cancel_flag(&self) -> LaunchCancel {
+        self.cancel.clone()
+    }
+
+    // Runs `future`, giving up as soon as the launch is cancelled.
+    pub async fn cancelable<T>(&self, future: impl Future<Output = T>) -> Result<T, String> {
+        tokio::select! {
+            result = future => Ok(result),
+            _ = self.cancel.cancelled() => Err(CANCELLED_MESSAGE.to_string()),
+        }
+    }
+}
+
+impl Drop for LaunchProgress {
+    fn drop(&mut self) {
+        self.debug_state.end_launch(&self.cancel);
+    }
+}
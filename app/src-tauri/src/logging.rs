@@ -0,0 +1,152 @@
+use parking_lot::{Mutex, RwLock};
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Arc;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{Builder, Rotation};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{fmt, reload, EnvFilter, Registry};
+
+// Overrides the filter, e.g. `WAYFIND_LOG=debug` or `WAYFIND_LOG=info,dap_client=trace` to
+// include full DAP payloads.
+const FILTER_ENV: &str = "WAYFIND_LOG";
+const DEFAULT_FILTER: &str = "info";
+// Daily files, a week's worth kept
+const MAX_LOG_FILES: usize = 7;
+// Lines kept in memory for get_recent_logs
+const RECENT_CAPACITY: usize = 2000;
+
+// The installed logging subsystem, managed as app state. Holding it keeps the file writer's
+// background thread alive; dropping it flushes the log.
+pub struct Logging {
+    filter: reload::Handle<EnvFilter, Registry>,
+    directives: RwLock<String>,
+    recent: RecentLogs,
+    _guard: Option<WorkerGuard>,
+}
+
+impl Logging {
+    // Swaps the active filter without restarting, e.g. "debug" or "info,dap_client=trace".
+    pub fn set_filter(&self, directives: &str) -> Result<(), String> {
+        let filter = EnvFilter::try_new(directives)
+            .map_err(|e| format!("Invalid log level {}: {}", directives, e))?;
+        self.filter
+            .reload(filter)
+            .map_err(|e| format!("Failed to change log level: {}", e))?;
+        *self.directives.write() = directives.to_string();
+        tracing::info!("Log level set to {}", directives);
+        Ok(())
+    }
+
+    pub fn filter(&self) -> String {
+        self.directives.read().clone()
+    }
+
+    // The last `count` formatted lines, oldest first.
+    pub fn recent(&self, count: usize) -> Vec<String> {
+        let lines = self.recent.lines.lock();
+        let skip = lines.len().saturating_sub(count);
+        lines.iter().skip(skip).cloned().collect()
+    }
+}
+
+// Installs the global subscriber: stderr, a rotating `wayfind.*.log` in `log_dir` and an
+// in-memory buffer of recent lines. Falls back to no log file if the directory can't be used.
+pub fn init(log_dir: Option<&Path>) -> Logging {
+    let directives = std::env::var(FILTER_ENV).unwrap_or_else(|_| DEFAULT_FILTER.to_string());
+    let (directives, filter) = match EnvFilter::try_new(&directives) {
+        Ok(filter) => (directives, filter),
+        Err(e) => {
+            eprintln!("Ignoring invalid {}: {}", FILTER_ENV, e);
+            (DEFAULT_FILTER.to_string(), EnvFilter::new(DEFAULT_FILTER))
+        }
+    };
+    let (filter, filter_handle) = reload::Layer::new(filter);
+
+    let appender = log_dir.and_then(|dir| {
+        Builder::new()
+            .rotation(Rotation::DAILY)
+            .filename_prefix("wayfind")
+            .filename_suffix("log")
+            .max_log_files(MAX_LOG_FILES)
+            .build(dir)
+            .map_err(|e| eprintln!("File logging disabled: {}", e))
+            .ok()
+    });
+    let (file_layer, guard) = match appender {
+        Some(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let layer = fmt::layer().with_ansi(false).with_writer(writer);
+            (Some(layer), Some(guard))
+        }
+        None => (None, None),
+    };
+
+    let recent = RecentLogs::default();
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_writer(std::io::stderr))
+        .with(file_layer)
+        .with(fmt::layer().with_ansi(false).with_writer(recent.clone()))
+        .init();
+
+    if let (Some(dir), Some(_)) = (log_dir, &guard) {
+        tracing::info!("Logging to {}", dir.display());
+    }
+    Logging {
+        filter: filter_handle,
+        directives: RwLock::new(directives),
+        recent,
+        _guard: guard,
+    }
+}
+
+// Ring buffer of formatted log lines, filled by its own fmt layer.
+#[derive(Clone, Default)]
+struct RecentLogs {
+    lines: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl<'a> MakeWriter<'a> for RecentLogs {
+    type Writer = RecentLogWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RecentLogWriter {
+            lines: Arc::clone(&self.lines),
+            buffer: Vec::new(),
+        }
+    }
+}
+
+// Collects one formatted event and appends it to the ring buffer when dropped.
+struct RecentLogWriter {
+    lines: Arc<Mutex<VecDeque<String>>>,
+    buffer: Vec<u8>,
+}
+
+impl Write for RecentLogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for RecentLogWriter {
+    fn drop(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let line = String::from_utf8_lossy(&self.buffer).trim_end().to_string();
+        let mut lines = self.lines.lock();
+        if lines.len() == RECENT_CAPACITY {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+}
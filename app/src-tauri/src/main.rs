@@ -1,21 +1,114 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod adapters;
+mod breakpoints;
+mod cargo_workspace;
+mod console_history;
+mod data_preview;
 mod debug_state;
+mod debug_targets;
 mod debugger;
+mod environment;
+mod exception_filters;
+mod inline_values;
+mod interpreter;
+mod launch_config;
+mod launch_policy;
+mod launch_progress;
+mod logging;
+mod memory;
+mod preflight;
+mod processes;
+mod pty;
+mod recursion;
+mod registers;
+mod renderers;
+mod report;
+mod sampler;
+mod session_history;
+mod session_summary;
+mod sessions;
+mod settings;
+mod skip_list;
+mod snippets;
+mod sources;
+mod stepping;
+mod stop_history;
+mod symbols;
+mod tasks;
+mod timeline;
+mod value_format;
+mod value_watch;
+mod variable_export;
+mod workspace;
 
-use debug_state::DebugSessionState;
-use debugger::client::{emit_status_update, BreakpointInput, DAPClient, DAPMessage, MessageType};
-use debugger::util::parse_lldb_result;
+use adapters::discovery::{find_lldb_dap, AdapterDetection};
+use adapters::doctor::AdapterCheck;
+use adapters::engine::{Connection, LaunchContext};
+use adapters::pool::AdapterPool;
+use breakpoints::BreakpointFile;
+use cargo_workspace::WorkspaceLayout;
+use console_history::ConsoleHistoryStore;
+use data_preview::DataPreview;
+use debug_state::{
+    DebugSessionState, DebuggerState, FrameSelection, LaunchRequest, SessionInfo, StopMode,
+    ThreadInfo,
+};
+use debugger::client::{
+    check_value_watches, emit_console_line, emit_debug_error, emit_debug_warning,
+    emit_session_summary, emit_status_update_with_extra, BreakpointInput, Capabilities, DAPClient,
+    DAPMessage, InFlightRequest, RetryPolicy,
+};
+use debugger::metrics::DapMetricsSnapshot;
+use debugger::port_manager::require_loopback;
+use debugger::protocol::{
+    self, EvaluateResponseBody, LoadedSourcesResponseBody, ScopesResponseBody, StackFrame,
+    StackTraceResponseBody, StartDebuggingRequestArguments, Target, TargetsResponseBody, Variable,
+    VariablesResponseBody,
+};
+use debugger::remote::{self, AdapterEndpoint};
+use environment::EnvironmentVariable;
+use exception_filters::ExceptionFilter;
+use inline_values::InlineValue;
+use interpreter::InterpreterScope;
+use launch_config::{AttachEndpoint, LaunchConfig, SignalHandling, TemplateInfo};
+use launch_progress::{LaunchProgress, CANCELLED_MESSAGE};
+use logging::Logging;
+use memory::MemoryBlock;
+use preflight::PythonEnvReport;
+use processes::ProcessEntry;
+use recursion::Recursion;
+use registers::RegisterGroup;
+use renderers::{RendererRegistry, RendererRule, Rendering};
+use report::{ReportFormat, ReportFrame, SessionReport};
+use sampler::SampledStacks;
 use serde_json::{json, Value};
+use session_history::{PastSession, PastSessionDetails, SessionHistoryStore};
+use session_summary::SessionSummary;
+use sessions::SessionManager;
+use settings::{Settings, SettingsStore};
 use shellexpand;
+use snippets::Snippet;
+use sources::AdapterSource;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
+use std::future::Future;
 use std::io::BufRead;
 use std::io::BufReader;
-use std::process::Command;
-use std::process::Stdio;
+use std::pin::Pin;
+use std::process::Child;
 use std::sync::Arc;
 use std::thread;
-use tauri::Emitter;
+use stepping::{Step, StepKind, StepRequest};
+use stop_history::StopSnapshot;
+use symbols::SymbolMatch;
+use tauri::{Emitter, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use timeline::SessionTimeline;
+use tracing::{debug, info, warn};
+use value_format::{FormatLimits, FullValue};
+use value_watch::ValueWatch;
+use workspace::Workspace;
 
 #[derive(serde::Serialize)]
 struct FileEntry {
@@ -25,6 +118,21 @@ struct FileEntry {
     is_dir: bool,
 }
 
+// Result of evaluate_selection, with the frame it was evaluated in.
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SelectionValue {
+    result: String,
+    #[serde(rename = "type")]
+    type_name: Option<String>,
+    // Non-zero when the value has children to fetch with the variables request
+    variables_reference: i64,
+    frame_id: i64,
+    frame_name: String,
+    // False when no frame was in the selection's file and the top frame was used instead
+    frame_matched: bool,
+}
+
 #[derive(serde::Serialize)]
 struct FrameInfo {
     id: i64,
@@ -32,6 +140,62 @@ struct FrameInfo {
     line: i64,
     column: Option<i64>,
     file: Option<String>,
+    #[serde(rename = "instructionPointerReference")]
+    instruction_pointer_reference: Option<String>,
+    // Set when the frame's source has no path and must be fetched with get_adapter_source
+    #[serde(rename = "sourceReference")]
+    source_reference: Option<i64>,
+    // Set on the first frame of collapsed recursion
+    recursion: Option<Recursion>,
+}
+
+impl FrameInfo {
+    fn from_stack_frame(f: StackFrame) -> Self {
+        let file = f.path().map(String::from);
+        let source_reference = f
+            .source
+            .as_ref()
+            .map(|src| src.source_reference)
+            .filter(|r| *r > 0);
+        FrameInfo {
+            id: f.id,
+            name: f.display_name(),
+            line: f.line,
+            column: f.column,
+            file,
+            instruction_pointer_reference: f.instruction_pointer_reference,
+            source_reference,
+            recursion: None,
+        }
+    }
+
+    // The frames of a stackTrace response body, with deep recursion collapsed if `collapse`.
+    fn from_stack_trace(body: StackTraceResponseBody, collapse: bool) -> Vec<FrameInfo> {
+        let frames: Vec<FrameInfo> = body
+            .stack_frames
+            .into_iter()
+            .map(FrameInfo::from_stack_frame)
+            .collect();
+        if !collapse {
+            return frames;
+        }
+        recursion::collapse(
+            frames,
+            |f| (f.name.clone(), f.file.clone(), f.line),
+            |f, recursion| f.recursion = Some(recursion),
+        )
+    }
+}
+
+// One thread's stack in get_all_backtraces.
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ThreadBacktrace {
+    thread_id: i64,
+    name: Option<String>,
+    frames: Vec<FrameInfo>,
+    // Why there are no frames, e.g. the thread is running
+    error: Option<String>,
 }
 
 #[tauri::command]
@@ -39,11 +203,14 @@ async fn read_file_content(
     file_path: String,
     start_line: Option<usize>,
     end_line: Option<usize>,
+    workspace: tauri::State<'_, Arc<Workspace>>,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
 ) -> Result<String, String> {
-    println!("Reading file content: {}", file_path);
+    debug!("Reading file content: {}", file_path);
+    let resolved = workspace.resolve(&file_path, &settings.get().allowed_paths)?;
 
     // Read the entire file
-    let content = fs::read_to_string(&file_path)
+    let content = fs::read_to_string(&resolved)
         .map_err(|e| format!("Error reading file {}: {}", file_path, e))?;
 
     // If no range specified, return the entire content
@@ -57,6 +224,10 @@ async fn read_file_content(
 
     // Convert from 1-based to 0-based indexing and handle defaults
     let start_idx = start_line.map(|l| l.saturating_sub(1)).unwrap_or(0);
+    if total_lines == 0 {
+        return Err(format!("File {} is empty", file_path));
+    }
+
     let end_idx = end_line
         .map(|l| l.saturating_sub(1).min(total_lines - 1))
         .unwrap_or(total_lines - 1);
@@ -77,12 +248,93 @@ async fn read_file_content(
     Ok(selected_lines)
 }
 
+// Registers the directory the user opened; filesystem commands are confined to it afterwards.
+// The webview doesn't get to pick the root: without `path` the user chooses it in the native
+// folder picker, and a `path` the frontend suggests is only opened once the user confirms it in
+// a native dialog. Returns None when the user backs out.
+#[tauri::command]
+async fn open_workspace(
+    path: Option<String>,
+    app_handle: tauri::AppHandle,
+    workspace: tauri::State<'_, Arc<Workspace>>,
+    renderers: tauri::State<'_, Arc<RendererRegistry>>,
+) -> Result<Option<String>, String> {
+    let path = match path {
+        Some(path) => {
+            let root = workspace::check_root(&path)?;
+            let (tx, rx) = tokio::sync::oneshot::channel();
+            app_handle
+                .dialog()
+                .message(format!(
+                    "Open {} as the workspace? Wayfind will read and write files in it.",
+                    root.display()
+                ))
+                .title("Open workspace")
+                .kind(MessageDialogKind::Warning)
+                .buttons(MessageDialogButtons::OkCancel)
+                .show(move |confirmed| {
+                    let _ = tx.send(confirmed);
+                });
+            if !rx.await.unwrap_or(false) {
+                return Ok(None);
+            }
+            root.to_string_lossy().into_owned()
+        }
+        None => {
+            let (tx, rx) = tokio::sync::oneshot::channel();
+            app_handle
+                .dialog()
+                .file()
+                .set_title("Open workspace")
+                .pick_folder(move |folder| {
+                    let _ = tx.send(folder);
+                });
+            let Some(folder) = rx.await.ok().flatten() else {
+                return Ok(None);
+            };
+            let folder = folder
+                .into_path()
+                .map_err(|e| format!("Cannot open workspace: {}", e))?;
+            folder.to_string_lossy().into_owned()
+        }
+    };
+
+    let root = workspace.open(&path)?;
+    info!("Opened workspace: {}", root.display());
+    // A broken renderer config shouldn't keep the project from opening
+    if let Err(e) = renderers.load(Some(&root)) {
+        warn!("{}", e);
+    }
+    Ok(Some(root.to_string_lossy().to_string()))
+}
+
+// Re-reads the open project's .wayfind/renderers.json after the user edited it.
+#[tauri::command]
+async fn reload_renderers(
+    workspace: tauri::State<'_, Arc<Workspace>>,
+    renderers: tauri::State<'_, Arc<RendererRegistry>>,
+) -> Result<Vec<RendererRule>, String> {
+    renderers.load(workspace.root().as_deref())
+}
+
+#[tauri::command]
+async fn get_renderers(
+    renderers: tauri::State<'_, Arc<RendererRegistry>>,
+) -> Result<Vec<RendererRule>, String> {
+    Ok(renderers.rules())
+}
+
 #[tauri::command]
-async fn read_directory(path: String) -> Result<Vec<FileEntry>, String> {
-    println!("Reading directory: {}", path); // Log the path
+async fn read_directory(
+    path: String,
+    workspace: tauri::State<'_, Arc<Workspace>>,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+) -> Result<Vec<FileEntry>, String> {
+    debug!("Reading directory: {}", path);
+    let resolved = workspace.resolve(&path, &settings.get().allowed_paths)?;
 
-    let entries = fs::read_dir(path.clone()).map_err(|e| {
-        println!("Error reading directory {}: {}", path, e);
+    let entries = fs::read_dir(&resolved).map_err(|e| {
+        warn!("Error reading directory {}: {}", path, e);
         e.to_string()
     })?;
 
@@ -99,13 +351,13 @@ async fn read_directory(path: String) -> Result<Vec<FileEntry>, String> {
             .unwrap_or("")
             .to_string();
 
-        println!("Found entry: {} (is_dir: {})", name, is_dir); // Log each entry
+        debug!("Found entry: {} (is_dir: {})", name, is_dir);
 
         let content = if !is_dir {
             match fs::read_to_string(&path) {
                 Ok(content) => Some(content),
                 Err(e) => {
-                    println!("Error reading file {}: {}", path.display(), e);
+                    warn!("Error reading file {}: {}", path.display(), e);
                     None
                 }
             }
@@ -133,292 +385,447 @@ async fn read_directory(path: String) -> Result<Vec<FileEntry>, String> {
         a.name.cmp(&b.name)
     });
 
-    println!("Returning {} entries from {}", files.len(), path); // Log the count
+    debug!("Returning {} entries from {}", files.len(), path);
     Ok(files)
 }
 
-#[tauri::command]
-async fn launch_debug_session(
-    app_handle: tauri::AppHandle,
-    script_path: String,
-    debug_engine: String, // New parameter to specify Python or Rust
-    debug_state: tauri::State<'_, Arc<DebugSessionState>>,
-) -> Result<String, String> {
-    // Create a basic validation check for the debug_engine parameter
-    match debug_engine.as_str() {
-        "python" => {
-            // Set the debugger type
-            {
-                let mut debugger_type = debug_state.debugger_type.write();
-                *debugger_type = Some("python".to_string());
-            }
-
-            // Existing Python/debugpy implementation
-            // 1. Find an available port to use for debugpy (starting at 5679)
-            let debugpy_port = crate::debugger::util::find_available_port(5678)
-                .map_err(|e| format!("Could not find available port: {}", e))?;
-
-            println!("Using port {} for debugpy", debugpy_port);
-
-            // 2. Spawn the Python process running debugpy.
-            let mut child = Command::new("/Users/mtn/.pyenv/versions/dbg/bin/python")
-                .args(&[
-                    "-Xfrozen_modules=off",
-                    "-u",
-                    "-m",
-                    "debugpy",
-                    "--listen",
-                    &format!("127.0.0.1:{}", debugpy_port),
-                    "--wait-for-client",
-                    &script_path,
-                ])
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .spawn()
-                .map_err(|e| format!("Failed to spawn debugpy process: {}", e))?;
-
-            println!("Spawned debugpy process with PID: {}", child.id());
-
-            if let Some(stdout) = child.stdout.take() {
-                let app_handle_clone = app_handle.clone();
-                thread::spawn(move || {
-                    let reader = BufReader::new(stdout);
-                    for line in reader.lines().flatten() {
-                        println!("Python stdout: {}", line);
-                        let _ = app_handle_clone.emit("program-output", line);
-                    }
-                });
+// Forwards a spawned process's stdout/stderr lines to the frontend console, keeping them in the
+// session's captured output.
+fn forward_child_output(
+    app_handle: &tauri::AppHandle,
+    debug_state: &Arc<DebugSessionState>,
+    child: &mut Child,
+    label: &str,
+) {
+    if let Some(stdout) = child.stdout.take() {
+        let app_handle_clone = app_handle.clone();
+        let debug_state = Arc::clone(debug_state);
+        let label = label.to_string();
+        thread::spawn(move || {
+            let reader = BufReader::new(stdout);
+            for line in reader.lines().flatten() {
+                debug!("{} stdout: {}", label, line);
+                emit_console_line(&app_handle_clone, &debug_state, "stdout", &line);
             }
+        });
+    }
 
-            if let Some(stderr) = child.stderr.take() {
-                let app_handle_clone = app_handle.clone();
-                thread::spawn(move || {
-                    let reader = BufReader::new(stderr);
-                    for line in reader.lines().flatten() {
-                        println!("Python stderr: {}", line);
-                        let _ = app_handle_clone.emit("program-error", line);
-                    }
-                });
+    if let Some(stderr) = child.stderr.take() {
+        let app_handle_clone = app_handle.clone();
+        let debug_state = Arc::clone(debug_state);
+        let label = label.to_string();
+        thread::spawn(move || {
+            let reader = BufReader::new(stderr);
+            for line in reader.lines().flatten() {
+                debug!("{} stderr: {}", label, line);
+                emit_console_line(&app_handle_clone, &debug_state, "stderr", &line);
             }
+        });
+    }
+}
 
-            // Give debugpy time to start up.
-            std::thread::sleep(std::time::Duration::from_secs(2));
-
-            // 3. Create a new DAPClient, connect it, and start its receiver.
-            let (mut dap_client, _rx) =
-                DAPClient::new(app_handle.clone(), Arc::clone(&*debug_state));
-            dap_client
-                .connect("127.0.0.1", debugpy_port as u16)
-                .map_err(|e| format!("Error connecting DAPClient: {}", e))?;
-
-            // Get a clone of the status_seq counter for the receiver thread
-            let status_seq = Arc::clone(&debug_state.status_seq);
+// Reports a failed launch step on the "debug-error" channel and returns the same message for the
+// command result, so callers that only look at the Result keep working.
+fn launch_error(
+    app_handle: &tauri::AppHandle,
+    code: &str,
+    message: &str,
+    detail: impl std::fmt::Display,
+) -> String {
+    let detail = detail.to_string();
+    let _ = emit_debug_error(app_handle, code, message, Some(&detail), false);
+    format!("{}: {}", message, detail)
+}
 
-            // Start the receiver loop so incoming DAP messages get handled.
-            {
-                // We call start_receiver() on the mutable client.
-                let mut client = dap_client;
-                // Pass the status_seq to start_receiver
-                client.start_receiver(Some(status_seq));
-
-                // Initialize and attach.
-                client
-                    .initialize()
-                    .await
-                    .map_err(|e| format!("Initialize failed: {}", e))?;
-                client
-                    .attach("127.0.0.1", debugpy_port as u16)
-                    .await
-                    .map_err(|e| format!("Attach failed: {}", e))?;
+// Connects to an adapter listening on `port`, retrying with backoff while it starts up. Each
+// retry is reported as a "connectRetry" launch stage.
+async fn connect_adapter(
+    app_handle: &tauri::AppHandle,
+    dap_client: &mut DAPClient,
+    port: u16,
+    retry: &RetryPolicy,
+    progress: &LaunchProgress,
+) -> Result<(), String> {
+    progress.stage("connect", &format!("127.0.0.1:{}", port))?;
+    progress
+        .cancelable(
+            dap_client.connect_with_retry("127.0.0.1", port, retry, |attempt, e| {
+                let _ = progress.stage(
+                    "connectRetry",
+                    &format!(
+                        "127.0.0.1:{} attempt {} of {}: {}",
+                        port, attempt, retry.attempts, e
+                    ),
+                );
+            }),
+        )
+        .await?
+        .map_err(|e| {
+            launch_error(
+                app_handle,
+                "connect_failed",
+                "Error connecting DAPClient",
+                e,
+            )
+        })?;
+    if let Some(debug_state) = &dap_client.debug_state {
+        *debug_state.adapter_endpoint.write() = Some(AdapterEndpoint::local(port));
+    }
+    Ok(())
+}
 
-                // Store the DAPClient in debug_state.
-                {
-                    let mut client_lock = debug_state.client.lock().await;
-                    client_lock.replace(client);
+// Connects to a program started with `debugpy --listen`, retrying until it's listening, the
+// endpoint's timeout passes or the launch is cancelled. The retry policy only sets the backoff;
+// the attempts are bounded by the timeout instead.
+async fn connect_when_listening(
+    app_handle: &tauri::AppHandle,
+    dap_client: &mut DAPClient,
+    endpoint: &AttachEndpoint,
+    retry: &RetryPolicy,
+    progress: &LaunchProgress,
+) -> Result<(), String> {
+    if endpoint.tls.is_none() {
+        require_loopback(&endpoint.host).map_err(|e| {
+            launch_error(
+                app_handle,
+                "connect_refused",
+                "Refusing an unencrypted connection to a remote debugpy",
+                e,
+            )
+        })?;
+    }
+    let adapter_endpoint = endpoint.adapter_endpoint();
+    let deadline = endpoint
+        .timeout_secs
+        .map(|secs| std::time::Instant::now() + std::time::Duration::from_secs(secs));
+    let mut attempt = 1;
+    loop {
+        match remote::connect(dap_client, &adapter_endpoint).await {
+            Ok(()) => {
+                if let Some(debug_state) = &dap_client.debug_state {
+                    *debug_state.adapter_endpoint.write() = Some(adapter_endpoint);
                 }
+                return Ok(());
             }
-
+            // Unreadable certificates or a failed handshake won't fix themselves
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::InvalidInput | std::io::ErrorKind::InvalidData
+                ) =>
             {
-                let mut proc_lock = debug_state.process.lock().await;
-                proc_lock.replace(child);
+                let message = format!("TLS connection to {} failed", adapter_endpoint.address());
+                return Err(launch_error(app_handle, "connect_failed", &message, e));
+            }
+            Err(e) if deadline.is_some_and(|deadline| std::time::Instant::now() >= deadline) => {
+                let message = format!(
+                    "Nothing started listening on {}:{}",
+                    endpoint.host, endpoint.port
+                );
+                return Err(launch_error(app_handle, "connect_failed", &message, e));
+            }
+            Err(e) => {
+                progress.stage(
+                    "connectRetry",
+                    &format!(
+                        "{}:{} attempt {}: {}",
+                        endpoint.host, endpoint.port, attempt, e
+                    ),
+                )?;
+                progress
+                    .cancelable(tokio::time::sleep(retry.delay(attempt)))
+                    .await?;
+                attempt += 1;
             }
-
-            // Emit an initializing status (to be updated by canonical events later)
-            emit_status_update(
-                &app_handle,
-                &debug_state.status_seq,
-                "initializing",
-                None,
-                None,
-                None,
-            )?;
-            println!("Debug session launched successfully");
-            Ok("Debug session launched successfully".into())
         }
-        "rust" => {
-            // Resolve the provided path (e.g. expand ~ and normalize relative segments)
-            let expanded_path = shellexpand::tilde(&script_path).into_owned();
-            let resolved_path = std::fs::canonicalize(&expanded_path)
-                .map_err(|e| format!("Failed to resolve path {}: {}", expanded_path, e))?;
-            println!("Resolved binary path: {}", resolved_path.to_string_lossy());
+    }
+}
 
-            #[cfg(unix)]
-            {
-                use std::os::unix::fs::PermissionsExt;
-                // On Unix-like systems, check if the file is executable
-                if let Ok(metadata) = std::fs::metadata(&resolved_path) {
-                    let permissions = metadata.permissions();
-                    if permissions.mode() & 0o111 == 0 {
-                        println!("Warning: The selected file does not have executable permissions");
-                        // Just a warning, continue anyway
-                    }
-                }
-            }
+// Waits for the response to an attach request sent before the configuration phase.
+async fn wait_for_attach(
+    client: &DAPClient,
+    attach_seq: i32,
+    progress: &LaunchProgress,
+) -> Result<(), String> {
+    let attach_resp = progress
+        .cancelable(client.wait_for_response(attach_seq, 10.0))
+        .await?
+        .ok_or_else(|| "Timeout waiting for attach response".to_string())?;
+    if attach_resp.success == Some(false) {
+        return Err(format!(
+            "Attach failed: {}",
+            attach_resp.message.unwrap_or_default()
+        ));
+    }
+    Ok(())
+}
 
-            // Set the debugger type
-            {
-                let mut debugger_type = debug_state.debugger_type.write();
-                *debugger_type = Some("rust".to_string());
-            }
+// Runs the launch config's pre-launch tasks in order, streaming their output on "task-output".
+// The first failing task aborts the launch.
+async fn run_pre_launch_tasks(
+    app_handle: &tauri::AppHandle,
+    debug_state: &Arc<DebugSessionState>,
+    progress: &LaunchProgress,
+    launch_config: &LaunchConfig,
+    workspace_root: Option<std::path::PathBuf>,
+) -> Result<(), String> {
+    for task in &launch_config.pre_launch_tasks {
+        let name = task.display_name();
+        progress.stage("preLaunchTask", &name)?;
+        info!("Running pre-launch task {}", name);
+        let _ = app_handle.emit(
+            "task-started",
+            json!({ "session": debug_state.token, "task": name }),
+        );
 
-            // Find an available port for lldb-dap
-            let lldb_port = crate::debugger::util::find_available_port(9123)
-                .map_err(|e| format!("Could not find available port: {}", e))?;
-
-            println!("Using port {} for lldb-dap", lldb_port);
-
-            // Search for lldb-dap in various locations
-            let lldb_dap_paths = [
-                "/Applications/Xcode.app/Contents/Developer/usr/bin/lldb-dap",
-                "/usr/bin/lldb-dap",
-                "/usr/local/bin/lldb-dap",
-            ];
-
-            let lldb_dap_path = lldb_dap_paths
-                .iter()
-                .find(|&&path| std::path::Path::new(path).exists())
-                .ok_or_else(|| "Could not find lldb-dap executable. Please ensure LLDB with DAP support is installed.".to_string())?;
-
-            println!("Using lldb-dap at: {}", lldb_dap_path);
-
-            // 2. Spawn the lldb-dap process
-            let mut child = Command::new(lldb_dap_path)
-                .arg("--port")
-                .arg(lldb_port.to_string())
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .spawn()
-                .map_err(|e| format!("Failed to spawn lldb-dap process: {}", e))?;
-
-            println!("Spawned lldb-dap process with PID: {}", child.id());
-
-            // Handle stdout and stderr just like with the Python debugger
-            if let Some(stdout) = child.stdout.take() {
-                let app_handle_clone = app_handle.clone();
-                thread::spawn(move || {
-                    let reader = BufReader::new(stdout);
-                    for line in reader.lines().flatten() {
-                        println!("lldb-dap stdout: {}", line);
-                        let _ = app_handle_clone.emit("program-output", line);
-                    }
-                });
-            }
+        let app_handle_clone = app_handle.clone();
+        let token = debug_state.token.clone();
+        let task_name = name.clone();
+        let task = task.clone();
+        let cwd = workspace_root.clone();
+        let cancel = progress.cancel_flag();
+        let result = tokio::task::spawn_blocking(move || {
+            let cancelled = move || cancel.is_cancelled();
+            tasks::run(&task, cwd.as_deref(), cancelled, move |stream, line| {
+                debug!("task {} {}: {}", task_name, stream.as_str(), line);
+                let _ = app_handle_clone.emit(
+                    "task-output",
+                    json!({
+                        "session": token,
+                        "task": task_name,
+                        "stream": stream.as_str(),
+                        "line": line,
+                    }),
+                );
+            })
+        })
+        .await
+        .unwrap_or_else(|e| Err(format!("Task {} panicked: {}", name, e)));
 
-            if let Some(stderr) = child.stderr.take() {
-                let app_handle_clone = app_handle.clone();
-                thread::spawn(move || {
-                    let reader = BufReader::new(stderr);
-                    for line in reader.lines().flatten() {
-                        println!("lldb-dap stderr: {}", line);
-                        let _ = app_handle_clone.emit("program-error", line);
-                    }
-                });
+        debug_state.timeline.write().record(
+            "preLaunchTask",
+            json!({ "task": name, "success": result.is_ok() }),
+        );
+        let _ = app_handle.emit(
+            "task-finished",
+            json!({
+                "session": debug_state.token,
+                "task": name,
+                "success": result.is_ok(),
+            }),
+        );
+        result.map_err(|e| match e.as_str() {
+            CANCELLED_MESSAGE => e,
+            _ => launch_error(app_handle, "prelaunch_failed", "Pre-launch task failed", e),
+        })?;
+    }
+    Ok(())
+}
+
+// Tauri commands take each invoke field and each piece of managed state as its own argument, and
+// the frontend and hot_restart call this one with named fields
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(engine = %debug_engine, session = tracing::field::Empty))]
+async fn launch_debug_session(
+    app_handle: tauri::AppHandle,
+    script_path: String,
+    debug_engine: String, // New parameter to specify Python or Rust
+    launch_config: Option<LaunchConfig>,
+    session: Option<String>,
+    confirmed: Option<bool>,
+    // Breakpoints by file to set before the program starts, on top of the stored ones
+    breakpoints: Option<BTreeMap<String, Vec<BreakpointInput>>>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+    workspace: tauri::State<'_, Arc<Workspace>>,
+    adapter_pool: tauri::State<'_, Arc<AdapterPool>>,
+) -> Result<String, String> {
+    // Launching something outside the workspace, a system binary or a setuid binary needs the
+    // frontend to ask the user and retry with `confirmed`. Unresolvable paths fall through to the
+    // engine-specific error below.
+    if !confirmed.unwrap_or(false) {
+        let expanded_path = shellexpand::tilde(&script_path).into_owned();
+        if let Ok(program) = std::fs::canonicalize(&expanded_path) {
+            let concerns = launch_policy::assess(&program, workspace.root().as_deref());
+            if !concerns.is_empty() {
+                let detail = serde_json::to_string(&concerns).unwrap_or_default();
+                let message = "Launch needs confirmation";
+                let _ = emit_debug_error(
+                    &app_handle,
+                    "launch_confirmation_required",
+                    message,
+                    Some(&detail),
+                    true,
+                );
+                return Err(format!("{}: {}", message, detail));
             }
+        }
+    }
 
-            // Give lldb-dap time to start up
-            std::thread::sleep(std::time::Duration::from_secs(1));
+    // Launching into a named session makes it the active one
+    let debug_state = match &session {
+        Some(token) => {
+            let debug_state = sessions.get_or_create(token);
+            sessions.set_active(token)?;
+            debug_state
+        }
+        None => sessions.active(),
+    };
+    tracing::Span::current().record("session", debug_state.token.as_str());
+    for (file, file_breakpoints) in breakpoints.unwrap_or_default() {
+        debug_state.breakpoints.write().set(&file, file_breakpoints);
+    }
+    let progress = LaunchProgress::new(&app_handle, &debug_state);
+    let launch_config = launch_config.unwrap_or_default();
+    debug_state.last_launch.write().replace(LaunchRequest {
+        script_path: script_path.clone(),
+        debug_engine: debug_engine.clone(),
+        launch_config: launch_config.clone(),
+    });
+    {
+        let mut timeline = debug_state.timeline.write();
+        *timeline = SessionTimeline::new();
+        timeline.record(
+            "launch",
+            json!({
+                "program": script_path,
+                "engine": debug_engine,
+                "config": launch_config,
+            }),
+        );
+    }
 
-            // 3. Create a new DAPClient, connect to it, and start its receiver
-            let (mut dap_client, _rx) =
-                DAPClient::new(app_handle.clone(), Arc::clone(&*debug_state));
-            dap_client
-                .connect("127.0.0.1", lldb_port)
-                .map_err(|e| format!("Error connecting DAPClient: {}", e))?;
+    run_pre_launch_tasks(
+        &app_handle,
+        &debug_state,
+        &progress,
+        &launch_config,
+        workspace.root(),
+    )
+    .await?;
 
-            // Get a clone of the status_seq counter for the receiver thread
-            let status_seq = Arc::clone(&debug_state.status_seq);
+    let Some(adapter) = adapters::engine::adapter(&debug_engine, &settings.get()) else {
+        let message = format!("Unsupported debug engine: {}", debug_engine);
+        let _ = emit_debug_error(&app_handle, "unsupported_engine", &message, None, false);
+        return Err(message);
+    };
+    *debug_state.debugger_type.write() = Some(adapter.engine().to_string());
+    debug_state.reset_session();
 
-            // 4. Initialize the client and launch the program
-            {
-                let mut client = dap_client;
-                client.start_receiver(Some(status_seq));
+    // The adapter builds the program and starts whatever it needs; connecting, initializing
+    // and the configuration phase are the same for every adapter
+    let (mut dap_client, _rx) = DAPClient::new(app_handle.clone(), Arc::clone(&debug_state));
+    let context = LaunchContext {
+        app_handle: &app_handle,
+        debug_state: &debug_state,
+        progress: &progress,
+        settings: settings.get(),
+        adapter_pool: &adapter_pool,
+        workspace_root: workspace.root(),
+        script_path: &script_path,
+        launch_config: &launch_config,
+    };
+    let prepared = adapter.prepare(&context, &mut dap_client)?;
+    if let Some(process) = prepared.process {
+        debug_state.process.lock().await.replace(process);
+    }
+    debug_state
+        .helper_processes
+        .lock()
+        .await
+        .extend(prepared.helper_processes);
 
-                // Initialize
-                client
-                    .initialize()
-                    .await
-                    .map_err(|e| format!("Initialize failed: {}", e))?;
-
-                // Launch instead of attach for Rust debugging
-                // Send a launch request using the resolved_path as the program path
-                let launch_seq = client
-                    .send_message(DAPMessage {
-                        seq: -1,
-                        message_type: MessageType::Request,
-                        command: Some("launch".to_string()),
-                        request_seq: None,
-                        success: None,
-                        arguments: Some(serde_json::json!({
-                            "program": resolved_path.to_string_lossy(),
-                            "stopOnEntry": false,
-                            "args": [],
-                            "cwd": resolved_path.parent()
-                                .map(|p| p.to_string_lossy().to_string())
-                                .unwrap_or_else(|| ".".to_string()),
-                        })),
-                        body: None,
-                        event: None,
-                    })
-                    .map_err(|e| format!("Failed to send launch request: {}", e))?;
-
-                // Wait for launch response
-                let launch_resp = client
-                    .wait_for_response(launch_seq, 10.0)
-                    .await
-                    .ok_or_else(|| "Timeout waiting for launch response".to_string())?;
+    match &prepared.connection {
+        Connection::Port(port) => {
+            connect_adapter(
+                &app_handle,
+                &mut dap_client,
+                *port,
+                &settings.get().connect_retry,
+                &progress,
+            )
+            .await?
+        }
+        Connection::WhenListening(endpoint) => {
+            connect_when_listening(
+                &app_handle,
+                &mut dap_client,
+                endpoint,
+                &settings.get().connect_retry,
+                &progress,
+            )
+            .await?
+        }
+        Connection::Connected => {}
+    }
+    dap_client
+        .start_receiver(Some(Arc::clone(&debug_state.status_seq)))
+        .map_err(|e| launch_error(&app_handle, "connect_failed", "Failed to start receiver", e))?;
 
-                if let Some(success) = launch_resp.success {
-                    if !success {
-                        return Err(format!("Launch failed: {:?}", launch_resp.body));
-                    }
-                }
+    progress.stage("initialize", adapter.adapter_name())?;
+    let capabilities = progress
+        .cancelable(dap_client.initialize())
+        .await?
+        .map_err(|e| format!("Initialize failed: {}", e))?;
+    debug_state.set_capabilities(capabilities.body.as_ref());
 
-                // Store the DAPClient in debug_state
-                {
-                    let mut client_lock = debug_state.client.lock().await;
-                    client_lock.replace(client);
-                }
-            }
+    let request = prepared.request;
+    progress.stage(request, &prepared.target)?;
+    let request_seq = dap_client
+        .send_message(DAPMessage::request(request, Some(prepared.arguments)))
+        .map_err(|e| {
+            launch_error(
+                &app_handle,
+                "launch_failed",
+                &format!("Failed to send {} request", request),
+                e,
+            )
+        })?;
+    // Stored before configuring so stops during the configuration phase see it
+    debug_state.client.lock().await.replace(dap_client);
 
-            {
-                let mut proc_lock = debug_state.process.lock().await;
-                proc_lock.replace(child);
-            }
+    // Emit an initializing status (to be updated by canonical events later)
+    emit_status_update_with_extra(
+        &app_handle,
+        &debug_state.status_seq,
+        "initializing",
+        None,
+        None,
+        None,
+        debug_state.status_extra(),
+    )?;
+    {
+        let client_lock = debug_state.client.lock().await;
+        let client = client_lock.as_ref().ok_or("No active debug session")?;
+        configure_session(
+            &app_handle,
+            &debug_state,
+            client,
+            &progress,
+            adapter.adapter_name(),
+        )
+        .await?;
 
-            // Emit an initializing status
-            emit_status_update(
-                &app_handle,
-                &debug_state.status_seq,
-                "initializing",
-                None,
-                None,
-                None,
-            )?;
-            println!("Rust debug session launched successfully");
-            Ok("Rust debug session launched successfully".into())
+        // debugpy and newer lldb-dap versions only answer once configuration is done
+        let response = progress
+            .cancelable(client.wait_for_response(request_seq, 10.0))
+            .await?
+            .ok_or_else(|| format!("Timeout waiting for {} response", request))?;
+        if response.success == Some(false) {
+            return Err(format!(
+                "{} failed: {}",
+                request,
+                response
+                    .message
+                    .or_else(|| response.body.map(|body| body.to_string()))
+                    .unwrap_or_default()
+            ));
         }
-        _ => Err(format!("Unsupported debug engine: {}", debug_engine)),
     }
+    progress.stage("ready", &prepared.label)?;
+    info!("{}", prepared.summary);
+    Ok(prepared.summary)
 }
 
 #[tauri::command]
@@ -428,14 +835,17 @@ async fn resolve_breakpoint_by_search(
     occurrence_index: Option<usize>,
     line_offset: Option<i32>,
     file_path: String,
+    workspace: tauri::State<'_, Arc<Workspace>>,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
 ) -> Result<Value, String> {
-    println!(
+    debug!(
         "Resolving line number via text search: '{}' in {}",
         search_text, file_path
     );
+    let resolved = workspace.resolve(&file_path, &settings.get().allowed_paths)?;
 
     // Read the file content
-    let content = fs::read_to_string(&file_path)
+    let content = fs::read_to_string(&resolved)
         .map_err(|e| format!("Failed to read file {}: {}", file_path, e))?;
 
     // Split into lines and find matches
@@ -457,7 +867,7 @@ async fn resolve_breakpoint_by_search(
                 // Look at a window of lines around the match
                 let start = line_num.saturating_sub(3);
                 let end = std::cmp::min(line_num + 3, lines.len());
-                let window = lines[start - 1..end].join("\n");
+                let window = lines[start.saturating_sub(1)..end].join("\n");
                 window.contains(&context)
             })
             .collect();
@@ -506,351 +916,3273 @@ async fn resolve_breakpoint_by_search(
     Ok(Value::Object(result_map))
 }
 
+// Warns when `file` was changed on disk after the session loaded it: the running code no longer
+// matches what the editor shows, so breakpoints can land on the wrong lines.
+fn warn_if_source_changed(
+    app_handle: &tauri::AppHandle,
+    debug_state: &DebugSessionState,
+    file: &str,
+    disk_hash: Option<&str>,
+) {
+    if debug_state
+        .breakpoints
+        .read()
+        .changed_since_load(file, disk_hash)
+    {
+        let _ = emit_debug_warning(
+            app_handle,
+            "source_changed",
+            "The file changed on disk since the session started; breakpoints may not match the running code",
+            Some(file),
+        );
+    }
+}
+
 #[tauri::command]
 async fn set_breakpoint(
     breakpoints: Vec<BreakpointInput>,
     file_path: String,
-    debug_state: tauri::State<'_, Arc<DebugSessionState>>,
+    // Whether the editor has unsaved changes in the file; kept as is when not given
+    source_modified: Option<bool>,
+    // SHA256 (hex) of the editor's buffer; it has unsaved changes if that differs from the disk
+    content_hash: Option<String>,
+    app_handle: tauri::AppHandle,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
 ) -> Result<Value, String> {
-    println!("Setting breakpoints");
+    let debug_state = sessions.active();
+    debug!("Setting breakpoints");
+    let disk_hash = breakpoints::disk_hash(&file_path);
+    let source_modified = source_modified.or_else(|| {
+        content_hash.map(|hash| {
+            !disk_hash
+                .as_deref()
+                .is_some_and(|disk| disk.eq_ignore_ascii_case(&hash))
+        })
+    });
+    // Remember them even without a session so they can be applied on the next launch
+    {
+        let mut store = debug_state.breakpoints.write();
+        store.set(&file_path, breakpoints);
+        if let Some(modified) = source_modified {
+            store.set_modified(&file_path, modified);
+        }
+    }
     let client_lock = debug_state.client.lock().await;
     let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
-    let response = dap_client
-        .set_breakpoints(file_path.clone(), breakpoints)
+    warn_if_source_changed(&app_handle, &debug_state, &file_path, disk_hash.as_deref());
+    let response = breakpoints::send(dap_client, &debug_state.breakpoints, &file_path)
         .await
         .map_err(|e| format!("Failed to set breakpoints: {}", e))?;
     if let Some(body) = response.body {
+        debug_state
+            .breakpoints
+            .write()
+            .record_adapter_ids(&file_path, &body);
         Ok(body)
     } else {
         Err("No breakpoints information in response.".into())
     }
 }
 
+// Called when the editor saves a file. Its unsaved-changes flag is cleared and, in a running
+// session, its breakpoints are re-sent against the saved content. Returns the adapter's
+// breakpoints, or null when there was nothing to send.
 #[tauri::command]
-async fn configuration_done(
-    debug_state: tauri::State<'_, Arc<DebugSessionState>>,
-) -> Result<String, String> {
+async fn source_saved(
+    file_path: String,
+    app_handle: tauri::AppHandle,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<Value, String> {
+    let debug_state = sessions.active();
+    let has_breakpoints = {
+        let mut store = debug_state.breakpoints.write();
+        store.set_modified(&file_path, false);
+        !store.active(&file_path).is_empty()
+    };
     let client_lock = debug_state.client.lock().await;
-    if client_lock.is_none() {
-        return Err("No active debug session".into());
+    let Some(dap_client) = client_lock.as_ref() else {
+        return Ok(Value::Null);
+    };
+    if !has_breakpoints {
+        return Ok(Value::Null);
     }
-    let dap_client = client_lock.as_ref().unwrap();
-    dap_client
-        .configuration_done()
+    let disk_hash = breakpoints::disk_hash(&file_path);
+    warn_if_source_changed(&app_handle, &debug_state, &file_path, disk_hash.as_deref());
+    let response = breakpoints::send(dap_client, &debug_state.breakpoints, &file_path)
         .await
-        .map_err(|e| format!("ConfigurationDone failed: {}", e))?;
-    // Use the canonical state update for configurationDone
-    debug_state.handle_configuration_done();
-    Ok("configurationDone sent; target program is now running.".into())
+        .map_err(|e| format!("Failed to re-send breakpoints in {}: {}", file_path, e))?;
+    debug_state
+        .timeline
+        .write()
+        .record("sourceSaved", json!({ "file": file_path }));
+    let body = response.body.unwrap_or(Value::Null);
+    debug_state
+        .breakpoints
+        .write()
+        .record_adapter_ids(&file_path, &body);
+    Ok(body)
 }
 
+// Content of a source the adapter provides by reference, cached per session.
 #[tauri::command]
-async fn continue_debug(
-    thread_id: i64,
-    debug_state: tauri::State<'_, Arc<DebugSessionState>>,
-) -> Result<String, String> {
-    let client_lock = debug_state.client.lock().await;
-    let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
-    match dap_client.continue_execution(thread_id).await {
-        Ok(_) => {
-            // Do not manually emit "running" status; canonical events will update the state.
-            Ok("Execution continued".into())
-        }
-        Err(e) => Err(format!("Failed to continue execution: {}", e)),
-    }
+async fn get_adapter_source(
+    source_reference: i64,
+    name: Option<String>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<AdapterSource, String> {
+    fetch_adapter_source(&sessions.active(), source_reference, name).await
 }
 
+// Just the text of a source the adapter serves by sourceReference.
 #[tauri::command]
-async fn step_in(
-    granularity: Option<String>,
-    debug_state: tauri::State<'_, Arc<DebugSessionState>>,
+async fn get_source(
+    source_reference: i64,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
 ) -> Result<String, String> {
-    let client_lock = debug_state.client.lock().await;
-    let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
-    let thread_id = match *debug_state.current_thread_id.read() {
-        Some(tid) => tid,
-        None => return Err("No current thread id available; debugger is not paused.".into()),
-    };
-    match dap_client.step_in(thread_id, granularity.as_deref()).await {
-        Ok(_) => Ok("Step in executed".into()),
-        Err(e) => Err(format!("Failed to step in: {}", e)),
-    }
+    let source = fetch_adapter_source(&sessions.active(), source_reference, None).await?;
+    Ok(source.content)
 }
 
+// Every source the adapter has loaded, for browsing code that isn't in the workspace (library
+// modules, generated code). Sources without a path carry a sourceReference for get_source.
 #[tauri::command]
-async fn step_over(
-    debug_state: tauri::State<'_, Arc<DebugSessionState>>,
-) -> Result<String, String> {
+async fn get_loaded_sources(
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<Vec<Value>, String> {
+    let debug_state = sessions.active();
+    if !debug_state
+        .capabilities
+        .read()
+        .supports_loaded_sources_request
+    {
+        return Err("This debug adapter doesn't list loaded sources".into());
+    }
     let client_lock = debug_state.client.lock().await;
     let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
-    let thread_id = match *debug_state.current_thread_id.read() {
-        Some(id) => id,
-        None => return Err("No current thread id available; debugger is not paused.".into()),
-    };
+    let response = dap_client
+        .loaded_sources()
+        .await
+        .map_err(|e| format!("loadedSources request failed: {}", e))?;
+    let body: LoadedSourcesResponseBody = protocol::body(&response)?;
+    Ok(body.sources)
+}
 
-    match dap_client.next(thread_id).await {
-        Ok(_) => {
-            // Status updates will be handled by the events system
-            Ok("Step over executed".into())
-        }
-        Err(e) => Err(format!("Failed to step over: {}", e)),
+async fn fetch_adapter_source(
+    debug_state: &DebugSessionState,
+    source_reference: i64,
+    name: Option<String>,
+) -> Result<AdapterSource, String> {
+    if let Some(source) = debug_state.adapter_sources.read().get(&source_reference) {
+        return Ok(source.clone());
     }
-}
 
-#[tauri::command]
-async fn step_out(
-    granularity: Option<String>,
-    debug_state: tauri::State<'_, Arc<DebugSessionState>>,
-) -> Result<String, String> {
     let client_lock = debug_state.client.lock().await;
     let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
-    let thread_id = match *debug_state.current_thread_id.read() {
-        Some(id) => id,
-        None => return Err("No current thread id available; debugger is not paused.".into()),
-    };
-
-    match dap_client.step_out(thread_id, granularity.as_deref()).await {
-        Ok(_) => {
-            // Do not manually emit "running" status; canonical events will update the state.
-            Ok("Step out executed".into())
-        }
-        Err(e) => Err(format!("Failed to step out: {}", e)),
+    let response = dap_client
+        .source(source_reference)
+        .await
+        .map_err(|e| format!("source request failed: {}", e))?;
+    if response.success == Some(false) {
+        return Err(format!(
+            "Failed to fetch source: {}",
+            response.message.unwrap_or_default()
+        ));
     }
+    let body = response.body.ok_or("No body in source response")?;
+    let source = AdapterSource::from_response(source_reference, name, &body)?;
+    debug_state
+        .adapter_sources
+        .write()
+        .insert(source_reference, source.clone());
+    Ok(source)
 }
 
+// Sets the breakpoints of a source known only by its sourceReference. Unlike file breakpoints
+// they aren't kept for the next launch, since the reference dies with the adapter session.
 #[tauri::command]
-async fn evaluate_expression(
-    expression: String,
-    debug_state: tauri::State<'_, Arc<DebugSessionState>>,
+async fn set_source_reference_breakpoints(
+    source_reference: i64,
+    name: Option<String>,
+    breakpoints: Vec<BreakpointInput>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
 ) -> Result<Value, String> {
-    // Get the DAP client
+    let debug_state = sessions.active();
     let client_lock = debug_state.client.lock().await;
     let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+    let response = dap_client
+        .set_breakpoints_by_reference(source_reference, name, breakpoints)
+        .await
+        .map_err(|e| format!("Failed to set breakpoints: {}", e))?;
+    response
+        .body
+        .ok_or_else(|| "No breakpoints information in response.".into())
+}
 
-    // Get the current debugger type
-    let debugger_type = {
-        let type_guard = debug_state.debugger_type.read();
-        type_guard.clone()
-    };
+// The configuration phase of a launch, run once the adapter reports "initialized": the stored
+// breakpoints are set, then configurationDone lets the program run. Breaking at entry is a
+// breakpoint on the script's first line for debugpy, sent along with the stored ones; for
+// lldb-dap it's a function breakpoint on main, set at launch.
+async fn configure_session(
+    app_handle: &tauri::AppHandle,
+    debug_state: &DebugSessionState,
+    dap_client: &DAPClient,
+    progress: &LaunchProgress,
+    adapter: &str,
+) -> Result<(), String> {
+    progress.stage("configure", adapter)?;
+    progress
+        .cancelable(dap_client.wait_for_event_async("initialized", 10.0))
+        .await?
+        .ok_or("Timeout waiting for the adapter to initialize")?;
+    send_stored_breakpoints(debug_state, dap_client).await?;
+    configuration_done(debug_state, dap_client).await?;
 
-    // Adjust the expression based on the debugger type
-    let eval_expression = match debugger_type.as_deref() {
-        Some("rust") => {
-            // LLDB requires expressions to be prefixed with "expr" or "expression"
-            if !expression.starts_with("expr ") && !expression.starts_with("expression ") {
-                format!("expr -- {}", expression)
-            } else {
-                expression
-            }
-        }
-        _ => expression.clone(), // No change for Python/other debuggers
-    };
+    // Unless the program already stopped (e.g. at entry), in which case "paused" follows
+    if *debug_state.state.read() == DebuggerState::Running {
+        emit_status_update_with_extra(
+            app_handle,
+            &debug_state.status_seq,
+            "running",
+            None,
+            None,
+            None,
+            debug_state.status_extra(),
+        )?;
+    }
+    Ok(())
+}
 
-    // Get frame ID for evaluation
-    let frame_id = match dap_client.stack_trace(1).await {
-        Ok(st_resp) => {
-            if let Some(body) = st_resp.body {
-                if let Some(stack_frames) = body.get("stackFrames").and_then(|sf| sf.as_array()) {
-                    if let Some(first_frame) = stack_frames.first() {
-                        // Extract the frame id
-                        first_frame
-                            .get("id")
-                            .and_then(|v| v.as_i64())
-                            .map(|id| id as i32)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        }
-        Err(e) => {
-            println!("Failed to get stack trace: {}", e);
-            None
-        }
+// Starts the session an adapter asked for with a startDebugging request (a debugpy subprocess, a
+// js-debug worker), registered alongside its parent so its events carry its own token.
+pub(crate) async fn start_child_session(
+    app_handle: tauri::AppHandle,
+    parent: Arc<DebugSessionState>,
+    arguments: StartDebuggingRequestArguments,
+) {
+    let sessions = app_handle.state::<Arc<SessionManager>>();
+    let child = sessions.create_child(&parent.token);
+    let name = arguments.child().name;
+    info!(
+        "Starting child session {} of {} ({:?})",
+        child.token, parent.token, name
+    );
+    parent.timeline.write().record(
+        "childSession",
+        json!({ "session": child.token, "request": arguments.request, "name": name }),
+    );
+
+    if let Err(e) = run_child_session(&app_handle, &parent, &child, arguments).await {
+        let _ = emit_debug_error(
+            &app_handle,
+            "child_session_failed",
+            &format!("Failed to start child session {}", child.token),
+            Some(&e),
+            true,
+        );
+        return;
+    }
+    let _ = app_handle.emit(
+        "child-session-started",
+        json!({
+            "session": child.token,
+            "parent": parent.token,
+            "name": name,
+            "info": child.session_info(),
+        }),
+    );
+}
+
+async fn run_child_session(
+    app_handle: &tauri::AppHandle,
+    parent: &DebugSessionState,
+    child: &Arc<DebugSessionState>,
+    arguments: StartDebuggingRequestArguments,
+) -> Result<(), String> {
+    *child.debugger_type.write() = parent.debugger_type.read().clone();
+    *child.adapter_version.write() = parent.adapter_version.read().clone();
+    *child.breakpoints.write() = parent.breakpoints.read().clone();
+    *child.exception_filters.write() = parent.exception_filters.read().clone();
+
+    // The configuration may name where to connect; otherwise the child shares the parent's adapter
+    let endpoint = match arguments.child().connect {
+        Some(connect) => AdapterEndpoint {
+            host: connect.host,
+            port: connect.port,
+            tls: None,
+        },
+        None => parent
+            .adapter_endpoint
+            .read()
+            .clone()
+            .ok_or("The parent session's adapter isn't reachable over TCP")?,
     };
+    if endpoint.tls.is_none() {
+        require_loopback(&endpoint.host)
+            .map_err(|e| format!("Refusing an unencrypted child session connection: {}", e))?;
+    }
 
-    // Now call evaluate with the potentially modified expression
-    let eval_resp = dap_client
-        .evaluate(&eval_expression, frame_id)
+    let progress = LaunchProgress::new(app_handle, child);
+    let (mut dap_client, _rx) = DAPClient::new(app_handle.clone(), Arc::clone(child));
+    let retry = app_handle.state::<Arc<SettingsStore>>().get().connect_retry;
+    remote::connect_with_retry(&mut dap_client, &endpoint, &retry, |attempt, e| {
+        warn!("Child session connect attempt {}: {}", attempt, e);
+    })
+    .await
+    .map_err(|e| format!("Failed to connect to {}: {}", endpoint.address(), e))?;
+    *child.adapter_endpoint.write() = Some(endpoint);
+    dap_client
+        .start_receiver(Some(Arc::clone(&child.status_seq)))
+        .map_err(|e| format!("Failed to start receiver: {}", e))?;
+    let capabilities = dap_client
+        .initialize()
         .await
-        .map_err(|e| format!("Failed to evaluate expression: {}", e))?;
+        .map_err(|e| format!("Initialize failed: {}", e))?;
+    child.set_capabilities(capabilities.body.as_ref());
 
-    if let Some(body) = eval_resp.body {
-        // For Rust/LLDB, we might want to parse the result to extract the actual value
-        if let Some("rust") = debugger_type.as_deref() {
-            if let Some(result_str) = body.get("result").and_then(|r| r.as_str()) {
-                // Process the result for LLDB
-                let processed_result = parse_lldb_result(result_str);
-
-                // Create a new body with the processed result
-                let mut processed_body = serde_json::Map::new();
-                processed_body.insert(
-                    "result".to_string(),
-                    serde_json::Value::String(processed_result),
-                );
-                processed_body.insert(
-                    "type".to_string(),
-                    body.get("type").cloned().unwrap_or(serde_json::Value::Null),
-                );
-                processed_body.insert(
-                    "variablesReference".to_string(),
-                    body.get("variablesReference")
-                        .cloned()
-                        .unwrap_or(serde_json::Value::Number(0.into())),
-                );
+    // Adapters answer launch and attach only once the configuration is done
+    let request = arguments.request;
+    let configuration = Value::Object(arguments.configuration);
+    let request_seq = dap_client
+        .send_message(DAPMessage::request(&request, Some(configuration)))
+        .map_err(|e| format!("Failed to send {}: {}", request, e))?;
+    child.client.lock().await.replace(dap_client);
 
-                return Ok(serde_json::Value::Object(processed_body));
+    let client_lock = child.client.lock().await;
+    let client = client_lock.as_ref().ok_or("No active debug session")?;
+    let adapter = child.debugger_type.read().clone().unwrap_or_default();
+    configure_session(app_handle, child, client, &progress, &adapter).await?;
+    let response = client
+        .wait_for_response(request_seq, 10.0)
+        .await
+        .ok_or_else(|| format!("Timeout waiting for {} response", request))?;
+    if response.success == Some(false) {
+        return Err(format!(
+            "{} failed: {}",
+            request,
+            response.message.unwrap_or_default()
+        ));
+    }
+    Ok(())
+}
+
+// Recovers a session whose adapter connection dropped before it ended: reconnects to the
+// adapter's endpoint and re-attaches, restoring breakpoints, as often as the connect retry
+// policy allows. If that fails the session is over.
+pub(crate) async fn reconnect_session(
+    app_handle: tauri::AppHandle,
+    debug_state: Arc<DebugSessionState>,
+) {
+    // lldb-dap hangs up after "terminated"; a launch in progress handles its own failures
+    if *debug_state.state.read() == DebuggerState::Terminated
+        || debug_state.launch_cancel.lock().is_some()
+    {
+        return;
+    }
+    warn!(
+        "Lost the connection to the adapter of session {}",
+        debug_state.token
+    );
+    debug_state
+        .timeline
+        .write()
+        .record("connectionLost", Value::Null);
+    let _ = emit_status_update_with_extra(
+        &app_handle,
+        &debug_state.status_seq,
+        "connection-lost",
+        None,
+        None,
+        None,
+        debug_state.status_extra(),
+    );
+    // Requests still waiting on the old connection time out on their own
+    debug_state.client.lock().await.take();
+    debug_state.reset_connection();
+
+    match reattach_session(&app_handle, &debug_state).await {
+        Ok(address) => {
+            info!("Re-attached session {} at {}", debug_state.token, address);
+            debug_state
+                .timeline
+                .write()
+                .record("reconnected", json!({ "address": address }));
+            let _ = app_handle.emit(
+                "session-reconnected",
+                json!({ "session": debug_state.token, "address": address }),
+            );
+        }
+        Err(e) => {
+            debug_state.client.lock().await.take();
+            *debug_state.state.write() = DebuggerState::Terminated;
+            debug_state
+                .timeline
+                .write()
+                .record("reconnectFailed", json!(e));
+            let _ = emit_debug_error(
+                &app_handle,
+                "connection_lost",
+                "Lost connection to the debug adapter",
+                Some(&e),
+                false,
+            );
+            let _ = emit_status_update_with_extra(
+                &app_handle,
+                &debug_state.status_seq,
+                "terminated",
+                None,
+                None,
+                None,
+                debug_state.status_extra(),
+            );
+            emit_session_summary(&app_handle, &debug_state, "connectionLost");
+            if let Some(mut child) = debug_state.process.lock().await.take() {
+                processes::kill_tree(&mut child);
+            }
+            for mut helper in debug_state.helper_processes.lock().await.drain(..) {
+                processes::kill_tree(&mut helper);
+            }
+            debug_state.sampler.lock().take();
+            debug_state.rr_trace.lock().take();
+            debug_state.pty.lock().take();
+        }
+    }
+}
+
+// Attaches to the session's debugpy again, the way the launch did. lldb-dap ends its debuggee
+// with the connection, so launched Rust sessions have nothing to go back to.
+async fn reattach_session(
+    app_handle: &tauri::AppHandle,
+    debug_state: &Arc<DebugSessionState>,
+) -> Result<String, String> {
+    let launch = debug_state
+        .last_launch
+        .read()
+        .clone()
+        .filter(|launch| launch.debug_engine == "python" && debug_state.parent.read().is_none())
+        .ok_or("Only debugpy sessions can be re-attached")?;
+    let endpoint = debug_state
+        .adapter_endpoint
+        .read()
+        .clone()
+        .ok_or("The adapter isn't reachable over TCP")?;
+    let address = endpoint.address();
+
+    let progress = LaunchProgress::new(app_handle, debug_state);
+    progress.stage("reconnect", &address)?;
+    let (mut dap_client, _rx) = DAPClient::new(app_handle.clone(), Arc::clone(debug_state));
+    let retry = app_handle.state::<Arc<SettingsStore>>().get().connect_retry;
+    progress
+        .cancelable(remote::connect_with_retry(
+            &mut dap_client,
+            &endpoint,
+            &retry,
+            |attempt, e| {
+                let _ = progress.stage(
+                    "connectRetry",
+                    &format!(
+                        "{} attempt {} of {}: {}",
+                        address, attempt, retry.attempts, e
+                    ),
+                );
+            },
+        ))
+        .await?
+        .map_err(|e| format!("Failed to reconnect to {}: {}", address, e))?;
+    dap_client
+        .start_receiver(Some(Arc::clone(&debug_state.status_seq)))
+        .map_err(|e| format!("Failed to start receiver: {}", e))?;
+    progress.stage("initialize", "debugpy")?;
+    let capabilities = progress
+        .cancelable(dap_client.initialize())
+        .await?
+        .map_err(|e| format!("Initialize failed: {}", e))?;
+    debug_state.set_capabilities(capabilities.body.as_ref());
+    progress.stage("attach", &address)?;
+    let attach_seq = progress
+        .cancelable(dap_client.attach_with(
+            &endpoint.host,
+            endpoint.port,
+            launch.launch_config.python_attach_arguments(),
+        ))
+        .await?
+        .map_err(|e| format!("Attach failed: {}", e))?;
+    debug_state.client.lock().await.replace(dap_client);
+
+    let client_lock = debug_state.client.lock().await;
+    let client = client_lock.as_ref().ok_or("No active debug session")?;
+    configure_session(app_handle, debug_state, client, &progress, "debugpy").await?;
+    wait_for_attach(client, attach_seq, &progress).await?;
+    progress.stage("ready", "debugpy")?;
+    Ok(address)
+}
+
+async fn configuration_done(
+    debug_state: &DebugSessionState,
+    dap_client: &DAPClient,
+) -> Result<(), String> {
+    let exception_filters = debug_state.exception_filters.read().to_send();
+    if let Some(filters) = exception_filters {
+        dap_client
+            .set_exception_breakpoints(filters)
+            .await
+            .map_err(|e| format!("Failed to set exception breakpoints: {}", e))?;
+    }
+
+    // Function breakpoints (e.g. break on panic) belong to the configuration phase
+    let function_breakpoints = debug_state.function_breakpoints();
+    if !function_breakpoints.is_empty() {
+        let response = dap_client
+            .set_function_breakpoints(function_breakpoints)
+            .await
+            .map_err(|e| format!("Failed to set function breakpoints: {}", e))?;
+        debug_state.record_function_breakpoint_ids(response.body.as_ref());
+    }
+
+    dap_client
+        .configuration_done()
+        .await
+        .map_err(|e| format!("ConfigurationDone failed: {}", e))?;
+    // Use the canonical state update for configurationDone
+    debug_state.handle_configuration_done();
+    Ok(())
+}
+
+#[tauri::command]
+async fn set_break_on_panic(
+    enabled: bool,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<Value, String> {
+    let debug_state = sessions.active();
+    *debug_state.break_on_panic.write() = enabled;
+
+    // Apply right away if a native session is already running; otherwise it is picked up
+    // during the next configuration phase.
+    let client_lock = debug_state.client.lock().await;
+    match client_lock.as_ref() {
+        Some(dap_client) if debug_state.is_lldb_session() => {
+            let response = dap_client
+                .set_function_breakpoints(debug_state.function_breakpoints())
+                .await
+                .map_err(|e| format!("Failed to set function breakpoints: {}", e))?;
+            debug_state.record_function_breakpoint_ids(response.body.as_ref());
+            Ok(response.body.unwrap_or(Value::Null))
+        }
+        _ => Ok(Value::Null),
+    }
+}
+
+// What the session's adapter supports, so the frontend can hide what it doesn't (all false
+// before an adapter has initialized).
+#[tauri::command]
+async fn get_capabilities(
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<Capabilities, String> {
+    let debug_state = sessions.active();
+    let capabilities = debug_state.capabilities.read().clone();
+    Ok(capabilities)
+}
+
+// The exceptions the session's adapter can stop at, and which are turned on.
+#[tauri::command]
+async fn get_exception_filters(
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<Vec<ExceptionFilter>, String> {
+    let debug_state = sessions.active();
+    let filters = debug_state.exception_filters.read().list();
+    Ok(filters)
+}
+
+// Turns on exactly `filters` among the adapter's exception filters (e.g. ["raised", "uncaught"]
+// for debugpy). A running session gets them right away; launches pick them up during the
+// configuration phase.
+#[tauri::command]
+async fn set_exception_filters(
+    filters: Vec<String>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<Vec<ExceptionFilter>, String> {
+    let debug_state = sessions.active();
+    let to_send = {
+        let mut exception_filters = debug_state.exception_filters.write();
+        exception_filters.choose(&filters);
+        exception_filters.to_send()
+    };
+
+    let client_lock = debug_state.client.lock().await;
+    if let (Some(dap_client), Some(to_send)) = (client_lock.as_ref(), to_send) {
+        let response = dap_client
+            .set_exception_breakpoints(to_send)
+            .await
+            .map_err(|e| format!("Failed to set exception breakpoints: {}", e))?;
+        if response.success == Some(false) {
+            return Err(format!(
+                "Failed to set exception breakpoints: {}",
+                response.message.unwrap_or_default()
+            ));
+        }
+    }
+    let filters = debug_state.exception_filters.read().list();
+    Ok(filters)
+}
+
+#[tauri::command]
+async fn continue_debug(
+    thread_id: i64,
+    single_thread: Option<bool>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<String, String> {
+    let debug_state = sessions.active();
+    let client_lock = debug_state.client.lock().await;
+    let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+    debug_state.timeline.write().record(
+        "continue",
+        json!({ "threadId": thread_id, "singleThread": single_thread }),
+    );
+    // Steps still waiting to be sent are superseded
+    debug_state.stepping.lock().clear();
+    match dap_client
+        .continue_execution(thread_id, single_thread.unwrap_or(false))
+        .await
+    {
+        Ok(_) => {
+            // Do not manually emit "running" status; canonical events will update the state.
+            Ok("Execution continued".into())
+        }
+        Err(e) => Err(format!("Failed to continue execution: {}", e)),
+    }
+}
+
+// Interrupts the running program. The "stopped" event that follows updates the status like any
+// other stop.
+#[tauri::command]
+async fn pause_debug(
+    thread_id: i64,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<String, String> {
+    let debug_state = sessions.active();
+    let client_lock = debug_state.client.lock().await;
+    let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+    debug_state
+        .timeline
+        .write()
+        .record("pauseRequested", json!({ "threadId": thread_id }));
+    let response = dap_client
+        .pause(thread_id)
+        .await
+        .map_err(|e| format!("Failed to pause execution: {}", e))?;
+    if response.success == Some(false) {
+        return Err(format!(
+            "Failed to pause execution: {}",
+            response.message.unwrap_or_default()
+        ));
+    }
+    Ok("Pause requested".into())
+}
+
+// Set next statement: moves the paused thread to `line` of `file` without running the code in
+// between. Adapters generally only allow lines in the current function.
+#[tauri::command]
+async fn goto_line(
+    file: String,
+    line: i64,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<Target, String> {
+    let debug_state = sessions.active();
+    let thread_id = match *debug_state.current_thread_id.read() {
+        Some(id) => id,
+        None => return Err("No current thread id available; debugger is not paused.".into()),
+    };
+    if !debug_state
+        .capabilities
+        .read()
+        .supports_goto_targets_request
+    {
+        return Err("This debug adapter doesn't support jumping to a line".into());
+    }
+    let client_lock = debug_state.client.lock().await;
+    let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+
+    let response = dap_client
+        .goto_targets(&file, line)
+        .await
+        .map_err(|e| format!("gotoTargets request failed: {}", e))?;
+    let body: TargetsResponseBody =
+        protocol::body(&response).map_err(|e| format!("Can't jump to line {}: {}", line, e))?;
+    let target = body
+        .targets
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("Line {} can't be jumped to from the current frame", line))?;
+
+    debug_state.timeline.write().record(
+        "goto",
+        json!({ "threadId": thread_id, "file": file, "line": line }),
+    );
+    let response = dap_client
+        .goto(thread_id, target.id)
+        .await
+        .map_err(|e| format!("goto request failed: {}", e))?;
+    if response.success == Some(false) {
+        return Err(format!(
+            "Can't jump to line {}: {}",
+            line,
+            response.message.unwrap_or_default()
+        ));
+    }
+    Ok(target)
+}
+
+// Re-runs frame `frame_id` from its first line, dropping the frames above it. Variables changed
+// since stay changed, which is the point: fix a value, then run the function again.
+#[tauri::command]
+async fn restart_frame(
+    frame_id: i64,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<String, String> {
+    let debug_state = sessions.active();
+    if !debug_state.capabilities.read().supports_restart_frame {
+        return Err("This debug adapter doesn't support restarting a frame".into());
+    }
+    let client_lock = debug_state.client.lock().await;
+    let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+    debug_state
+        .timeline
+        .write()
+        .record("restartFrame", json!({ "frameId": frame_id }));
+    // Steps waiting to be sent were meant for the old position
+    debug_state.stepping.lock().clear();
+    let response = dap_client
+        .restart_frame(frame_id)
+        .await
+        .map_err(|e| format!("restartFrame request failed: {}", e))?;
+    if response.success == Some(false) {
+        return Err(format!(
+            "Failed to restart the frame: {}",
+            response.message.unwrap_or_default()
+        ));
+    }
+    Ok("Frame restarted".into())
+}
+
+// Sends a step for the current thread, or queues it behind the one in flight (see
+// SteppingController).
+async fn request_step(debug_state: &DebugSessionState, step: Step) -> Result<String, String> {
+    let client_lock = debug_state.client.lock().await;
+    let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+    let thread_id = match *debug_state.current_thread_id.read() {
+        Some(id) => id,
+        None => return Err("No current thread id available; debugger is not paused.".into()),
+    };
+
+    let label = step.kind.label();
+    if debug_state.stepping.lock().request(thread_id, step.clone()) == StepRequest::Queued {
+        return Ok(format!("{} queued", label));
+    }
+    debug_state.timeline.write().record(
+        step.kind.name(),
+        json!({
+            "threadId": thread_id,
+            "granularity": step.granularity,
+            "singleThread": step.single_thread,
+            "targetId": step.target_id,
+        }),
+    );
+    match step.send(dap_client, thread_id).await {
+        // Status updates will be handled by the events system
+        Ok(()) => Ok(format!("{} executed", label)),
+        Err(e) => {
+            debug_state.stepping.lock().cancel(thread_id);
+            Err(format!("Failed to {}: {}", label.to_lowercase(), e))
+        }
+    }
+}
+
+#[tauri::command]
+async fn step_in(
+    granularity: Option<String>,
+    single_thread: Option<bool>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<String, String> {
+    let step = Step {
+        kind: StepKind::In,
+        granularity,
+        single_thread: single_thread.unwrap_or(false),
+        target_id: None,
+    };
+    request_step(&sessions.active(), step).await
+}
+
+#[tauri::command]
+async fn step_over(
+    granularity: Option<String>,
+    single_thread: Option<bool>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<String, String> {
+    let step = Step {
+        kind: StepKind::Over,
+        granularity,
+        single_thread: single_thread.unwrap_or(false),
+        target_id: None,
+    };
+    request_step(&sessions.active(), step).await
+}
+
+#[tauri::command]
+async fn step_out(
+    granularity: Option<String>,
+    single_thread: Option<bool>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<String, String> {
+    let step = Step {
+        kind: StepKind::Out,
+        granularity,
+        single_thread: single_thread.unwrap_or(false),
+        target_id: None,
+    };
+    request_step(&sessions.active(), step).await
+}
+
+// The calls on the current line of `frame_id` that stepIn can pick between.
+async fn step_in_targets(
+    debug_state: &DebugSessionState,
+    dap_client: &DAPClient,
+    frame_id: i64,
+) -> Result<Vec<Target>, String> {
+    if !debug_state
+        .capabilities
+        .read()
+        .supports_step_in_targets_request
+    {
+        return Err("This debug adapter doesn't support choosing a call to step into".into());
+    }
+    let response = dap_client
+        .step_in_targets(frame_id)
+        .await
+        .map_err(|e| format!("stepInTargets request failed: {}", e))?;
+    let body: TargetsResponseBody = protocol::body(&response)?;
+    Ok(body.targets)
+}
+
+// Calls on the current line that can be stepped into, e.g. `g(x)` and `f(...)` for `f(g(x))`.
+// Defaults to the current thread's top frame.
+#[tauri::command]
+async fn get_step_in_targets(
+    frame_id: Option<i64>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<Vec<Target>, String> {
+    let debug_state = sessions.active();
+    let thread_id = *debug_state.current_thread_id.read();
+    let client_lock = debug_state.client.lock().await;
+    let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+    let frame_id = match (frame_id, thread_id) {
+        (Some(frame_id), _) => frame_id,
+        (None, Some(thread_id)) => top_frame_id(dap_client, thread_id)
+            .await?
+            .map(i64::from)
+            .ok_or("The paused thread has no stack frames")?,
+        (None, None) => {
+            return Err("No current thread id available; debugger is not paused.".into())
+        }
+    };
+    step_in_targets(&debug_state, dap_client, frame_id).await
+}
+
+// Steps into the call `target_id` (from get_step_in_targets for `frame_id`) rather than the
+// first call of the line.
+#[tauri::command]
+async fn step_in_target(
+    frame_id: i64,
+    target_id: i64,
+    single_thread: Option<bool>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<String, String> {
+    let debug_state = sessions.active();
+    {
+        let client_lock = debug_state.client.lock().await;
+        let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+        // Targets only hold for the line they were listed on
+        let targets = step_in_targets(&debug_state, dap_client, frame_id).await?;
+        if !targets.iter().any(|t| t.id == target_id) {
+            return Err("That call is no longer on the current line".into());
+        }
+    }
+    let step = Step {
+        kind: StepKind::In,
+        granularity: None,
+        single_thread: single_thread.unwrap_or(false),
+        target_id: Some(target_id),
+    };
+    request_step(&debug_state, step).await
+}
+
+// Suspends or resumes one thread of an lldb session. A frozen thread stays put when the rest of
+// the program continues or steps, until it is thawed.
+async fn set_thread_frozen(
+    app_handle: &tauri::AppHandle,
+    debug_state: &DebugSessionState,
+    thread_id: i64,
+    frozen: bool,
+) -> Result<Vec<ThreadInfo>, String> {
+    if !debug_state.is_lldb_session() {
+        return Err("Freezing threads is only supported in Rust sessions".into());
+    }
+    let (verb, method) = if frozen {
+        ("freeze", "Suspend")
+    } else {
+        ("thaw", "Resume")
+    };
+
+    {
+        let client_lock = debug_state.client.lock().await;
+        let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+        let command = format!(
+            "`script lldb.process.GetThreadByID({}).{}()",
+            thread_id, method
+        );
+        let resp = dap_client
+            .evaluate(&command, None)
+            .await
+            .map_err(|e| format!("Failed to {} thread {}: {}", verb, thread_id, e))?;
+        let result = EvaluateResponseBody::result_of(&resp).unwrap_or_default();
+        if resp.success == Some(false) || !result.contains("True") {
+            return Err(format!(
+                "lldb could not {} thread {}: {}",
+                verb,
+                thread_id,
+                result.trim()
+            ));
+        }
+    }
+
+    {
+        let mut frozen_threads = debug_state.frozen_threads.write();
+        if frozen {
+            frozen_threads.insert(thread_id);
+        } else {
+            frozen_threads.remove(&thread_id);
+        }
+    }
+    debug_state.timeline.write().record(
+        if frozen { "freezeThread" } else { "thawThread" },
+        json!({ "threadId": thread_id }),
+    );
+    let threads = debug_state.thread_list();
+    let _ = app_handle.emit("threads-changed", &threads);
+    Ok(threads)
+}
+
+#[tauri::command]
+async fn freeze_thread(
+    thread_id: i64,
+    app_handle: tauri::AppHandle,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<Vec<ThreadInfo>, String> {
+    let debug_state = sessions.active();
+    set_thread_frozen(&app_handle, &debug_state, thread_id, true).await
+}
+
+#[tauri::command]
+async fn thaw_thread(
+    thread_id: i64,
+    app_handle: tauri::AppHandle,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<Vec<ThreadInfo>, String> {
+    let debug_state = sessions.active();
+    set_thread_frozen(&app_handle, &debug_state, thread_id, false).await
+}
+
+// Changes how lldb handles a signal in the running session, e.g. passing SIGPIPE through without
+// stopping. The disposition is also kept for hot restarts. Returns lldb's signal table row.
+#[tauri::command]
+async fn set_signal_handling(
+    handling: SignalHandling,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<String, String> {
+    let debug_state = sessions.active();
+    if !debug_state.is_lldb_session() {
+        return Err("Signal handling is only configurable in Rust sessions".into());
+    }
+    let command = handling.lldb_command()?;
+
+    let output = {
+        let client_lock = debug_state.client.lock().await;
+        let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+        let resp = dap_client
+            .evaluate(&format!("`{}", command), None)
+            .await
+            .map_err(|e| format!("Failed to set signal handling: {}", e))?;
+        let result = EvaluateResponseBody::result_of(&resp).unwrap_or_default();
+        if resp.success == Some(false) || result.contains("error:") {
+            return Err(format!("lldb rejected {}: {}", command, result.trim()));
+        }
+        result
+    };
+
+    if let Some(last_launch) = debug_state.last_launch.write().as_mut() {
+        let signals = &mut last_launch.launch_config.signals;
+        signals.retain(|s| s.signal != handling.signal);
+        signals.push(handling.clone());
+    }
+    debug_state
+        .timeline
+        .write()
+        .record("signalHandling", json!(handling));
+    Ok(output)
+}
+
+#[tauri::command]
+async fn step_back(sessions: tauri::State<'_, Arc<SessionManager>>) -> Result<String, String> {
+    let debug_state = sessions.active();
+    if !debug_state.capabilities.read().supports_step_back {
+        return Err("This debug adapter doesn't support stepping back".into());
+    }
+    let client_lock = debug_state.client.lock().await;
+    let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+    let thread_id = match *debug_state.current_thread_id.read() {
+        Some(id) => id,
+        None => return Err("No current thread id available; debugger is not paused.".into()),
+    };
+
+    debug_state
+        .timeline
+        .write()
+        .record("stepBack", json!({ "threadId": thread_id }));
+    match dap_client.step_back(thread_id).await {
+        Ok(_) => Ok("Step back executed".into()),
+        Err(e) => Err(format!("Failed to step back: {}", e)),
+    }
+}
+
+#[tauri::command]
+async fn reverse_continue(
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<String, String> {
+    let debug_state = sessions.active();
+    // reverseContinue comes with stepBack support
+    if !debug_state.capabilities.read().supports_step_back {
+        return Err("This debug adapter doesn't support reverse continue".into());
+    }
+    let client_lock = debug_state.client.lock().await;
+    let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+    let thread_id = match *debug_state.current_thread_id.read() {
+        Some(id) => id,
+        None => return Err("No current thread id available; debugger is not paused.".into()),
+    };
+
+    debug_state
+        .timeline
+        .write()
+        .record("reverseContinue", json!({ "threadId": thread_id }));
+    match dap_client.reverse_continue(thread_id).await {
+        Ok(_) => Ok("Reverse continue executed".into()),
+        Err(e) => Err(format!("Failed to reverse continue: {}", e)),
+    }
+}
+
+// Sends keystrokes to the terminal the program runs in, e.g. a line for input() or Ctrl-C
+// ("\u0003").
+#[tauri::command]
+async fn write_pty(
+    data: String,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<(), String> {
+    let debug_state = sessions.active();
+    let mut pty = debug_state.pty.lock();
+    let console = pty
+        .as_mut()
+        .ok_or("The program isn't running in a terminal")?;
+    console.write(data.as_bytes())
+}
+
+// Tells the program its terminal changed size, so it can redraw.
+#[tauri::command]
+async fn resize_pty(
+    rows: u16,
+    cols: u16,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<(), String> {
+    let debug_state = sessions.active();
+    let pty = debug_state.pty.lock();
+    let console = pty
+        .as_ref()
+        .ok_or("The program isn't running in a terminal")?;
+    console.resize(rows, cols)
+}
+
+// Debug console history of the open project, oldest first, optionally filtered by `query` and
+// cut to the `limit` most recent entries.
+#[tauri::command]
+async fn get_console_history(
+    query: Option<String>,
+    limit: Option<usize>,
+    console_history: tauri::State<'_, Arc<ConsoleHistoryStore>>,
+    workspace: tauri::State<'_, Arc<Workspace>>,
+) -> Result<Vec<String>, String> {
+    Ok(console_history.entries(workspace.root().as_deref(), query.as_deref(), limit))
+}
+
+#[tauri::command]
+async fn clear_console_history(
+    console_history: tauri::State<'_, Arc<ConsoleHistoryStore>>,
+    workspace: tauri::State<'_, Arc<Workspace>>,
+) -> Result<(), String> {
+    console_history.clear(workspace.root().as_deref())
+}
+
+// Sets how much of strings and collections adapters render, and whether integers are shown in
+// hex. lldb sessions apply the limits right away; they also carry over to later launches.
+#[tauri::command]
+async fn set_format_limits(
+    limits: FormatLimits,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<FormatLimits, String> {
+    let debug_state = sessions.active();
+    *debug_state.format_limits.write() = limits.clone();
+
+    if debug_state.is_lldb_session() {
+        let client_lock = debug_state.client.lock().await;
+        if let Some(dap_client) = client_lock.as_ref() {
+            for command in limits.lldb_commands() {
+                dap_client
+                    .evaluate(&format!("`{}", command), None)
+                    .await
+                    .map_err(|e| format!("Failed to apply {}: {}", command, e))?;
+            }
+        }
+    }
+    Ok(limits)
+}
+
+// Scopes (locals, globals, registers, ...) of `frame_id`, or of the paused thread's top frame,
+// for the roots of the Variables pane. Expand a scope with get_variables on its
+// variablesReference.
+#[tauri::command]
+async fn get_scopes(
+    frame_id: Option<i64>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<Vec<Value>, String> {
+    let debug_state = sessions.active();
+    let thread_id = *debug_state.current_thread_id.read();
+    let client_lock = debug_state.client.lock().await;
+    let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+
+    let frame_id = match (frame_id, thread_id) {
+        (Some(frame_id), _) => frame_id,
+        (None, Some(thread_id)) => top_frame_id(dap_client, thread_id)
+            .await?
+            .map(i64::from)
+            .ok_or("The paused thread has no stack frames")?,
+        (None, None) => return Err("The program is not paused".to_string()),
+    };
+    let response = dap_client
+        .scopes(frame_id)
+        .await
+        .map_err(|e| format!("scopes request failed: {}", e))?;
+    let body: ScopesResponseBody<Value> = protocol::body(&response)?;
+    Ok(body.scopes)
+}
+
+// Children of a scope or structured value, `count` of them from `start` (all by default). Each
+// child with a non-zero variablesReference can be expanded the same way. `frame_id` is the frame
+// the scope came from; expression renderers are evaluated there.
+#[tauri::command]
+async fn get_variables(
+    variables_reference: i64,
+    frame_id: Option<i64>,
+    start: Option<u32>,
+    count: Option<u32>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+    renderers: tauri::State<'_, Arc<RendererRegistry>>,
+) -> Result<Vec<Value>, String> {
+    let debug_state = sessions.active();
+    let format = debug_state.format_limits.read().dap_format();
+    let client_lock = debug_state.client.lock().await;
+    let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+
+    let response = dap_client
+        .variables_page(variables_reference, start, count, format)
+        .await
+        .map_err(|e| format!("variables request failed: {}", e))?;
+    let body: VariablesResponseBody<Value> = protocol::body(&response)?;
+    let mut variables = body.variables;
+
+    let frame_id = frame_id.map(|id| id as i32);
+    render_variables(dap_client, &renderers, frame_id, &mut variables).await;
+    data_preview::mark_previewable(&mut variables);
+    Ok(variables)
+}
+
+// Fetches the complete value of variable `name` inside container `variables_reference`: the
+// untruncated rendering plus `count` of its children from `start` (100 by default), so big
+// values can be paged in on demand. `frame_id` is the frame that owns the variable.
+#[tauri::command]
+async fn fetch_full_value(
+    variables_reference: i64,
+    name: String,
+    frame_id: Option<i64>,
+    start: Option<u32>,
+    count: Option<u32>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+    renderers: tauri::State<'_, Arc<RendererRegistry>>,
+) -> Result<FullValue, String> {
+    let debug_state = sessions.active();
+    let format = debug_state.format_limits.read().dap_format();
+    let client_lock = debug_state.client.lock().await;
+    let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+
+    let siblings = dap_client
+        .variables_page(variables_reference, None, None, format.clone())
+        .await
+        .map_err(|e| format!("variables request failed: {}", e))?;
+    let variable = protocol::body::<VariablesResponseBody>(&siblings)?
+        .variables
+        .into_iter()
+        .find(|v| v.name == name)
+        .ok_or_else(|| format!("No variable named {}", name))?;
+
+    // The clipboard context asks the adapter for the value without truncation
+    let mut value = variable.value.clone();
+    let frame_id = frame_id.map(|id| id as i32);
+    if let Some(evaluate_name) = &variable.evaluate_name {
+        if let Ok(resp) = dap_client
+            .evaluate_in(evaluate_name, frame_id, "clipboard", format.clone())
+            .await
+        {
+            if let Ok(body) = protocol::body::<EvaluateResponseBody>(&resp) {
+                value = body.result;
+            }
+        }
+    }
+
+    let start = start.unwrap_or(0);
+    let reference = variable.variables_reference;
+    let mut children = if reference > 0 {
+        let response = dap_client
+            .variables_page(reference, Some(start), Some(count.unwrap_or(100)), format)
+            .await
+            .map_err(|e| format!("variables request failed: {}", e))?;
+        protocol::body::<VariablesResponseBody<Value>>(&response)
+            .map(|body| body.variables)
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    render_variables(dap_client, &renderers, frame_id, &mut children).await;
+    data_preview::mark_previewable(&mut children);
+    let rendered_value = match &variable.type_name {
+        Some(type_name) => render_value(
+            dap_client,
+            &renderers,
+            frame_id,
+            type_name,
+            &value,
+            variable.evaluate_name.as_deref(),
+        )
+        .await
+        .map(|(_, rendered)| rendered),
+        None => None,
+    };
+
+    Ok(FullValue {
+        name,
+        value,
+        rendered_value,
+        variables_reference: reference,
+        children,
+        start,
+        indexed_children: variable.indexed_variables,
+    })
+}
+
+// Copies the value behind `variables_reference` out of the debugger as JSON, expanding nested
+// values down to `depth` levels (5 by default). Values past the depth are kept as rendered.
+#[tauri::command]
+async fn export_variable(
+    variables_reference: i64,
+    depth: Option<u32>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<Value, String> {
+    let debug_state = sessions.active();
+    let client_lock = debug_state.client.lock().await;
+    let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+
+    let mut budget = variable_export::MAX_EXPORTED_NODES;
+    let value = export_children(
+        dap_client,
+        variables_reference,
+        depth.unwrap_or(5),
+        &mut budget,
+    )
+    .await?;
+    if budget == 0 {
+        warn!(
+            "export_variable stopped after {} values",
+            variable_export::MAX_EXPORTED_NODES
+        );
+    }
+    Ok(value)
+}
+
+fn export_children<'a>(
+    dap_client: &'a DAPClient,
+    variables_reference: i64,
+    depth: u32,
+    budget: &'a mut usize,
+) -> Pin<Box<dyn Future<Output = Result<Value, String>> + Send + 'a>> {
+    Box::pin(async move {
+        let response = dap_client
+            .variables(variables_reference)
+            .await
+            .map_err(|e| format!("variables request failed: {}", e))?;
+        let variables = protocol::body::<VariablesResponseBody>(&response)
+            .map(|body| body.variables)
+            .unwrap_or_default();
+
+        let mut children = Vec::new();
+        for variable in variables {
+            if variable_export::is_synthetic(&variable.name) {
+                continue;
+            }
+            if *budget == 0 {
+                break;
+            }
+            *budget -= 1;
+
+            let reference = variable.variables_reference;
+            let value = if reference > 0 && depth > 1 {
+                export_children(dap_client, reference, depth - 1, budget).await?
+            } else {
+                variable_export::leaf_value(&variable.value)
+            };
+            children.push((variable.name, value));
+        }
+        Ok(variable_export::assemble(children))
+    })
+}
+
+#[tauri::command]
+async fn evaluate_expression(
+    expression: String,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+    console_history: tauri::State<'_, Arc<ConsoleHistoryStore>>,
+    workspace: tauri::State<'_, Arc<Workspace>>,
+    renderers: tauri::State<'_, Arc<RendererRegistry>>,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+) -> Result<Value, String> {
+    let debug_state = sessions.active();
+    if let Err(e) = console_history.record(workspace.root().as_deref(), &expression) {
+        warn!("{}", e);
+    }
+    // Get the DAP client
+    let client_lock = debug_state.client.lock().await;
+    let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+    debug_state.record_console_input(&expression);
+
+    // Get frame ID for evaluation
+    let frame_id = match dap_client.stack_trace(1).await {
+        Ok(st_resp) => protocol::body::<StackTraceResponseBody>(&st_resp)
+            .ok()
+            .and_then(|body| body.stack_frames.first().map(|frame| frame.id as i32)),
+        Err(e) => {
+            warn!("Failed to get stack trace: {}", e);
+            None
+        }
+    };
+
+    // The session's adapter may need console input and results adjusted
+    let adapter = adapters::engine::for_session(&debug_state, &settings.get());
+    let paused = frame_id.is_some();
+    let (eval_expression, context) = match &adapter {
+        Some(adapter) => adapter.console_evaluate(&expression, paused),
+        None => (
+            expression.clone(),
+            adapters::engine::console_context(paused),
+        ),
+    };
+
+    // Now call evaluate with the potentially modified expression
+    let format = debug_state.format_limits.read().dap_format();
+    let eval_resp = dap_client
+        .evaluate_in(&eval_expression, frame_id, context, format)
+        .await
+        .map_err(|e| format!("Failed to evaluate expression: {}", e))?;
+    let evaluated = EvaluateResponseBody::from_response(&eval_resp);
+    debug_state.timeline.write().record(
+        "evaluate",
+        json!({
+            "expression": expression,
+            "success": eval_resp.success,
+            "result": evaluated.as_ref().map(|e| &e.result),
+        }),
+    );
+    if eval_resp.success == Some(false) && eval_resp.message.as_deref() == Some("cancelled") {
+        return Err("Evaluation cancelled".into());
+    }
+
+    if let Some(mut body) = eval_resp.body {
+        let Some(evaluated) = evaluated else {
+            return Ok(body);
+        };
+        let processed = adapter.and_then(|adapter| adapter.evaluate_result(&evaluated.result));
+        let rewritten = processed.is_some();
+        let result = processed.unwrap_or(evaluated.result);
+
+        // The project's renderer for the result type, if any, goes next to the raw result
+        if let Some(type_name) = &evaluated.type_name {
+            if let Some((renderer, rendered)) = render_value(
+                dap_client,
+                &renderers,
+                frame_id,
+                type_name,
+                &result,
+                Some(&expression),
+            )
+            .await
+            {
+                if let Value::Object(ref mut map) = body {
+                    map.insert("renderedResult".to_string(), json!(rendered));
+                    map.insert("renderer".to_string(), json!(renderer));
+                }
+            }
+        }
+
+        // A result the adapter cleaned up goes out in a new body
+        if rewritten {
+            let mut processed_body = serde_json::Map::new();
+            processed_body.insert("result".to_string(), json!(result));
+            processed_body.insert("type".to_string(), json!(evaluated.type_name));
+            processed_body.insert(
+                "variablesReference".to_string(),
+                json!(evaluated.variables_reference),
+            );
+            for key in ["renderedResult", "renderer"] {
+                if let Some(value) = body.get(key) {
+                    processed_body.insert(key.to_string(), value.clone());
+                }
+            }
+            return Ok(serde_json::Value::Object(processed_body));
+        }
+        // Return the full body if no special processing was done
+        return Ok(body);
+    }
+    Err("No result returned from evaluate".into())
+}
+
+// Requests the active session's adapter hasn't answered yet, oldest first.
+#[tauri::command]
+async fn list_in_flight_requests(
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<Vec<InFlightRequest>, String> {
+    let debug_state = sessions.active();
+    let canceller = debug_state.canceller.lock().clone();
+    Ok(canceller.map(|c| c.in_flight()).unwrap_or_default())
+}
+
+// Gives up on request `request_seq`, or on every evaluation in flight when it's not given, so a
+// hung expression doesn't hold the session until the timeout. Adapters that support it are told
+// to abandon the work. Returns how many requests were cancelled.
+#[tauri::command]
+async fn cancel_request(
+    request_seq: Option<i32>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<usize, String> {
+    let debug_state = sessions.active();
+    let canceller = debug_state
+        .canceller
+        .lock()
+        .clone()
+        .ok_or("No active debug session")?;
+    let targets: Vec<i32> = match request_seq {
+        Some(seq) => vec![seq],
+        None => canceller
+            .in_flight()
+            .into_iter()
+            .filter(|r| r.command == "evaluate")
+            .map(|r| r.seq)
+            .collect(),
+    };
+    let notify_adapter = debug_state.capabilities.read().supports_cancel_request;
+
+    let mut cancelled = 0;
+    for seq in targets {
+        let was_in_flight = canceller
+            .cancel(seq, notify_adapter)
+            .await
+            .map_err(|e| format!("Failed to cancel request {}: {}", seq, e))?;
+        if was_in_flight {
+            cancelled += 1;
+            debug_state
+                .timeline
+                .write()
+                .record("requestCancelled", json!({ "seq": seq }));
+        }
+    }
+    Ok(cancelled)
+}
+
+#[tauri::command]
+async fn get_call_stack(
+    thread_id: i64,
+    collapse: Option<bool>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<Vec<FrameInfo>, String> {
+    let debug_state = sessions.active();
+    // Grab the DAP client
+    let client_lock = debug_state.client.lock().await;
+    let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+
+    // Issue the stackTrace request for every frame
+    let resp = dap_client
+        .stack_trace_page(thread_id, 0, 0)
+        .await
+        .map_err(|e| format!("stack_trace request failed: {e}"))?;
+
+    // Deep recursion is collapsed unless the caller asks for every frame (to expand it)
+    let body: StackTraceResponseBody = protocol::body(&resp)?;
+    Ok(FrameInfo::from_stack_trace(body, collapse.unwrap_or(true)))
+}
+
+// Every thread's stack at once, e.g. to see what each thread of a deadlocked program is waiting
+// on. All stackTrace requests go out before any response is awaited, so the adapter works on
+// them together instead of one round trip per thread.
+#[tauri::command]
+async fn get_all_backtraces(
+    collapse: Option<bool>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<Vec<ThreadBacktrace>, String> {
+    let debug_state = sessions.active();
+    if !matches!(*debug_state.state.read(), DebuggerState::Paused { .. }) {
+        return Err("The program must be paused to collect backtraces".into());
+    }
+    let client_lock = debug_state.client.lock().await;
+    let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+
+    // Pick up threads that started since the last thread event
+    if let Ok(resp) = dap_client.threads().await {
+        debug_state.update_threads(&resp);
+    }
+
+    let pending: Vec<(ThreadInfo, Result<i32, String>)> = debug_state
+        .thread_list()
+        .into_iter()
+        .map(|thread| {
+            // lldb always stops every thread, even if the stop didn't say so
+            let seq = if thread.paused || debug_state.is_lldb_session() {
+                dap_client
+                    .send_message(DAPMessage::request(
+                        "stackTrace",
+                        Some(json!({ "threadId": thread.id, "startFrame": 0, "levels": 0 })),
+                    ))
+                    .map_err(|e| format!("stackTrace request failed: {}", e))
+            } else {
+                Err("Thread is running".to_string())
+            };
+            (thread, seq)
+        })
+        .collect();
+
+    let mut backtraces = Vec::with_capacity(pending.len());
+    for (thread, seq) in pending {
+        let response = match seq {
+            Ok(seq) => dap_client
+                .wait_for_response(seq, 10.0)
+                .await
+                .ok_or_else(|| "Timeout waiting for stackTrace response".to_string()),
+            Err(e) => Err(e),
+        };
+        let (frames, error) = match response.and_then(|resp| protocol::body(&resp)) {
+            Ok(body) => (
+                FrameInfo::from_stack_trace(body, collapse.unwrap_or(true)),
+                None,
+            ),
+            Err(e) => (Vec::new(), Some(e)),
+        };
+        backtraces.push(ThreadBacktrace {
+            thread_id: thread.id,
+            name: thread.name,
+            frames,
+            error,
+        });
+    }
+    Ok(backtraces)
+}
+
+// Registers of a frame (the current thread's top frame by default) for native sessions. Each
+// register is flagged if its value changed since the previous call, so stepping an instruction
+// highlights what it touched.
+#[tauri::command]
+async fn get_registers(
+    frame_id: Option<i64>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<Vec<RegisterGroup>, String> {
+    let debug_state = sessions.active();
+    if !debug_state.is_lldb_session() {
+        return Err("Registers are only available in Rust sessions".into());
+    }
+    let thread_id = match *debug_state.current_thread_id.read() {
+        Some(id) => id,
+        None => return Err("No current thread id available; debugger is not paused.".into()),
+    };
+
+    let client_lock = debug_state.client.lock().await;
+    let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+    let frame_id = match frame_id {
+        Some(id) => id,
+        None => top_frame_id(dap_client, thread_id)
+            .await?
+            .ok_or("The current thread has no frames")? as i64,
+    };
+    let previous = debug_state.register_snapshot.read().clone();
+
+    let scopes = dap_client
+        .scopes(frame_id)
+        .await
+        .map_err(|e| format!("scopes request failed: {}", e))?;
+    let registers_ref = protocol::body::<ScopesResponseBody>(&scopes)?
+        .scopes
+        .into_iter()
+        // Adapters following the spec mark it with a presentationHint; older lldb-dap builds
+        // only name it
+        .find(|scope| {
+            scope.presentation_hint.as_deref() == Some("registers") || scope.name == "Registers"
+        })
+        .map(|scope| scope.variables_reference)
+        .filter(|r| *r > 0);
+
+    let groups = match registers_ref {
+        // The scope holds one variable per register set, each expanding to its registers
+        Some(reference) => {
+            let sets = dap_client
+                .variables(reference)
+                .await
+                .map_err(|e| format!("variables request failed: {}", e))?;
+            let sets = protocol::body::<VariablesResponseBody>(&sets)
+                .map(|body| body.variables)
+                .unwrap_or_default();
+
+            let mut groups = Vec::new();
+            for set in sets {
+                let registers = dap_client
+                    .variables(set.variables_reference)
+                    .await
+                    .map_err(|e| format!("variables request failed: {}", e))?;
+                if let Ok(body) = protocol::body::<VariablesResponseBody>(&registers) {
+                    groups.push(registers::group_from_variables(
+                        &set.name,
+                        &body.variables,
+                        &previous,
+                    ));
+                }
+            }
+            groups
+        }
+        None => {
+            let response = dap_client
+                // Commands only run in the repl context, which uses lldb's selected frame
+                .evaluate("`register read", None)
+                .await
+                .map_err(|e| format!("register read failed: {}", e))?;
+            let output = EvaluateResponseBody::result_of(&response).unwrap_or_default();
+            registers::parse_register_read(&output, &previous)
+        }
+    };
+
+    *debug_state.register_snapshot.write() = registers::snapshot(&groups);
+    Ok(groups)
+}
+
+#[tauri::command]
+async fn get_disassembly(
+    memory_reference: Option<String>,
+    instruction_offset: Option<i64>,
+    instruction_count: Option<i64>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<Value, String> {
+    let debug_state = sessions.active();
+    let client_lock = debug_state.client.lock().await;
+    let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+
+    // Default to the instruction we're currently stopped at, with some context before it
+    let memory_reference = memory_reference
+        .or_else(|| debug_state.current_instruction_pointer.read().clone())
+        .ok_or("No instruction pointer available; debugger is not paused.")?;
+    let instruction_offset = instruction_offset.unwrap_or(-16);
+    let instruction_count = instruction_count.unwrap_or(64);
+
+    let response = dap_client
+        .disassemble(&memory_reference, instruction_offset, instruction_count)
+        .await
+        .map_err(|e| format!("Failed to disassemble: {}", e))?;
+
+    let mut body = response
+        .body
+        .ok_or("No instructions in disassemble response")?;
+    if let Value::Object(ref mut map) = body {
+        map.insert(
+            "instructionPointerReference".to_string(),
+            json!(memory_reference),
+        );
+    }
+    Ok(body)
+}
+
+// Reads `count` bytes (256 by default) at `offset` from `memory_reference`, e.g. a variable's
+// memoryReference or an address like "0x7ffd5c3e1a40", for the memory inspector.
+#[tauri::command]
+async fn read_memory(
+    memory_reference: String,
+    offset: Option<i64>,
+    count: Option<u32>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<MemoryBlock, String> {
+    let debug_state = sessions.active();
+    let client_lock = debug_state.client.lock().await;
+    let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+
+    let count = count.unwrap_or(256).min(memory::MAX_READ_BYTES);
+    let response = dap_client
+        .read_memory(&memory_reference, offset.unwrap_or(0), count)
+        .await
+        .map_err(|e| format!("readMemory request failed: {}", e))?;
+    if response.success == Some(false) {
+        return Err(format!(
+            "Failed to read memory at {}: {}",
+            memory_reference,
+            response.message.unwrap_or_default()
+        ));
+    }
+    let body = response.body.ok_or("No body in readMemory response")?;
+    MemoryBlock::from_response(&body)
+}
+
+#[tauri::command]
+async fn get_inline_values(
+    file: String,
+    first_line: i64,
+    last_line: i64,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+    workspace: tauri::State<'_, Arc<Workspace>>,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+) -> Result<Vec<InlineValue>, String> {
+    let resolved = workspace.resolve(&file, &settings.get().allowed_paths)?;
+    let debug_state = sessions.active();
+    let thread_id = match *debug_state.current_thread_id.read() {
+        Some(id) => id,
+        None => return Err("No current thread id available; debugger is not paused.".into()),
+    };
+
+    // Values of the paused frame's visible scopes, by name
+    let mut variables = HashMap::new();
+    {
+        let client_lock = debug_state.client.lock().await;
+        let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+
+        let stack = dap_client
+            .stack_trace(thread_id)
+            .await
+            .map_err(|e| format!("stack_trace request failed: {}", e))?;
+        let stack: StackTraceResponseBody = protocol::body(&stack)?;
+        let frame = stack
+            .stack_frames
+            .first()
+            .ok_or("No stack frames for the current thread")?;
+        // Locals only make sense next to the function they belong to
+        if frame.path() != Some(file.as_str()) {
+            return Ok(Vec::new());
+        }
+
+        let scopes = dap_client
+            .scopes(frame.id)
+            .await
+            .map_err(|e| format!("scopes request failed: {}", e))?;
+        let scopes = protocol::body::<ScopesResponseBody>(&scopes)
+            .map(|body| body.scopes)
+            .unwrap_or_default();
+
+        for scope in scopes {
+            // Expensive scopes (globals, registers) are skipped, as the spec suggests
+            if scope.expensive {
+                continue;
+            }
+            let response = dap_client
+                .variables(scope.variables_reference)
+                .await
+                .map_err(|e| format!("variables request failed: {}", e))?;
+            let scope_variables = protocol::body::<VariablesResponseBody>(&response)
+                .map(|body| body.variables)
+                .unwrap_or_default();
+            for variable in scope_variables {
+                // Inner scopes come first and shadow outer ones
+                variables.entry(variable.name).or_insert(variable.value);
+            }
+        }
+    }
+
+    let source =
+        fs::read_to_string(&resolved).map_err(|e| format!("Failed to read {}: {}", file, e))?;
+    Ok(inline_values::match_identifiers(
+        &source, first_line, last_line, &variables,
+    ))
+}
+
+// Renders one value with the project's renderers, evaluating expression renderers in `frame_id`.
+// Returns the renderer's name and the rendering.
+async fn render_value(
+    dap_client: &DAPClient,
+    renderers: &RendererRegistry,
+    frame_id: Option<i32>,
+    type_name: &str,
+    value: &str,
+    evaluate_name: Option<&str>,
+) -> Option<(&'static str, String)> {
+    let (renderer, rendering) = renderers.render(type_name, value, evaluate_name)?;
+    match rendering {
+        Rendering::Text(text) => Some((renderer, text)),
+        Rendering::Evaluate(expression) => {
+            let response = dap_client
+                .evaluate_in(&expression, frame_id, "hover", None)
+                .await
+                .ok()?;
+            let body: EvaluateResponseBody = protocol::body(&response).ok()?;
+            Some((renderer, body.result))
+        }
+    }
+}
+
+// Adds `renderedValue` and `renderer` to the DAP Variable objects a project renderer applies to.
+async fn render_variables(
+    dap_client: &DAPClient,
+    renderers: &RendererRegistry,
+    frame_id: Option<i32>,
+    variables: &mut [Value],
+) {
+    if renderers.is_empty() {
+        return;
+    }
+    for variable in variables.iter_mut() {
+        let Some(parsed) = Variable::from_value(variable) else {
+            continue;
+        };
+        let Some(type_name) = &parsed.type_name else {
+            continue;
+        };
+        let Some((renderer, rendered)) = render_value(
+            dap_client,
+            renderers,
+            frame_id,
+            type_name,
+            &parsed.value,
+            parsed.evaluate_name.as_deref(),
+        )
+        .await
+        else {
+            continue;
+        };
+        if let Value::Object(ref mut map) = variable {
+            map.insert("renderedValue".to_string(), json!(rendered));
+            map.insert("renderer".to_string(), json!(renderer));
+        }
+    }
+}
+
+// The project's snippet library (.wayfind/snippets.json).
+#[tauri::command]
+async fn list_snippets(
+    workspace: tauri::State<'_, Arc<Workspace>>,
+) -> Result<Vec<Snippet>, String> {
+    snippets::load(workspace.root().as_deref())
+}
+
+// Evaluates the named snippet like a console expression, in the current top frame.
+#[tauri::command]
+async fn run_snippet(
+    name: String,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+    console_history: tauri::State<'_, Arc<ConsoleHistoryStore>>,
+    workspace: tauri::State<'_, Arc<Workspace>>,
+    renderers: tauri::State<'_, Arc<RendererRegistry>>,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+) -> Result<Value, String> {
+    let snippet = snippets::load(workspace.root().as_deref())?
+        .into_iter()
+        .find(|snippet| snippet.name == name)
+        .ok_or_else(|| format!("No snippet named '{}'", name))?;
+    let debugger_type = sessions.active().debugger_type.read().clone();
+    if !snippet.runs_in(debugger_type.as_deref()) {
+        return Err(format!(
+            "Snippet '{}' is written for {} sessions",
+            name,
+            snippet.language.as_deref().unwrap_or_default()
+        ));
+    }
+    sessions
+        .active()
+        .timeline
+        .write()
+        .record("snippet", json!({ "name": name }));
+    evaluate_expression(
+        snippet.expression,
+        sessions,
+        console_history,
+        workspace,
+        renderers,
+        settings,
+    )
+    .await
+}
+
+// Evaluates exactly what is selected in the editor. The frame is the one in the paused stack
+// whose file is `file` and whose line is closest to `line` (innermost on ties), so a selection in
+// a caller evaluates in that caller. The hover context keeps this free of side effects: lldb-dap
+// only looks up variable paths, and debugpy doesn't echo into the console.
+#[tauri::command]
+async fn evaluate_selection(
+    text: String,
+    file: String,
+    line: i64,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+) -> Result<SelectionValue, String> {
+    let debug_state = sessions.active();
+    let expression = text.trim();
+    if expression.is_empty() {
+        return Err("Nothing is selected".into());
+    }
+    let thread_id = match *debug_state.state.read() {
+        DebuggerState::Paused { thread_id, .. } => thread_id,
+        _ => return Err("The program must be paused to evaluate a selection".into()),
+    };
+
+    let client_lock = debug_state.client.lock().await;
+    let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+    // stack_trace only fetches the top frame; the selection may be in any caller
+    let stack = dap_client
+        .stack_trace_page(thread_id, 0, 0)
+        .await
+        .map_err(|e| format!("stack_trace request failed: {}", e))?;
+    let frames = protocol::body::<StackTraceResponseBody>(&stack)?.stack_frames;
+
+    let selected_file = std::fs::canonicalize(&file).unwrap_or_else(|_| file.clone().into());
+    let in_file = |frame: &&StackFrame| {
+        frame.path().is_some_and(|path| {
+            let path = std::path::Path::new(path);
+            path == selected_file
+                || std::fs::canonicalize(path).is_ok_and(|path| path == selected_file)
+        })
+    };
+    let matched = frames
+        .iter()
+        .filter(in_file)
+        .min_by_key(|frame| (frame.line - line).abs());
+    let frame_matched = matched.is_some();
+    let frame = matched
+        .or(frames.first())
+        .ok_or("The paused thread has no stack frames")?;
+    let frame_id = frame.id;
+    let frame_name = frame.display_name();
+
+    let format = debug_state.format_limits.read().dap_format();
+    let resp = dap_client
+        .evaluate_in(expression, Some(frame_id as i32), "hover", format)
+        .await
+        .map_err(|e| format!("Failed to evaluate selection: {}", e))?;
+    if resp.success == Some(false) {
+        return Err(resp
+            .message
+            .unwrap_or_else(|| format!("Could not evaluate {}", expression)));
+    }
+    let body: EvaluateResponseBody = protocol::body(&resp)?;
+    let result = adapters::engine::for_session(&debug_state, &settings.get())
+        .and_then(|adapter| adapter.evaluate_result(&body.result))
+        .unwrap_or(body.result);
+    debug_state.timeline.write().record(
+        "evaluate",
+        json!({ "expression": expression, "success": true, "result": result, "frameId": frame_id }),
+    );
+
+    Ok(SelectionValue {
+        result,
+        type_name: body.type_name,
+        variables_reference: body.variables_reference,
+        frame_id,
+        frame_name,
+        frame_matched,
+    })
+}
+
+// Id of the innermost frame of a paused thread, for evaluating in the program's context.
+async fn top_frame_id(dap_client: &DAPClient, thread_id: i64) -> Result<Option<i32>, String> {
+    let stack = dap_client
+        .stack_trace(thread_id)
+        .await
+        .map_err(|e| format!("stack_trace request failed: {}", e))?;
+    Ok(protocol::body::<StackTraceResponseBody>(&stack)
+        .ok()
+        .and_then(|body| body.stack_frames.first().map(|frame| frame.id as i32)))
+}
+
+#[tauri::command]
+async fn search_symbols(
+    query: String,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<Vec<SymbolMatch>, String> {
+    let debug_state = sessions.active();
+    if query.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let client_lock = debug_state.client.lock().await;
+    let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+
+    if debug_state.is_lldb_session() {
+        let response = dap_client
+            .evaluate(&symbols::lldb_lookup_command(&query)?, None)
+            .await
+            .map_err(|e| format!("Symbol lookup failed: {}", e))?;
+        let output = EvaluateResponseBody::result_of(&response).unwrap_or_default();
+        return Ok(symbols::parse_lldb_lookup(&output));
+    }
+
+    // debugpy can only evaluate inside a frame, so Python sessions need to be paused
+    let thread_id = match *debug_state.current_thread_id.read() {
+        Some(id) => id,
+        None => return Err("Pause the program to search Python symbols".into()),
+    };
+    let frame_id = top_frame_id(dap_client, thread_id).await?;
+    let response = dap_client
+        .evaluate(&symbols::python_search_expression(&query)?, frame_id)
+        .await
+        .map_err(|e| format!("Symbol search failed: {}", e))?;
+    let result = EvaluateResponseBody::result_of(&response).unwrap_or_else(|| "[]".to_string());
+    Ok(symbols::parse_python_search(&result))
+}
+
+// The environment the paused program actually sees, sorted by name.
+#[tauri::command]
+async fn get_debuggee_environment(
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<Vec<EnvironmentVariable>, String> {
+    let debug_state = sessions.active();
+    let thread_id = match *debug_state.current_thread_id.read() {
+        Some(id) => id,
+        None => return Err("Pause the program to inspect its environment".into()),
+    };
+
+    let client_lock = debug_state.client.lock().await;
+    let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+    let response = if debug_state.is_lldb_session() {
+        dap_client.evaluate(environment::lldb_command(), None).await
+    } else {
+        let frame_id = top_frame_id(dap_client, thread_id).await?;
+        dap_client
+            .evaluate(environment::python_expression(), frame_id)
+            .await
+    }
+    .map_err(|e| format!("Failed to read the environment: {}", e))?;
+
+    let result = EvaluateResponseBody::result_of(&response).unwrap_or_default();
+    if response.success == Some(false) {
+        return Err(format!("Failed to read the environment: {}", result));
+    }
+    environment::parse(&result)
+}
+
+// Shape, element types and the first `rows` rows (20 by default) of a NumPy array or pandas
+// frame/series, for Python sessions. Wide values are cut to `columns` columns (also 20).
+#[tauri::command]
+async fn get_data_preview(
+    expression: String,
+    rows: Option<usize>,
+    columns: Option<usize>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<DataPreview, String> {
+    let debug_state = sessions.active();
+    if debug_state.debugger_type.read().as_deref() != Some("python") {
+        return Err("Data previews are only available in Python sessions".into());
+    }
+    let thread_id = match *debug_state.current_thread_id.read() {
+        Some(id) => id,
+        None => return Err("Pause the program to preview data".into()),
+    };
+
+    let client_lock = debug_state.client.lock().await;
+    let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+    let frame_id = top_frame_id(dap_client, thread_id).await?;
+    let response = dap_client
+        .evaluate_in(
+            &data_preview::python_expression(
+                &expression,
+                rows.unwrap_or(20),
+                columns.unwrap_or(20),
+            ),
+            frame_id,
+            // Hover results may be truncated; the preview has to come back whole
+            "clipboard",
+            None,
+        )
+        .await
+        .map_err(|e| format!("Failed to preview {}: {}", expression, e))?;
+    let result = EvaluateResponseBody::result_of(&response).unwrap_or_default();
+    if response.success == Some(false) {
+        return Err(format!(
+            "Failed to preview {}: {}",
+            expression,
+            response.message.as_deref().unwrap_or(&result)
+        ));
+    }
+    data_preview::parse(&result)
+}
+
+// One target of a compound launch, e.g. a Python server and the Rust client talking to it.
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CompoundTarget {
+    // Session token used with set_active_session; must be unique within the launch
+    token: String,
+    script_path: String,
+    debug_engine: String,
+    launch_config: Option<LaunchConfig>,
+}
+
+#[tauri::command]
+async fn launch_compound(
+    app_handle: tauri::AppHandle,
+    targets: Vec<CompoundTarget>,
+    confirmed: Option<bool>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+    workspace: tauri::State<'_, Arc<Workspace>>,
+    adapter_pool: tauri::State<'_, Arc<AdapterPool>>,
+) -> Result<Vec<SessionInfo>, String> {
+    let first = targets
+        .first()
+        .ok_or("A compound launch needs at least one target")?;
+    let first_token = first.token.clone();
+    let mut tokens = HashSet::new();
+    if let Some(target) = targets.iter().find(|t| !tokens.insert(t.token.as_str())) {
+        return Err(format!(
+            "Compound targets share the session token '{}'",
+            target.token
+        ));
+    }
+
+    // A target that fails to launch stops the ones started before it, so a compound never
+    // runs half-started
+    let mut launched: Vec<String> = Vec::new();
+    for target in targets {
+        let token = target.token.clone();
+        let result = launch_debug_session(
+            app_handle.clone(),
+            target.script_path,
+            target.debug_engine,
+            target.launch_config,
+            Some(target.token),
+            confirmed,
+            None,
+            sessions.clone(),
+            settings.clone(),
+            workspace.clone(),
+            adapter_pool.clone(),
+        )
+        .await;
+        if let Err(e) = result {
+            for token in &launched {
+                let Some(debug_state) = sessions.get(token) else {
+                    continue;
+                };
+                if let Err(err) = terminate_session(&app_handle, &debug_state).await {
+                    warn!("Failed to stop compound target {}: {}", token, err);
+                }
             }
+            return Err(e);
         }
-        // Return the full body if no special processing was done
-        return Ok(body);
+        launched.push(token);
     }
-    Err("No result returned from evaluate".into())
+
+    // Route commands to the first target until the user switches
+    sessions.set_active(&first_token)?;
+    Ok(sessions.list())
 }
 
 #[tauri::command]
-async fn get_call_stack(
-    thread_id: i64,
-    debug_state: tauri::State<'_, std::sync::Arc<DebugSessionState>>,
-) -> Result<Vec<FrameInfo>, String> {
-    // Grab the DAP client
+async fn set_active_session(
+    token: String,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<SessionInfo, String> {
+    sessions.set_active(&token)?;
+    Ok(sessions.active().session_info())
+}
+
+#[tauri::command]
+async fn list_sessions(
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<Vec<SessionInfo>, String> {
+    Ok(sessions.list())
+}
+
+#[tauri::command]
+async fn get_session_info(
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<SessionInfo, String> {
+    let debug_state = sessions.active();
+    Ok(debug_state.session_info())
+}
+
+// Live threads with their names, refreshed from the adapter when a session is running.
+#[tauri::command]
+async fn get_threads(
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<Vec<ThreadInfo>, String> {
+    let debug_state = sessions.active();
     let client_lock = debug_state.client.lock().await;
-    let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+    if let Some(dap_client) = client_lock.as_ref() {
+        let resp = dap_client
+            .threads()
+            .await
+            .map_err(|e| format!("threads request failed: {}", e))?;
+        debug_state.update_threads(&resp);
+    }
+    Ok(debug_state.thread_list())
+}
 
-    // Issue the stackTrace request
-    let resp = dap_client
-        .stack_trace(thread_id)
-        .await
-        .map_err(|e| format!("stack_trace request failed: {e}"))?;
+// Diagnoses a Python interpreter for debugging (defaults to the one sessions launch with), and
+// with `install` pip-installs debugpy into it when missing or too old.
+#[tauri::command]
+async fn check_python_env(
+    interpreter: Option<String>,
+    install: Option<bool>,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+    workspace: tauri::State<'_, Arc<Workspace>>,
+) -> Result<PythonEnvReport, String> {
+    let interpreter = match interpreter {
+        Some(i) => shellexpand::tilde(&i).into_owned(),
+        None => interpreter::resolve(workspace.root().as_deref(), &settings.get())?,
+    };
+    let report = preflight::check_python_env(&interpreter, install.unwrap_or(false));
+    info!(
+        "Python env {}: python {:?}, debugpy {:?}",
+        interpreter, report.python_version, report.debugpy_version
+    );
+    Ok(report)
+}
 
-    // The response body should have something like { "stackFrames": [ { "id": ..., "name": ..., "line": ..., "column": ..., "source": {...} }, ... ] }
-    if let Some(body) = resp.body {
-        let frames = body
-            .get("stackFrames")
-            .and_then(|val| val.as_array())
-            .unwrap_or(&vec![])
-            .iter()
-            .map(|f| {
-                // Extract fields
-                let id = f.get("id").and_then(|v| v.as_i64()).unwrap_or(0);
-                let name = f
-                    .get("name")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("<unknown>")
-                    .to_string();
-                let line = f.get("line").and_then(|v| v.as_i64()).unwrap_or(0);
-                let column = f.get("column").and_then(|v| v.as_i64());
-                let file = f
-                    .get("source")
-                    .and_then(|src| src.get("path"))
-                    .and_then(|p| p.as_str())
-                    .map(String::from);
-
-                FrameInfo {
-                    id,
-                    name,
-                    line,
-                    column,
-                    file,
-                }
-            })
-            .collect::<Vec<FrameInfo>>();
+// Sets the interpreter Python sessions launch with, for the open project or globally. None
+// clears it, falling back to the global one or python3 on PATH. Returns the check of the
+// interpreter now in effect, so the UI can offer to install debugpy.
+#[tauri::command]
+async fn set_python_interpreter(
+    interpreter: Option<String>,
+    scope: InterpreterScope,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+    workspace: tauri::State<'_, Arc<Workspace>>,
+) -> Result<PythonEnvReport, String> {
+    let interpreter = interpreter.filter(|i| !i.trim().is_empty());
+    if let Some(i) = &interpreter {
+        let expanded = shellexpand::tilde(i).into_owned();
+        let report = preflight::check_python_env(&expanded, false);
+        if !report.runs {
+            return Err(format!(
+                "{} is not a usable Python interpreter: {}",
+                expanded,
+                report.error.unwrap_or_default()
+            ));
+        }
+    }
+    match scope {
+        InterpreterScope::Project => {
+            let root = workspace.root().ok_or("No workspace is open")?;
+            interpreter::set_project_interpreter(&root, interpreter)?;
+        }
+        InterpreterScope::Global => {
+            let mut new_settings = settings.get();
+            new_settings.python_interpreter = interpreter;
+            settings.set(new_settings)?;
+        }
+    }
+    let effective = interpreter::resolve(workspace.root().as_deref(), &settings.get())?;
+    info!("Python interpreter set ({:?}): {}", scope, effective);
+    Ok(preflight::check_python_env(&effective, false))
+}
 
-        Ok(frames)
-    } else {
-        Err("No stackFrames in the response".to_owned())
+#[tauri::command]
+async fn get_workspace_layout(root: String) -> Result<WorkspaceLayout, String> {
+    let root = shellexpand::tilde(&root).into_owned();
+    cargo_workspace::workspace_layout(std::path::Path::new(&root))
+}
+
+// Runnable things in the open workspace, for one-click choices in the launch dialog.
+#[tauri::command]
+async fn index_debug_targets(
+    workspace: tauri::State<'_, Arc<Workspace>>,
+) -> Result<debug_targets::DebugTargetIndex, String> {
+    let root = workspace.root().ok_or("No workspace is open")?;
+    Ok(debug_targets::index(&root))
+}
+
+#[tauri::command]
+async fn list_launch_templates() -> Result<Vec<TemplateInfo>, String> {
+    Ok(launch_config::python_templates())
+}
+
+#[tauri::command]
+async fn list_processes(filter: Option<String>) -> Result<Vec<ProcessEntry>, String> {
+    Ok(processes::list_processes(filter.as_deref()))
+}
+
+#[tauri::command]
+async fn get_sampled_stacks(
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<SampledStacks, String> {
+    let debug_state = sessions.active();
+    let stacks = debug_state
+        .sampler
+        .lock()
+        .as_ref()
+        .map(|sampler| sampler.stacks());
+    stacks.ok_or_else(|| "py-spy sampling is not active for this session".to_string())
+}
+
+#[tauri::command]
+async fn get_settings(settings: tauri::State<'_, Arc<SettingsStore>>) -> Result<Settings, String> {
+    Ok(settings.get())
+}
+
+#[tauri::command]
+async fn save_settings(
+    new_settings: Settings,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+    adapter_pool: tauri::State<'_, Arc<AdapterPool>>,
+) -> Result<(), String> {
+    adapters::custom::validate(&new_settings.custom_adapters)?;
+    let lldb_dap_changed = new_settings.lldb_dap_path != settings.get().lldb_dap_path;
+    settings.set(new_settings)?;
+    // The warm adapter was started from the old lldb-dap
+    if lldb_dap_changed {
+        adapter_pool.recycle(find_lldb_dap(&settings.get()).ok());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn detect_adapters(
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+) -> Result<Vec<AdapterDetection>, String> {
+    Ok(adapters::discovery::detect_adapters(&settings.get()))
+}
+
+// Setup checklist: presence, version and a smoke `initialize` handshake for every adapter.
+#[tauri::command]
+async fn run_diagnostics(
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+    workspace: tauri::State<'_, Arc<Workspace>>,
+) -> Result<Vec<AdapterCheck>, String> {
+    let settings = settings.get();
+    // Without an interpreter, the debugpy row reports python3 as not running
+    let python = interpreter::resolve(workspace.root().as_deref(), &settings)
+        .unwrap_or_else(|_| "python3".to_string());
+    Ok(adapters::doctor::run_diagnostics(&settings, &python))
+}
+
+// Aborts the session's launch in progress and kills whatever it spawned. Returns false when
+// nothing was launching.
+#[tauri::command]
+async fn cancel_launch(
+    session: Option<String>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+    app_handle: tauri::AppHandle,
+) -> Result<bool, String> {
+    let debug_state = match &session {
+        Some(token) => sessions
+            .get(token)
+            .ok_or_else(|| format!("No debug session named '{}'", token))?,
+        None => sessions.active(),
+    };
+    let Some(cancel) = debug_state.launch_cancel.lock().clone() else {
+        return Ok(false);
+    };
+    cancel.cancel();
+    info!("Cancelling launch of session {}", debug_state.token);
+
+    debug_state.client.lock().await.take();
+    if let Some(mut child) = debug_state.process.lock().await.take() {
+        processes::kill_tree(&mut child);
     }
+    for mut helper in debug_state.helper_processes.lock().await.drain(..) {
+        processes::kill_tree(&mut helper);
+    }
+    debug_state.sampler.lock().take();
+    debug_state.rr_trace.lock().take();
+    debug_state.pty.lock().take();
+    debug_state
+        .timeline
+        .write()
+        .record("launchCancelled", Value::Null);
+
+    let _ = app_handle.emit(
+        "launch-progress",
+        json!({ "session": debug_state.token, "stage": "cancelled", "detail": "" }),
+    );
+    emit_status_update_with_extra(
+        &app_handle,
+        &debug_state.status_seq,
+        "terminated",
+        None,
+        None,
+        None,
+        debug_state.status_extra(),
+    )?;
+    Ok(true)
 }
 
+// Ends the active session. Stop terminates the debuggee through the adapter, Detach leaves it
+// running, and ForceKill kills everything without waiting on the adapter.
 #[tauri::command]
-async fn terminate_program(
-    debug_state: tauri::State<'_, Arc<DebugSessionState>>,
+async fn stop_session(
+    mode: Option<StopMode>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
     app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    let debug_state = sessions.active();
+    let mode = mode.unwrap_or_default();
+    match mode {
+        StopMode::Stop => terminate_session(&app_handle, &debug_state).await,
+        StopMode::Detach => disconnect_session(&app_handle, &debug_state, false, false).await,
+        StopMode::ForceKill => force_kill_session(&app_handle, &debug_state).await,
+    }
+}
+
+async fn terminate_session(
+    app_handle: &tauri::AppHandle,
+    debug_state: &DebugSessionState,
 ) -> Result<String, String> {
     let debugger_type = {
         let dt = debug_state.debugger_type.read();
         dt.clone()
     };
+    debug_state
+        .timeline
+        .write()
+        .record("terminateRequested", Value::Null);
 
     if let Some(client) = debug_state.client.lock().await.as_ref() {
-        if debugger_type.as_deref() == Some("rust") {
-            println!("Rust debug termination: fire and forget");
+        // Without a detected adapter, fall back to assuming every lldb-dap needs it
+        let no_terminated_event = match debug_state.adapter_version.read().as_ref() {
+            Some(version) => version.quirks.no_terminated_event,
+            None => matches!(debugger_type.as_deref(), Some("rust") | Some("rr")),
+        };
+        if no_terminated_event {
+            debug!("Adapter doesn't send terminated: fire and forget");
 
-            // We manually emit a "terminated" status update since lldb-DAP exits without emitting one
-            // It's emitted first rather than waiting for client.terminate() to complete
-            emit_status_update(
-                &app_handle,
+            // We manually emit a "terminated" status update since this lldb-dap exits without
+            // emitting one. It's emitted first rather than waiting for client.terminate() to complete
+            emit_status_update_with_extra(
+                app_handle,
                 &debug_state.status_seq,
                 "terminated",
                 None,
                 None,
                 None,
+                debug_state.status_extra(),
             )?;
             let _ = client.terminate().await;
         } else {
             match client.terminate().await {
                 Ok(_) => {
-                    println!("Terminate request sent successfully");
+                    debug!("Terminate request sent successfully");
                 }
                 Err(e) => {
                     let error_str = e.to_string();
-                    println!("Error sending terminate request: {}", error_str);
-                    emit_status_update(
-                        &app_handle,
+                    warn!("Error sending terminate request: {}", error_str);
+                    emit_status_update_with_extra(
+                        app_handle,
                         &debug_state.status_seq,
                         "terminated",
                         None,
                         None,
                         None,
+                        debug_state.status_extra(),
                     )?;
                 }
             }
         }
     } else {
-        emit_status_update(
-            &app_handle,
+        emit_status_update_with_extra(
+            app_handle,
             &debug_state.status_seq,
             "terminated",
             None,
             None,
             None,
+            debug_state.status_extra(),
         )?;
     }
 
+    // Before the kill below, so a program that already exited reports its own status
+    emit_session_summary(app_handle, debug_state, "terminated");
+
     let mut proc_lock = debug_state.process.lock().await;
     if let Some(child) = proc_lock.as_mut() {
-        let _ = child.kill();
+        processes::kill_tree(child);
     }
     *proc_lock = None;
 
+    for mut helper in debug_state.helper_processes.lock().await.drain(..) {
+        processes::kill_tree(&mut helper);
+    }
+    debug_state.sampler.lock().take();
+    debug_state.rr_trace.lock().take();
+    debug_state.pty.lock().take();
+
     Ok("Debug session terminated".into())
 }
 
+// Ends the active session with a disconnect request, saying exactly what happens to the
+// debuggee: `terminate_debuggee` ends it, otherwise it runs on without the debugger, or stays
+// suspended with `suspend_debuggee` where the adapter supports that. By default an attached
+// program is left running.
+#[tauri::command]
+async fn disconnect(
+    terminate_debuggee: Option<bool>,
+    suspend_debuggee: Option<bool>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    let debug_state = sessions.active();
+    disconnect_session(
+        &app_handle,
+        &debug_state,
+        terminate_debuggee.unwrap_or(false),
+        suspend_debuggee.unwrap_or(false),
+    )
+    .await
+}
+
+// With terminateDebuggee off, debugpy and lldb-dap both resume the program (unless asked to
+// suspend it) and let it run on without the debugger.
+async fn disconnect_session(
+    app_handle: &tauri::AppHandle,
+    debug_state: &DebugSessionState,
+    terminate_debuggee: bool,
+    suspend_debuggee: bool,
+) -> Result<String, String> {
+    let debugger_type = {
+        let dt = debug_state.debugger_type.read();
+        dt.clone()
+    };
+    if !terminate_debuggee && debugger_type.as_deref() == Some("rr") {
+        return Err("An rr replay can't run on without the debugger; stop it instead".into());
+    }
+
+    if terminate_debuggee {
+        debug_state
+            .timeline
+            .write()
+            .record("terminateRequested", json!({ "disconnect": true }));
+    }
+    {
+        let client_lock = debug_state.client.lock().await;
+        let client = client_lock.as_ref().ok_or("No active debug session")?;
+        let resp = client
+            .disconnect_with(terminate_debuggee, suspend_debuggee)
+            .await
+            .map_err(|e| format!("Failed to disconnect: {}", e))?;
+        if resp.success == Some(false) {
+            return Err(format!(
+                "Adapter refused to disconnect: {}",
+                resp.message.unwrap_or_default()
+            ));
+        }
+    }
+    debug_state.client.lock().await.take();
+
+    if terminate_debuggee {
+        let mut extra = debug_state.status_extra();
+        extra.insert("disconnected".to_string(), json!(true));
+        emit_status_update_with_extra(
+            app_handle,
+            &debug_state.status_seq,
+            "terminated",
+            None,
+            None,
+            None,
+            extra,
+        )?;
+        emit_session_summary(app_handle, debug_state, "terminated");
+        if let Some(mut child) = debug_state.process.lock().await.take() {
+            processes::kill_tree(&mut child);
+        }
+        for mut helper in debug_state.helper_processes.lock().await.drain(..) {
+            processes::kill_tree(&mut helper);
+        }
+        debug_state.sampler.lock().take();
+        debug_state.rr_trace.lock().take();
+        debug_state.pty.lock().take();
+        return Ok("Disconnected and terminated the debuggee".into());
+    }
+
+    debug_state
+        .timeline
+        .write()
+        .record("detached", json!({ "suspended": suspend_debuggee }));
+
+    // Under debugpy the session's process is the program itself, so it's let go rather than
+    // killed. lldb-dap's debuggee is its own process and outlives the adapter.
+    let process = debug_state.process.lock().await.take();
+    if debugger_type.as_deref() != Some("python") {
+        if let Some(mut adapter) = process {
+            let _ = adapter.kill();
+        }
+    }
+    for mut helper in debug_state.helper_processes.lock().await.drain(..) {
+        processes::kill_tree(&mut helper);
+    }
+    debug_state.sampler.lock().take();
+    debug_state.rr_trace.lock().take();
+
+    let mut extra = debug_state.status_extra();
+    extra.insert("detached".to_string(), json!(true));
+    extra.insert("suspended".to_string(), json!(suspend_debuggee));
+    emit_status_update_with_extra(
+        app_handle,
+        &debug_state.status_seq,
+        "terminated",
+        None,
+        None,
+        None,
+        extra,
+    )?;
+    emit_session_summary(app_handle, debug_state, "detached");
+    Ok("Detached from the debuggee".into())
+}
+
+// For sessions where the adapter no longer responds: nothing is sent to it. The debuggee is
+// killed by the pid from its "process" event, then the adapter and helpers.
+async fn force_kill_session(
+    app_handle: &tauri::AppHandle,
+    debug_state: &DebugSessionState,
+) -> Result<String, String> {
+    let debuggee_pid = debug_state
+        .debuggee_process
+        .read()
+        .as_ref()
+        .and_then(|process| process.system_process_id);
+    if let Some(pid) = debuggee_pid.and_then(|pid| u32::try_from(pid).ok()) {
+        if !processes::kill_process(pid) {
+            debug!("Debuggee {} was already gone", pid);
+        }
+    }
+
+    debug_state.client.lock().await.take();
+    if let Some(mut child) = debug_state.process.lock().await.take() {
+        processes::kill_tree(&mut child);
+    }
+    for mut helper in debug_state.helper_processes.lock().await.drain(..) {
+        processes::kill_tree(&mut helper);
+    }
+    debug_state.sampler.lock().take();
+    debug_state.rr_trace.lock().take();
+    debug_state.pty.lock().take();
+    debug_state.timeline.write().record("killed", Value::Null);
+
+    emit_status_update_with_extra(
+        app_handle,
+        &debug_state.status_seq,
+        "terminated",
+        None,
+        None,
+        None,
+        debug_state.status_extra(),
+    )?;
+    emit_session_summary(app_handle, debug_state, "killed");
+    Ok("Debug session killed".into())
+}
+
+#[tauri::command]
+async fn set_watch_expressions(
+    expressions: Vec<String>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<(), String> {
+    let debug_state = sessions.active();
+    *debug_state.watch_expressions.write() = expressions;
+    Ok(())
+}
+
+// Breaks when `expression` changes value, for adapters without data breakpoints: it's
+// re-evaluated at every stop and unchanged stops are resumed. With `step`, execution advances
+// line by line between checks. When paused, the current value becomes the baseline.
+#[tauri::command]
+async fn add_value_watch(
+    expression: String,
+    step: Option<bool>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<Vec<ValueWatch>, String> {
+    let debug_state = sessions.active();
+    {
+        let mut watches = debug_state.value_watches.write();
+        watches.retain(|w| w.expression != expression);
+        watches.push(ValueWatch {
+            expression: expression.clone(),
+            step: step.unwrap_or(false),
+            last_value: None,
+        });
+    }
+
+    let thread_id = match *debug_state.state.read() {
+        DebuggerState::Paused { thread_id, .. } => Some(thread_id),
+        _ => None,
+    };
+    if let Some(thread_id) = thread_id {
+        let client_lock = debug_state.client.lock().await;
+        if let Some(dap_client) = client_lock.as_ref() {
+            check_value_watches(dap_client, &debug_state, thread_id).await;
+        }
+    }
+    debug_state
+        .timeline
+        .write()
+        .record("valueWatchAdded", json!({ "expression": expression }));
+    let watches = debug_state.value_watches.read().clone();
+    Ok(watches)
+}
+
+#[tauri::command]
+async fn remove_value_watch(
+    expression: String,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<Vec<ValueWatch>, String> {
+    let debug_state = sessions.active();
+    debug_state
+        .value_watches
+        .write()
+        .retain(|w| w.expression != expression);
+    debug_state
+        .timeline
+        .write()
+        .record("valueWatchRemoved", json!({ "expression": expression }));
+    let watches = debug_state.value_watches.read().clone();
+    Ok(watches)
+}
+
+// How the active session's latest launch ended, once it has.
+#[tauri::command]
+async fn get_last_session_summary(
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<Option<SessionSummary>, String> {
+    let debug_state = sessions.active();
+    let summary = debug_state.last_summary.read().clone();
+    Ok(summary)
+}
+
+// Ended sessions from the history, newest first.
+#[tauri::command]
+async fn list_past_sessions(
+    limit: Option<usize>,
+    session_history: tauri::State<'_, Arc<SessionHistoryStore>>,
+) -> Result<Vec<PastSession>, String> {
+    session_history.list(limit)
+}
+
+// One past session with the breakpoints it stopped at and the launch to repeat it with.
+#[tauri::command]
+async fn get_session_details(
+    id: i64,
+    session_history: tauri::State<'_, Arc<SessionHistoryStore>>,
+) -> Result<PastSessionDetails, String> {
+    session_history
+        .details(id)?
+        .ok_or_else(|| format!("No past session with id {}", id))
+}
+
+// Snapshots of the session's latest stops, oldest first.
+#[tauri::command]
+async fn get_stop_history(
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<Vec<StopSnapshot>, String> {
+    let debug_state = sessions.active();
+    let stops = debug_state.stop_history.read().list();
+    Ok(stops)
+}
+
+// Where the program was `n` stops ago (0 is the current stop). Read-only: the program stays
+// where it is.
+#[tauri::command]
+async fn view_stop(
+    n: usize,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<StopSnapshot, String> {
+    let debug_state = sessions.active();
+    let stop = debug_state.stop_history.read().get(n);
+    stop.ok_or_else(|| format!("No stop recorded {} stops back", n))
+}
+
+#[tauri::command]
+async fn select_frame(
+    frame: Option<FrameSelection>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<(), String> {
+    let debug_state = sessions.active();
+    *debug_state.selected_frame.write() = frame;
+    Ok(())
+}
+
+// What the frontend needs to rebuild its views after hot_restart.
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RestartContext {
+    // "restart" when the running adapter restarted the debuggee, "relaunch" otherwise
+    method: String,
+    breakpoints: BTreeMap<String, Vec<BreakpointInput>>,
+    watch_expressions: Vec<String>,
+    console_history: Vec<String>,
+    selected_frame: Option<FrameSelection>,
+}
+
+// Re-sends the stored breakpoints for every file to the active adapter.
+async fn apply_stored_breakpoints(debug_state: &DebugSessionState) -> Result<(), String> {
+    let client_lock = debug_state.client.lock().await;
+    let dap_client = client_lock.as_ref().ok_or("No active debug session")?;
+    send_stored_breakpoints(debug_state, dap_client).await
+}
+
+async fn send_stored_breakpoints(
+    debug_state: &DebugSessionState,
+    dap_client: &DAPClient,
+) -> Result<(), String> {
+    let mut files: Vec<String> = debug_state
+        .breakpoints
+        .read()
+        .snapshot()
+        .into_keys()
+        .collect();
+    // The entry breakpoint goes out this once; later updates of its file carry only the user's
+    // breakpoints, and a reattached program is already past it
+    let entry = debug_state.entry_line.write().take();
+    if let Some((file, _)) = &entry {
+        if !files.contains(file) {
+            files.push(file.clone());
+        }
+    }
+    for file_path in files {
+        let entry_line = entry
+            .as_ref()
+            .filter(|(file, _)| *file == file_path)
+            .map(|(_, line)| *line);
+        let response = breakpoints::send_with_entry(
+            dap_client,
+            &debug_state.breakpoints,
+            &file_path,
+            entry_line,
+        )
+        .await
+        .map_err(|e| format!("Failed to restore breakpoints in {}: {}", file_path, e))?;
+        if let Some(body) = response.body {
+            debug_state
+                .breakpoints
+                .write()
+                .record_adapter_ids(&file_path, &body);
+        }
+    }
+    Ok(())
+}
+
+// Follows a file or directory rename reported by the file watcher. Breakpoints move to the new
+// path; in a running session the old path's breakpoints are cleared and re-sent under the new
+// one. Returns the moved breakpoints by their new path.
+#[tauri::command]
+async fn rename_breakpoint_path(
+    old_path: String,
+    new_path: String,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<BTreeMap<String, Vec<BreakpointInput>>, String> {
+    let debug_state = sessions.active();
+    let moves = debug_state.breakpoints.write().rename(&old_path, &new_path);
+    if moves.is_empty() {
+        return Ok(BTreeMap::new());
+    }
+    debug_state.timeline.write().record(
+        "breakpointsMoved",
+        json!({ "from": old_path, "to": new_path }),
+    );
+
+    let client_lock = debug_state.client.lock().await;
+    if let Some(dap_client) = client_lock.as_ref() {
+        for (old, new) in &moves {
+            dap_client
+                .set_breakpoints(old.clone(), Vec::new())
+                .await
+                .map_err(|e| format!("Failed to clear breakpoints in {}: {}", old, e))?;
+            let response = breakpoints::send(dap_client, &debug_state.breakpoints, new)
+                .await
+                .map_err(|e| format!("Failed to set breakpoints in {}: {}", new, e))?;
+            if let Some(body) = response.body {
+                debug_state
+                    .breakpoints
+                    .write()
+                    .record_adapter_ids(new, &body);
+            }
+        }
+    }
+
+    let snapshot = debug_state.breakpoints.read().snapshot();
+    Ok(moves
+        .into_iter()
+        .filter_map(|(_, new)| snapshot.get(&new).map(|bps| (new, bps.clone())))
+        .collect())
+}
+
+#[tauri::command]
+async fn export_breakpoints(
+    path: String,
+    root: String,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+    workspace: tauri::State<'_, Arc<Workspace>>,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+) -> Result<usize, String> {
+    let debug_state = sessions.active();
+    let root = shellexpand::tilde(&root).into_owned();
+    let path = workspace.resolve(&path, &settings.get().allowed_paths)?;
+    let file = debug_state
+        .breakpoints
+        .read()
+        .export(std::path::Path::new(&root));
+    let json = serde_json::to_string_pretty(&file)
+        .map_err(|e| format!("Failed to serialize breakpoints: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(file.breakpoints.len())
+}
+
+#[tauri::command]
+async fn import_breakpoints(
+    path: String,
+    root: String,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+    workspace: tauri::State<'_, Arc<Workspace>>,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+) -> Result<BTreeMap<String, Vec<BreakpointInput>>, String> {
+    let debug_state = sessions.active();
+    let path = workspace.resolve(&path, &settings.get().allowed_paths)?;
+    let contents = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let file: BreakpointFile = serde_json::from_str(&contents)
+        .map_err(|e| format!("Invalid breakpoint file {}: {}", path.display(), e))?;
+    let root = shellexpand::tilde(&root).into_owned();
+    let imported = debug_state
+        .breakpoints
+        .write()
+        .import(file, std::path::Path::new(&root))?;
+
+    // Apply right away if a session is running
+    if debug_state.client.lock().await.is_some() {
+        apply_stored_breakpoints(&debug_state).await?;
+    }
+    Ok(imported)
+}
+
+#[tauri::command]
+async fn hot_restart(
+    app_handle: tauri::AppHandle,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+    workspace: tauri::State<'_, Arc<Workspace>>,
+    adapter_pool: tauri::State<'_, Arc<AdapterPool>>,
+) -> Result<RestartContext, String> {
+    let debug_state = sessions.active();
+    let last_launch = debug_state
+        .last_launch
+        .read()
+        .clone()
+        .ok_or("Nothing to restart; no session has been launched")?;
+
+    // lldb-dap can restart a launched debuggee in place, which skips respawning the adapter
+    // and keeps its loaded symbols. rr replays and debugpy always go through a full relaunch.
+    let mut restarted = false;
+    if debug_state.debugger_type.read().as_deref() == Some("rust") {
+        let client_lock = debug_state.client.lock().await;
+        if let Some(dap_client) = client_lock.as_ref() {
+            match dap_client.restart().await {
+                Ok(response) if response.success != Some(false) => restarted = true,
+                Ok(response) => warn!("Adapter refused restart: {:?}", response.message),
+                Err(e) => warn!("Restart request failed: {}", e),
+            }
+        }
+    }
+
+    if restarted {
+        apply_stored_breakpoints(&debug_state).await?;
+    } else {
+        terminate_session(&app_handle, &debug_state).await?;
+        launch_debug_session(
+            app_handle,
+            last_launch.script_path,
+            last_launch.debug_engine,
+            Some(last_launch.launch_config),
+            Some(debug_state.token.clone()),
+            // Already vetted when the session was first launched
+            Some(true),
+            None,
+            sessions.clone(),
+            settings,
+            workspace,
+            adapter_pool,
+        )
+        .await?;
+    }
+
+    let context = RestartContext {
+        method: if restarted { "restart" } else { "relaunch" }.to_string(),
+        breakpoints: debug_state.breakpoints.read().snapshot(),
+        watch_expressions: debug_state.watch_expressions.read().clone(),
+        console_history: debug_state.console_history.read().clone(),
+        selected_frame: debug_state.selected_frame.read().clone(),
+    };
+    Ok(context)
+}
+
+#[tauri::command]
+async fn export_session_timeline(
+    path: String,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+    workspace: tauri::State<'_, Arc<Workspace>>,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+) -> Result<String, String> {
+    let debug_state = sessions.active();
+    let path = workspace.resolve(&path, &settings.get().allowed_paths)?;
+    let timeline = debug_state.timeline.read().clone();
+    let json = serde_json::to_string_pretty(&timeline)
+        .map_err(|e| format!("Failed to serialize timeline: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+// Writes a Markdown or HTML summary of the active session for bug reports: launch config,
+// breakpoints hit, the current stack, evaluations (all, or only `expressions`) and captured
+// program output. Returns the written path.
+#[tauri::command]
+async fn export_session_report(
+    path: String,
+    format: Option<String>,
+    expressions: Option<Vec<String>>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+    workspace: tauri::State<'_, Arc<Workspace>>,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+) -> Result<String, String> {
+    let debug_state = sessions.active();
+    let format = ReportFormat::resolve(format.as_deref(), &path)?;
+    let path = workspace.resolve(&path, &settings.get().allowed_paths)?;
+
+    let mut report = SessionReport {
+        token: debug_state.token.clone(),
+        state: debug_state.state.read().as_str().to_string(),
+        launch: debug_state.last_launch.read().clone(),
+        breakpoint_hits: BTreeMap::new(),
+        stack: Vec::new(),
+        evaluations: Vec::new(),
+        output: debug_state.program_output.lock().iter().cloned().collect(),
+    };
+    report.add_timeline(&debug_state.timeline.read(), expressions.as_deref());
+
+    // The stack is only meaningful while paused
+    let paused_thread = match &*debug_state.state.read() {
+        DebuggerState::Paused { thread_id, .. } => Some(*thread_id),
+        _ => None,
+    };
+    if let Some(thread_id) = paused_thread {
+        let client_lock = debug_state.client.lock().await;
+        if let Some(dap_client) = client_lock.as_ref() {
+            if let Ok(response) = dap_client.stack_trace(thread_id).await {
+                if let Ok(body) = protocol::body::<StackTraceResponseBody>(&response) {
+                    report.stack = body
+                        .stack_frames
+                        .iter()
+                        .map(|f| ReportFrame {
+                            name: f.display_name(),
+                            file: f.path().map(String::from),
+                            line: f.line,
+                        })
+                        .collect();
+                }
+            }
+        }
+    }
+
+    fs::write(&path, report.render(format))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+async fn get_dap_metrics(
+    reset: Option<bool>,
+    sessions: tauri::State<'_, Arc<SessionManager>>,
+) -> Result<DapMetricsSnapshot, String> {
+    let debug_state = sessions.active();
+    let snapshot = debug_state.dap_metrics.snapshot();
+    if reset.unwrap_or(false) {
+        debug_state.dap_metrics.reset();
+    }
+    Ok(snapshot)
+}
+
+// Changes the log filter at runtime, e.g. "debug" or "info,dap_client=trace" for full DAP
+// payloads. Returns the filter now in effect.
+#[tauri::command]
+async fn set_log_level(
+    level: String,
+    logging: tauri::State<'_, Arc<Logging>>,
+) -> Result<String, String> {
+    logging.set_filter(&level)?;
+    Ok(logging.filter())
+}
+
+#[tauri::command]
+async fn get_recent_logs(
+    n: Option<usize>,
+    logging: tauri::State<'_, Arc<Logging>>,
+) -> Result<Vec<String>, String> {
+    Ok(logging.recent(n.unwrap_or(200)))
+}
+
 fn main() {
-    let debug_session_state = Arc::new(DebugSessionState::new());
+    let session_manager = Arc::new(SessionManager::new());
 
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
-        .manage(debug_session_state)
+        .manage(session_manager)
+        .manage(Arc::new(Workspace::new()))
+        .manage(Arc::new(RendererRegistry::new()))
+        .setup(|app| {
+            let log_dir = app.path().app_log_dir().ok();
+            app.manage(Arc::new(logging::init(log_dir.as_deref())));
+
+            let settings_path = app
+                .path()
+                .app_config_dir()
+                .ok()
+                .map(|dir| dir.join("settings.json"));
+            let settings = SettingsStore::load(settings_path);
+
+            // Start an lldb-dap ahead of the first Rust launch
+            let adapter_pool = Arc::new(AdapterPool::default());
+            if let Ok(lldb_dap_path) = find_lldb_dap(&settings.get()) {
+                adapter_pool.recycle(Some(lldb_dap_path));
+            }
+            app.manage(adapter_pool);
+            app.manage(Arc::new(settings));
+
+            let history_dir = app
+                .path()
+                .app_data_dir()
+                .ok()
+                .map(|dir| dir.join("console_history"));
+            app.manage(Arc::new(ConsoleHistoryStore::new(history_dir)));
+
+            let session_db = app
+                .path()
+                .app_data_dir()
+                .ok()
+                .map(|dir| dir.join("sessions.db"));
+            app.manage(Arc::new(SessionHistoryStore::open(session_db)));
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
+            open_workspace,
             read_directory,
             launch_debug_session,
             resolve_breakpoint_by_search,
             set_breakpoint,
-            configuration_done,
+            source_saved,
+            set_break_on_panic,
+            get_capabilities,
+            get_exception_filters,
+            set_exception_filters,
             continue_debug,
+            pause_debug,
+            goto_line,
+            restart_frame,
             step_in,
             step_over,
             step_out,
+            get_step_in_targets,
+            step_in_target,
+            freeze_thread,
+            thaw_thread,
+            set_signal_handling,
+            step_back,
+            reverse_continue,
             evaluate_expression,
+            list_in_flight_requests,
+            cancel_request,
+            evaluate_selection,
+            list_snippets,
+            run_snippet,
+            export_variable,
+            set_format_limits,
+            get_scopes,
+            get_variables,
+            fetch_full_value,
+            get_renderers,
+            reload_renderers,
             get_call_stack,
-            terminate_program,
+            get_all_backtraces,
+            get_disassembly,
+            read_memory,
+            get_registers,
+            get_inline_values,
+            search_symbols,
+            get_debuggee_environment,
+            get_data_preview,
+            get_session_info,
+            get_threads,
+            launch_compound,
+            set_active_session,
+            list_sessions,
+            get_sampled_stacks,
+            list_processes,
+            list_launch_templates,
+            get_workspace_layout,
+            index_debug_targets,
+            check_python_env,
+            set_python_interpreter,
+            get_settings,
+            save_settings,
+            detect_adapters,
+            run_diagnostics,
+            stop_session,
+            disconnect,
+            get_console_history,
+            write_pty,
+            resize_pty,
+            clear_console_history,
+            add_value_watch,
+            remove_value_watch,
+            get_adapter_source,
+            get_source,
+            get_loaded_sources,
+            set_source_reference_breakpoints,
+            cancel_launch,
+            hot_restart,
+            rename_breakpoint_path,
+            export_breakpoints,
+            import_breakpoints,
+            set_watch_expressions,
+            select_frame,
+            get_last_session_summary,
+            list_past_sessions,
+            get_session_details,
+            get_stop_history,
+            view_stop,
+            export_session_timeline,
+            export_session_report,
+            get_dap_metrics,
+            set_log_level,
+            get_recent_logs,
             read_file_content,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            // Don't leave the idle adapter running after the app is gone
+            if let tauri::RunEvent::Exit = event {
+                app_handle.state::<Arc<AdapterPool>>().recycle(None);
+            }
+        });
 }
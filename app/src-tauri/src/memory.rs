@@ -0,0 +1,102 @@
+use base64::Engine;
+use serde::Serialize;
+use serde_json::Value;
+
+// Bytes per hex dump row
+const ROW_BYTES: usize = 16;
+// Upper bound on one read, so a typo in the count can't stall the adapter
+pub const MAX_READ_BYTES: u32 = 64 * 1024;
+
+// One row of the hex view: address, bytes in hex and the same bytes as ASCII, e.g.
+// "0x00007ffd5c3e1a40", "48 65 6c 6c 6f 00 ...", "Hello...".
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HexRow {
+    pub address: String,
+    pub hex: String,
+    pub ascii: String,
+}
+
+// The result of a readMemory request, decoded.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryBlock {
+    // Address of the first byte, as the adapter reported it
+    pub address: String,
+    pub bytes: Vec<u8>,
+    // Bytes after the readable ones the adapter couldn't read (e.g. an unmapped page)
+    pub unreadable_bytes: u64,
+    pub rows: Vec<HexRow>,
+}
+
+impl MemoryBlock {
+    // Decodes a readMemory response body.
+    pub fn from_response(body: &Value) -> Result<Self, String> {
+        let address = body
+            .get("address")
+            .and_then(|a| a.as_str())
+            .ok_or("No address in readMemory response")?
+            .to_string();
+        // No data means nothing at the address was readable
+        let bytes = match body.get("data").and_then(|d| d.as_str()) {
+            Some(data) => base64::engine::general_purpose::STANDARD
+                .decode(data)
+                .map_err(|e| format!("Malformed memory data: {}", e))?,
+            None => Vec::new(),
+        };
+        let unreadable_bytes = body
+            .get("unreadableBytes")
+            .and_then(|u| u.as_u64())
+            .unwrap_or(0);
+        let rows = hex_rows(&address, &bytes);
+        Ok(MemoryBlock {
+            address,
+            bytes,
+            unreadable_bytes,
+            rows,
+        })
+    }
+}
+
+fn parse_address(address: &str) -> Option<u64> {
+    let digits = address
+        .strip_prefix("0x")
+        .or_else(|| address.strip_prefix("0X"))?;
+    u64::from_str_radix(digits, 16).ok()
+}
+
+// Rows are addressed absolutely when the start address is numeric, otherwise by offset from it.
+fn hex_rows(address: &str, bytes: &[u8]) -> Vec<HexRow> {
+    let start = parse_address(address);
+    bytes
+        .chunks(ROW_BYTES)
+        .enumerate()
+        .map(|(row, chunk)| {
+            let offset = (row * ROW_BYTES) as u64;
+            let address = match start {
+                Some(start) => format!("0x{:016x}", start.wrapping_add(offset)),
+                None => format!("{}+0x{:x}", address, offset),
+            };
+            let hex = chunk
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let ascii = chunk
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            HexRow {
+                address,
+                hex,
+                ascii,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,163 @@
+use object::read::ReadCache;
+use object::Object;
+use serde::Serialize;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+// Checks whether a native binary carries debug info we can set breakpoints against. Only the
+// headers, section table and (for Mach-O) symbol table are read, not the whole binary:
+// - ELF and packed Mach-O binaries have DWARF sections (.debug_info / __debug_info)
+// - macOS split debug info lives in a sibling .dSYM bundle
+// - cargo's default on macOS (split-debuginfo=unpacked) leaves DWARF in the codegen-unit object
+//   files, which the binary's debug map references
+// - ELF debug info split off with objcopy is named by .gnu_debuglink
+// - Windows keeps it in a sibling .pdb
+pub fn has_debug_info(binary: &Path) -> io::Result<bool> {
+    let dsym = binary.with_file_name(format!(
+        "{}.dSYM",
+        binary
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
+    ));
+    if dsym.is_dir() || binary.with_extension("pdb").is_file() {
+        return Ok(true);
+    }
+
+    let data = ReadCache::new(File::open(binary)?);
+    let file =
+        object::File::parse(&data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    if file.has_debug_symbols() || !file.object_map().objects().is_empty() {
+        return Ok(true);
+    }
+
+    // Where gdb looks for the debuglink file: beside the binary or in a .debug directory there
+    if let Ok(Some((name, _))) = file.gnu_debuglink() {
+        let name = String::from_utf8_lossy(name).to_string();
+        let dir = binary.parent().unwrap_or(Path::new("."));
+        return Ok(dir.join(&name).is_file() || dir.join(".debug").join(&name).is_file());
+    }
+    Ok(false)
+}
+
+// Verifies that `interpreter` can import debugpy, returning the import error otherwise.
+pub fn check_debugpy(interpreter: &str) -> Result<(), String> {
+    let output = Command::new(interpreter)
+        .args(["-c", "import debugpy"])
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", interpreter, e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+// Oldest debugpy release the Python launch is known to work with.
+const MIN_DEBUGPY_VERSION: &str = "1.6.0";
+
+// What check_python_env found out about an interpreter.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PythonEnvReport {
+    pub interpreter: String,
+    // The interpreter could be started at all
+    pub runs: bool,
+    pub python_version: Option<String>,
+    // None when debugpy isn't importable
+    pub debugpy_version: Option<String>,
+    pub min_debugpy_version: &'static str,
+    pub debugpy_compatible: bool,
+    // debugpy was (re)installed by this check
+    pub installed: bool,
+    // Why a step failed, e.g. the interpreter's or pip's error output
+    pub error: Option<String>,
+}
+
+// Checks that `interpreter` runs and has a compatible debugpy. With `install`, a missing or
+// outdated debugpy is installed with pip into that interpreter's environment first.
+pub fn check_python_env(interpreter: &str, install: bool) -> PythonEnvReport {
+    let mut report = PythonEnvReport {
+        interpreter: interpreter.to_string(),
+        runs: false,
+        python_version: None,
+        debugpy_version: None,
+        min_debugpy_version: MIN_DEBUGPY_VERSION,
+        debugpy_compatible: false,
+        installed: false,
+        error: None,
+    };
+
+    match run_python(
+        interpreter,
+        &["-c", "import platform; print(platform.python_version())"],
+    ) {
+        Ok(version) => {
+            report.runs = true;
+            report.python_version = Some(version);
+        }
+        Err(e) => {
+            report.error = Some(e);
+            return report;
+        }
+    }
+
+    report.debugpy_version = debugpy_version(interpreter);
+    report.debugpy_compatible = is_compatible(report.debugpy_version.as_deref());
+
+    if install && !report.debugpy_compatible {
+        let requirement = format!("debugpy>={}", MIN_DEBUGPY_VERSION);
+        match run_python(
+            interpreter,
+            &["-m", "pip", "install", "--upgrade", &requirement],
+        ) {
+            Ok(_) => {
+                report.installed = true;
+                report.debugpy_version = debugpy_version(interpreter);
+                report.debugpy_compatible = is_compatible(report.debugpy_version.as_deref());
+            }
+            Err(e) => report.error = Some(e),
+        }
+    }
+    report
+}
+
+pub fn debugpy_version(interpreter: &str) -> Option<String> {
+    run_python(
+        interpreter,
+        &["-c", "import debugpy; print(debugpy.__version__)"],
+    )
+    .ok()
+}
+
+fn is_compatible(version: Option<&str>) -> bool {
+    version.is_some_and(|v| version_parts(v) >= version_parts(MIN_DEBUGPY_VERSION))
+}
+
+// Leading numeric components, so "1.8.0rc1" compares as 1.8.0.
+fn version_parts(version: &str) -> Vec<u32> {
+    version
+        .split('.')
+        .map_while(|part| {
+            let digits: String = part.chars().take_while(|c| c.is_ascii_digit()).collect();
+            digits.parse().ok()
+        })
+        .collect()
+}
+
+// Runs the interpreter and returns its trimmed stdout, or its stderr on failure.
+fn run_python(interpreter: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(interpreter)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", interpreter, e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
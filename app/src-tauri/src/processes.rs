@@ -0,0 +1,189 @@
+use serde::Serialize;
+use std::io;
+use std::process::{Child, Command, ExitStatus};
+use std::thread;
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, Users};
+
+// A running process, as shown in the attach picker.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessEntry {
+    pub pid: u32,
+    pub name: String,
+    pub cmdline: String,
+    pub user: Option<String>,
+}
+
+// Lists running processes, optionally keeping only those whose name, command line or pid
+// contains `filter` (case-insensitive).
+pub fn list_processes(filter: Option<&str>) -> Vec<ProcessEntry> {
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::everything(),
+    );
+    let users = Users::new_with_refreshed_list();
+
+    let filter = filter
+        .map(|f| f.trim().to_lowercase())
+        .filter(|f| !f.is_empty());
+
+    let mut entries: Vec<ProcessEntry> = system
+        .processes()
+        .iter()
+        .map(|(pid, process)| ProcessEntry {
+            pid: pid.as_u32(),
+            name: process.name().to_string_lossy().to_string(),
+            cmdline: process
+                .cmd()
+                .iter()
+                .map(|arg| arg.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join(" "),
+            user: process
+                .user_id()
+                .and_then(|uid| users.get_user_by_id(uid))
+                .map(|user| user.name().to_string()),
+        })
+        .filter(|entry| match &filter {
+            Some(f) => {
+                entry.name.to_lowercase().contains(f)
+                    || entry.cmdline.to_lowercase().contains(f)
+                    || entry.pid.to_string().contains(f)
+            }
+            None => true,
+        })
+        .collect();
+
+    entries.sort_by(|a, b| {
+        a.name
+            .to_lowercase()
+            .cmp(&b.name.to_lowercase())
+            .then(a.pid.cmp(&b.pid))
+    });
+    entries
+}
+
+// Kills `pid` outright (SIGKILL on unix), with no chance to clean up. Returns false when no such
+// process is running.
+pub fn kill_process(pid: u32) -> bool {
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+    system.process(pid).is_some_and(|process| process.kill())
+}
+
+// Starts the child in its own process group (on unix) so kill_tree can also reach processes it
+// spawned that have since been reparented.
+pub fn own_process_group(command: &mut Command) -> &mut Command {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    command
+}
+
+// Kills `child` along with everything it spawned: worker processes of a Python script, or the
+// program lldb-dap launched. Descendants are found by parent pid, which also works on Windows;
+// on unix the child's process group (see own_process_group) is killed as well.
+pub fn kill_tree(child: &mut Child) {
+    let root = Pid::from_u32(child.id());
+    let mut system = System::new();
+    system.refresh_processes(ProcessesToUpdate::All, true);
+
+    // Collected before killing anything, since orphans are reparented right away
+    let mut tree = vec![root];
+    let mut next = 0;
+    while next < tree.len() {
+        let parent = tree[next];
+        tree.extend(
+            system
+                .processes()
+                .iter()
+                .filter(|(_, process)| process.parent() == Some(parent))
+                .map(|(pid, _)| *pid),
+        );
+        next += 1;
+    }
+
+    #[cfg(unix)]
+    {
+        let _ = Command::new("kill")
+            .args(["-KILL", "--", &format!("-{}", root)])
+            .stderr(std::process::Stdio::null())
+            .status();
+    }
+    for pid in tree {
+        if let Some(process) = system.process(pid) {
+            process.kill();
+        }
+    }
+    let _ = child.kill();
+}
+
+// How wait_killable's wait on a child ended.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Wait {
+    Exited(ExitStatus),
+    TimedOut,
+    Cancelled,
+}
+
+// Waits for `child` from a blocking thread. If it's still running after `timeout`, or once
+// `cancelled` returns true (e.g. the launch it belongs to was cancelled), it's killed along with
+// everything it spawned.
+pub fn wait_killable(
+    child: &mut Child,
+    timeout: Duration,
+    cancelled: impl Fn() -> bool,
+) -> io::Result<Wait> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Wait::Exited(status));
+        }
+        let outcome = if cancelled() {
+            Wait::Cancelled
+        } else if Instant::now() >= deadline {
+            Wait::TimedOut
+        } else {
+            thread::sleep(Duration::from_millis(100));
+            continue;
+        };
+        kill_tree(child);
+        return Ok(outcome);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn wait_killable_kills_a_cancelled_child() {
+        let mut child = own_process_group(&mut Command::new("sleep"))
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let started = Instant::now();
+        let wait = wait_killable(&mut child, Duration::from_secs(30), || true).unwrap();
+        assert_eq!(wait, Wait::Cancelled);
+        child.wait().unwrap();
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn wait_killable_times_out() {
+        let mut child = own_process_group(&mut Command::new("sleep"))
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let wait = wait_killable(&mut child, Duration::from_millis(200), || false).unwrap();
+        assert_eq!(wait, Wait::TimedOut);
+    }
+}
@@ -0,0 +1,173 @@
+use portable_pty::{native_pty_system, MasterPty, PtySize, SlavePty};
+use serde::Serialize;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Arc;
+use std::thread;
+use tauri::{AppHandle, Emitter};
+
+use crate::debug_state::DebugSessionState;
+use crate::debugger::client::emit_console_line;
+
+// Until the frontend terminal reports its size
+const DEFAULT_SIZE: PtySize = PtySize {
+    rows: 24,
+    cols: 80,
+    pixel_width: 0,
+    pixel_height: 0,
+};
+
+// A chunk of terminal output exactly as the program wrote it, escape sequences and all.
+#[derive(Serialize, Clone)]
+struct PtyOutput<'a> {
+    session: &'a str,
+    data: &'a [u8],
+}
+
+// The pseudo-terminal a Python debuggee runs in. The program sees a terminal on stdin, stdout
+// and stderr, so progress bars, input() and curses behave as they do in a shell. Raw output
+// goes out on "pty-output" for the frontend's terminal widget, which sends keystrokes back
+// with write_pty; complete lines are also recorded as console lines.
+pub struct PtyConsole {
+    master: Box<dyn MasterPty + Send>,
+    writer: Box<dyn Write + Send>,
+    // Held until the program is spawned: the terminal hangs up once no one has it open
+    slave: Option<Box<dyn SlavePty + Send>>,
+    tty: PathBuf,
+}
+
+impl PtyConsole {
+    pub fn open() -> Result<Self, String> {
+        let pair = native_pty_system()
+            .openpty(DEFAULT_SIZE)
+            .map_err(|e| format!("Failed to open a pseudo-terminal: {}", e))?;
+        let tty = terminal_path(&*pair.master)?;
+        let writer = pair
+            .master
+            .take_writer()
+            .map_err(|e| format!("Failed to open the terminal for input: {}", e))?;
+        Ok(PtyConsole {
+            master: pair.master,
+            writer,
+            slave: Some(pair.slave),
+            tty,
+        })
+    }
+
+    // Makes the terminal `command`'s stdin, stdout and stderr and its controlling terminal.
+    // The program gets a session of its own, which also makes it a process group leader as
+    // processes::kill_tree expects.
+    #[cfg(unix)]
+    pub fn attach(&self, command: &mut Command) -> std::io::Result<()> {
+        use std::os::unix::process::CommandExt;
+
+        let tty = || {
+            std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(&self.tty)
+        };
+        command.stdin(tty()?).stdout(tty()?).stderr(tty()?);
+        if std::env::var_os("TERM").is_none() {
+            command.env("TERM", "xterm-256color");
+        }
+        // Only async-signal-safe calls between fork and exec
+        unsafe {
+            command.pre_exec(|| {
+                if libc::setsid() == -1 || libc::ioctl(0, libc::TIOCSCTTY, 0) == -1 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    pub fn attach(&self, _command: &mut Command) -> std::io::Result<()> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "pseudo-terminals are only supported on unix",
+        ))
+    }
+
+    // Streams the program's output once it's running. Reading ends when the program and
+    // everything it started have closed the terminal.
+    pub fn start(&mut self, app_handle: &AppHandle, debug_state: &Arc<DebugSessionState>) {
+        self.slave.take();
+        let mut reader = match self.master.try_clone_reader() {
+            Ok(reader) => reader,
+            Err(e) => {
+                tracing::warn!("Failed to read from the program's terminal: {}", e);
+                return;
+            }
+        };
+        let app_handle = app_handle.clone();
+        let debug_state = Arc::clone(debug_state);
+        thread::spawn(move || {
+            let mut buf = [0u8; 4096];
+            let mut line = Vec::new();
+            loop {
+                let n = match reader.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => n,
+                };
+                let _ = app_handle.emit(
+                    "pty-output",
+                    PtyOutput {
+                        session: &debug_state.token,
+                        data: &buf[..n],
+                    },
+                );
+                for &byte in &buf[..n] {
+                    if byte == b'\n' {
+                        emit_line(&app_handle, &debug_state, &line);
+                        line.clear();
+                    } else {
+                        line.push(byte);
+                    }
+                }
+            }
+            if !line.is_empty() {
+                emit_line(&app_handle, &debug_state, &line);
+            }
+        });
+    }
+
+    pub fn write(&mut self, data: &[u8]) -> Result<(), String> {
+        self.writer
+            .write_all(data)
+            .and_then(|()| self.writer.flush())
+            .map_err(|e| format!("Failed to write to the program's terminal: {}", e))
+    }
+
+    pub fn resize(&self, rows: u16, cols: u16) -> Result<(), String> {
+        self.master
+            .resize(PtySize {
+                rows,
+                cols,
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .map_err(|e| format!("Failed to resize the program's terminal: {}", e))
+    }
+}
+
+#[cfg(unix)]
+fn terminal_path(master: &dyn MasterPty) -> Result<PathBuf, String> {
+    master
+        .tty_name()
+        .ok_or_else(|| "The pseudo-terminal has no device path".to_string())
+}
+
+#[cfg(not(unix))]
+fn terminal_path(_master: &dyn MasterPty) -> Result<PathBuf, String> {
+    Err("Pseudo-terminals are only supported on unix".to_string())
+}
+
+// The terminal turns "\n" into "\r\n"; console lines don't keep the "\r".
+fn emit_line(app_handle: &AppHandle, debug_state: &DebugSessionState, line: &[u8]) {
+    let line = String::from_utf8_lossy(line.strip_suffix(b"\r").unwrap_or(line));
+    emit_console_line(app_handle, debug_state, "stdout", &line);
+}
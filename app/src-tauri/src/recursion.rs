@@ -0,0 +1,69 @@
+use serde::Serialize;
+
+// A cycle of frames has to repeat this many times back to back before it's collapsed.
+const MIN_REPEATS: usize = 4;
+// Longest cycle looked for, so mutual recursion (a -> b -> a -> b) collapses too.
+const MAX_PERIOD: usize = 4;
+
+// Marks the first frame of a collapsed recursion: the cycle it starts is shown once and the
+// `hidden_frames` after it, its further repeats, are left out.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Recursion {
+    // Frames in one cycle (1 for plain recursion)
+    pub period: usize,
+    // Times the cycle repeats, including the one shown
+    pub repeats: usize,
+    pub hidden_frames: usize,
+}
+
+// Collapses runs of repeating frames, innermost first. Frames are compared by `key` (e.g.
+// function, file and line); `mark` attaches the Recursion to the first frame of each run.
+pub fn collapse<T, K: PartialEq>(
+    frames: Vec<T>,
+    key: impl Fn(&T) -> K,
+    mark: impl Fn(&mut T, Recursion),
+) -> Vec<T> {
+    let keys: Vec<K> = frames.iter().map(&key).collect();
+    let mut kept = Vec::with_capacity(frames.len());
+    let mut frames = frames.into_iter();
+    let mut index = 0;
+    while index < keys.len() {
+        match longest_run(&keys, index) {
+            Some(recursion) => {
+                let mut cycle: Vec<T> = frames.by_ref().take(recursion.period).collect();
+                if let Some(first) = cycle.first_mut() {
+                    mark(first, recursion);
+                }
+                kept.extend(cycle);
+                frames.by_ref().take(recursion.hidden_frames).for_each(drop);
+                index += recursion.period * recursion.repeats;
+            }
+            None => {
+                kept.extend(frames.next());
+                index += 1;
+            }
+        }
+    }
+    kept
+}
+
+// The run starting at `start` that covers the most frames, preferring shorter cycles.
+fn longest_run<K: PartialEq>(keys: &[K], start: usize) -> Option<Recursion> {
+    (1..=MAX_PERIOD)
+        .rev()
+        .filter_map(|period| {
+            let mut repeats = 1;
+            while start + (repeats + 1) * period <= keys.len()
+                && (0..period).all(|k| keys[start + k] == keys[start + repeats * period + k])
+            {
+                repeats += 1;
+            }
+            (repeats >= MIN_REPEATS).then_some(Recursion {
+                period,
+                repeats,
+                hidden_frames: period * (repeats - 1),
+            })
+        })
+        .max_by_key(|run| run.period * run.repeats)
+}
@@ -0,0 +1,99 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::debugger::protocol::Variable;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Register {
+    pub name: String,
+    pub value: String,
+    // Differs from the value at the previous get_registers call
+    pub changed: bool,
+}
+
+// One register set of the "Registers" scope, e.g. "General Purpose Registers".
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterGroup {
+    pub name: String,
+    pub registers: Vec<Register>,
+}
+
+// Builds a group from the variables of a register set, flagging registers whose value differs
+// from `previous` (keyed by "group/register"). Registers seen for the first time aren't flagged.
+pub fn group_from_variables(
+    name: &str,
+    variables: &[Variable],
+    previous: &HashMap<String, String>,
+) -> RegisterGroup {
+    let registers = variables
+        .iter()
+        .map(|variable| {
+            let changed = previous
+                .get(&snapshot_key(name, &variable.name))
+                .is_some_and(|old| *old != variable.value);
+            Register {
+                name: variable.name.clone(),
+                value: variable.value.clone(),
+                changed,
+            }
+        })
+        .collect();
+
+    RegisterGroup {
+        name: name.to_string(),
+        registers,
+    }
+}
+
+// Values to compare the next fetch against.
+pub fn snapshot(groups: &[RegisterGroup]) -> HashMap<String, String> {
+    groups
+        .iter()
+        .flat_map(|group| {
+            group
+                .registers
+                .iter()
+                .map(|r| (snapshot_key(&group.name, &r.name), r.value.clone()))
+        })
+        .collect()
+}
+
+fn snapshot_key(group: &str, register: &str) -> String {
+    format!("{}/{}", group, register)
+}
+
+// Parses `register read` output, the fallback when the adapter has no "Registers" scope:
+//
+//   General Purpose Registers:
+//          rax = 0x0000000000000001
+//          rip = 0x0000555555555139  app`main + 4 at main.rs:2
+pub fn parse_register_read(output: &str, previous: &HashMap<String, String>) -> Vec<RegisterGroup> {
+    let mut groups: Vec<RegisterGroup> = Vec::new();
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if let Some(header) = trimmed.strip_suffix(':') {
+            groups.push(RegisterGroup {
+                name: header.to_string(),
+                registers: Vec::new(),
+            });
+            continue;
+        }
+        let (Some(group), Some((name, value))) = (groups.last_mut(), trimmed.split_once(" = "))
+        else {
+            continue;
+        };
+        let name = name.trim();
+        let value = value.trim();
+        let changed = previous
+            .get(&snapshot_key(&group.name, name))
+            .is_some_and(|old| old != value);
+        group.registers.push(Register {
+            name: name.to_string(),
+            value: value.to_string(),
+            changed,
+        });
+    }
+    groups
+}
@@ -0,0 +1,300 @@
+use parking_lot::RwLock;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Where a project keeps its renderer rules, relative to the workspace root.
+const CONFIG_FILE: &str = ".wayfind/renderers.json";
+
+// Epoch unit of an integer timestamp.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeUnit {
+    #[default]
+    Seconds,
+    Millis,
+    Micros,
+    Nanos,
+}
+
+// A richer representation for values of some type. Renderers only add a rendering; the raw
+// value is always kept.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "renderer", rename_all = "camelCase")]
+pub enum Renderer {
+    // An integer since the Unix epoch, shown as a UTC date
+    Timestamp {
+        #[serde(default)]
+        unit: TimeUnit,
+    },
+    // chrono's NaiveDate/NaiveDateTime/DateTime as lldb prints their fields, shown as a date
+    Chrono,
+    // A byte buffer (`[1, 2, 255]`, `b'\x01\x02'`, ...) shown as hex
+    Hex,
+    // An expression evaluated by the adapter, with `{expr}` replaced by the variable's
+    // evaluateName, e.g. `{expr}.isoformat()`
+    Expression {
+        template: String,
+    },
+}
+
+// One project rule: values whose type matches `type_pattern` (a regex over the whole type
+// name) go through `renderer`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RendererRule {
+    #[serde(rename = "type")]
+    pub type_pattern: String,
+    #[serde(flatten)]
+    pub renderer: Renderer,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RendererFile {
+    renderers: Vec<RendererRule>,
+}
+
+// What a renderer produced for one value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rendering {
+    Text(String),
+    // Evaluate this expression; its result is the rendering
+    Evaluate(String),
+}
+
+struct CompiledRule {
+    rule: RendererRule,
+    pattern: Regex,
+}
+
+// The open project's renderer rules, reloaded when a workspace is opened or the user edits them.
+#[derive(Default)]
+pub struct RendererRegistry {
+    rules: RwLock<Vec<CompiledRule>>,
+}
+
+impl RendererRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Replaces the rules with the project's `.wayfind/renderers.json`. A project without the file
+    // has no rules; rules with invalid patterns fail the whole load.
+    pub fn load(&self, project: Option<&Path>) -> Result<Vec<RendererRule>, String> {
+        let rules = match project.map(config_path) {
+            Some(path) if path.exists() => {
+                let content = fs::read_to_string(&path)
+                    .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+                let file: RendererFile = serde_json::from_str(&content)
+                    .map_err(|e| format!("Invalid renderer config {}: {}", path.display(), e))?;
+                file.renderers
+            }
+            _ => Vec::new(),
+        };
+
+        let compiled = rules
+            .iter()
+            .map(|rule| {
+                Regex::new(&format!("^(?:{})$", rule.type_pattern))
+                    .map(|pattern| CompiledRule {
+                        rule: rule.clone(),
+                        pattern,
+                    })
+                    .map_err(|e| format!("Invalid type pattern {}: {}", rule.type_pattern, e))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        *self.rules.write() = compiled;
+        Ok(rules)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.read().is_empty()
+    }
+
+    pub fn rules(&self) -> Vec<RendererRule> {
+        self.rules.read().iter().map(|c| c.rule.clone()).collect()
+    }
+
+    // Renders a value with the first rule matching its type. Returns the renderer's name along
+    // with the rendering, or None when no rule applies or the value isn't in the expected shape.
+    pub fn render(
+        &self,
+        type_name: &str,
+        value: &str,
+        evaluate_name: Option<&str>,
+    ) -> Option<(&'static str, Rendering)> {
+        let rules = self.rules.read();
+        let rule = rules.iter().find(|c| c.pattern.is_match(type_name))?;
+        let rendering = match &rule.rule.renderer {
+            Renderer::Timestamp { unit } => Rendering::Text(render_timestamp(value, *unit)?),
+            Renderer::Chrono => Rendering::Text(render_chrono(value)?),
+            Renderer::Hex => Rendering::Text(render_hex(value)?),
+            Renderer::Expression { template } => {
+                Rendering::Evaluate(template.replace("{expr}", evaluate_name?))
+            }
+        };
+        Some((rule.rule.renderer.name(), rendering))
+    }
+}
+
+impl Renderer {
+    fn name(&self) -> &'static str {
+        match self {
+            Renderer::Timestamp { .. } => "timestamp",
+            Renderer::Chrono => "chrono",
+            Renderer::Hex => "hex",
+            Renderer::Expression { .. } => "expression",
+        }
+    }
+}
+
+pub fn config_path(project: &Path) -> PathBuf {
+    project.join(CONFIG_FILE)
+}
+
+fn render_timestamp(value: &str, unit: TimeUnit) -> Option<String> {
+    let raw: i64 = value.trim().parse().ok()?;
+    let (per_second, digits) = match unit {
+        TimeUnit::Seconds => (1, 0),
+        TimeUnit::Millis => (1_000, 3),
+        TimeUnit::Micros => (1_000_000, 6),
+        TimeUnit::Nanos => (1_000_000_000, 9),
+    };
+    let secs = raw.div_euclid(per_second);
+    let fraction = raw.rem_euclid(per_second);
+    let (date, time) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+    let (year, month, day) = civil_from_days(date);
+    let mut rendered = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    );
+    if digits > 0 && fraction > 0 {
+        rendered.push_str(&format!(".{:0width$}", fraction, width = digits));
+    }
+    rendered.push('Z');
+    Some(rendered)
+}
+
+// chrono packs a date as `year << 13 | ordinal << 4 | flags` (the field is `ymdf`, or `yof` in
+// newer releases) and the time of day as `secs` plus `frac` nanoseconds.
+fn render_chrono(value: &str) -> Option<String> {
+    let packed = field(value, "ymdf").or_else(|| field(value, "yof"))?;
+    let year = packed >> 13;
+    let ordinal = (packed >> 4) & 0x1ff;
+    let (year, month, day) = civil_from_days(days_from_civil(year, 1, 1) + ordinal - 1);
+    let mut rendered = format!("{:04}-{:02}-{:02}", year, month, day);
+    if let Some(secs) = field(value, "secs") {
+        rendered.push_str(&format!(
+            "T{:02}:{:02}:{:02}",
+            secs / 3600,
+            secs % 3600 / 60,
+            secs % 60
+        ));
+        if let Some(frac) = field(value, "frac").filter(|f| *f > 0) {
+            rendered.push_str(&format!(".{:09}", frac));
+        }
+    }
+    Some(rendered)
+}
+
+// An integer field `name` anywhere in a rendered struct, e.g. `ymdf = 16578317` or `secs: 3600`.
+fn field(value: &str, name: &str) -> Option<i64> {
+    let pattern = Regex::new(&format!(r"\b{}\s*[:=]\s*(-?\d+)", regex::escape(name))).ok()?;
+    pattern.captures(value)?[1].parse().ok()
+}
+
+fn render_hex(value: &str) -> Option<String> {
+    let value = value.trim();
+    let bytes = python_bytes(value).or_else(|| byte_list(value))?;
+    Some(
+        bytes
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+// A Python bytes/bytearray repr: b'..' or bytearray(b'..').
+fn python_bytes(value: &str) -> Option<Vec<u8>> {
+    let inner = value
+        .strip_prefix("bytearray(")
+        .and_then(|v| v.strip_suffix(')'))
+        .unwrap_or(value)
+        .strip_prefix('b')?;
+    let quote = inner.chars().next().filter(|c| *c == '\'' || *c == '"')?;
+    let literal = inner.strip_prefix(quote)?.strip_suffix(quote)?;
+
+    let mut bytes = Vec::new();
+    let mut chars = literal.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            bytes.push(u8::try_from(c as u32).ok()?);
+            continue;
+        }
+        let byte = match chars.next()? {
+            'x' => {
+                let hex: String = chars.by_ref().take(2).collect();
+                u8::from_str_radix(&hex, 16).ok()?
+            }
+            'n' => b'\n',
+            'r' => b'\r',
+            't' => b'\t',
+            '0' => 0,
+            other => u8::try_from(other as u32).ok()?,
+        };
+        bytes.push(byte);
+    }
+    Some(bytes)
+}
+
+// A list of byte values as lldb prints slices and vectors, e.g. `size=3 { [0] = 1 [1] = 2 ... }`
+// or `[1, 2, 255]`.
+fn byte_list(value: &str) -> Option<Vec<u8>> {
+    let body = value
+        .find(['{', '['])
+        .map(|start| &value[start..])
+        .unwrap_or(value);
+    let element = Regex::new(r"\[\d+\]\s*=\s*").ok()?;
+    let body = element.replace_all(body, "");
+    let bytes = body
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(|token| match token.strip_prefix("0x") {
+            Some(hex) => u8::from_str_radix(hex, 16).ok(),
+            None => token.parse::<u8>().ok(),
+        })
+        .collect::<Option<Vec<u8>>>()?;
+    (!bytes.is_empty()).then_some(bytes)
+}
+
+// Days since 1970-01-01 to a proleptic Gregorian date (Howard Hinnant's algorithm).
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
@@ -0,0 +1,251 @@
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::debug_state::LaunchRequest;
+use crate::timeline::SessionTimeline;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+impl ReportFormat {
+    // Explicit format names win; otherwise `.html`/`.htm` paths get HTML and everything else
+    // Markdown.
+    pub fn resolve(format: Option<&str>, path: &str) -> Result<Self, String> {
+        match format.map(|f| f.to_ascii_lowercase()).as_deref() {
+            Some("markdown") | Some("md") => Ok(ReportFormat::Markdown),
+            Some("html") => Ok(ReportFormat::Html),
+            Some(other) => Err(format!("Unknown report format: {}", other)),
+            None => {
+                let lower = path.to_ascii_lowercase();
+                if lower.ends_with(".html") || lower.ends_with(".htm") {
+                    Ok(ReportFormat::Html)
+                } else {
+                    Ok(ReportFormat::Markdown)
+                }
+            }
+        }
+    }
+}
+
+pub struct ReportFrame {
+    pub name: String,
+    pub file: Option<String>,
+    pub line: i64,
+}
+
+pub struct ReportEvaluation {
+    pub expression: String,
+    pub result: String,
+    pub success: bool,
+}
+
+// Everything a session report shows, gathered from the session state.
+pub struct SessionReport {
+    pub token: String,
+    pub state: String,
+    pub launch: Option<LaunchRequest>,
+    // (file, line) -> times stopped there on a breakpoint
+    pub breakpoint_hits: BTreeMap<(String, i64), usize>,
+    // Innermost first; empty when the program wasn't paused
+    pub stack: Vec<ReportFrame>,
+    pub evaluations: Vec<ReportEvaluation>,
+    pub output: Vec<String>,
+}
+
+impl SessionReport {
+    // Collects breakpoint stops and evaluations from the timeline. With `expressions`, only
+    // evaluations of those expressions are kept.
+    pub fn add_timeline(&mut self, timeline: &SessionTimeline, expressions: Option<&[String]>) {
+        for entry in &timeline.entries {
+            let detail = &entry.detail;
+            match entry.kind.as_str() {
+                "stopped" if detail.get("reason").and_then(Value::as_str) == Some("breakpoint") => {
+                    let file = detail.get("file").and_then(Value::as_str);
+                    let line = detail.get("line").and_then(Value::as_i64);
+                    if let (Some(file), Some(line)) = (file, line) {
+                        *self
+                            .breakpoint_hits
+                            .entry((file.to_string(), line))
+                            .or_insert(0) += 1;
+                    }
+                }
+                "evaluate" => {
+                    let Some(expression) = detail.get("expression").and_then(Value::as_str) else {
+                        continue;
+                    };
+                    if expressions.is_some_and(|selected| !selected.iter().any(|e| e == expression))
+                    {
+                        continue;
+                    }
+                    self.evaluations.push(ReportEvaluation {
+                        expression: expression.to_string(),
+                        result: detail
+                            .get("result")
+                            .and_then(Value::as_str)
+                            .unwrap_or_default()
+                            .to_string(),
+                        success: detail.get("success").and_then(Value::as_bool) != Some(false),
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Markdown => self.render_markdown(),
+            ReportFormat::Html => self.render_html(),
+        }
+    }
+
+    fn launch_json(&self) -> String {
+        self.launch
+            .as_ref()
+            .and_then(|launch| serde_json::to_string_pretty(launch).ok())
+            .unwrap_or_else(|| "No launch recorded".to_string())
+    }
+
+    fn frame_location(frame: &ReportFrame) -> String {
+        match &frame.file {
+            Some(file) => format!("{}:{}", file, frame.line),
+            None => "<no source>".to_string(),
+        }
+    }
+
+    fn render_markdown(&self) -> String {
+        let mut out = format!(
+            "# Debug session report\n\nSession `{}`, state: {}\n\n## Launch\n\n```json\n{}\n```\n\n",
+            self.token,
+            self.state,
+            self.launch_json()
+        );
+
+        out.push_str("## Breakpoints hit\n\n");
+        if self.breakpoint_hits.is_empty() {
+            out.push_str("None\n\n");
+        } else {
+            out.push_str("| Location | Hits |\n| --- | --- |\n");
+            for ((file, line), hits) in &self.breakpoint_hits {
+                out.push_str(&format!("| `{}:{}` | {} |\n", file, line, hits));
+            }
+            out.push('\n');
+        }
+
+        out.push_str("## Final stack\n\n");
+        if self.stack.is_empty() {
+            out.push_str("Not paused\n\n");
+        } else {
+            for (index, frame) in self.stack.iter().enumerate() {
+                out.push_str(&format!(
+                    "{}. `{}` at `{}`\n",
+                    index,
+                    frame.name,
+                    Self::frame_location(frame)
+                ));
+            }
+            out.push('\n');
+        }
+
+        out.push_str("## Evaluations\n\n");
+        if self.evaluations.is_empty() {
+            out.push_str("None\n\n");
+        } else {
+            for evaluation in &self.evaluations {
+                let marker = if evaluation.success { "" } else { " (failed)" };
+                out.push_str(&format!(
+                    "- `{}`{}\n\n  ```\n  {}\n  ```\n",
+                    evaluation.expression,
+                    marker,
+                    evaluation.result.replace('\n', "\n  ")
+                ));
+            }
+            out.push('\n');
+        }
+
+        out.push_str("## Output\n\n```\n");
+        for line in &self.output {
+            out.push_str(line);
+            out.push('\n');
+        }
+        out.push_str("```\n");
+        out
+    }
+
+    fn render_html(&self) -> String {
+        let mut out = format!(
+            "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Debug session report</title></head>\n<body>\n\
+             <h1>Debug session report</h1>\n<p>Session <code>{}</code>, state: {}</p>\n\
+             <h2>Launch</h2>\n<pre>{}</pre>\n",
+            escape_html(&self.token),
+            escape_html(&self.state),
+            escape_html(&self.launch_json())
+        );
+
+        out.push_str("<h2>Breakpoints hit</h2>\n");
+        if self.breakpoint_hits.is_empty() {
+            out.push_str("<p>None</p>\n");
+        } else {
+            out.push_str("<table>\n<tr><th>Location</th><th>Hits</th></tr>\n");
+            for ((file, line), hits) in &self.breakpoint_hits {
+                out.push_str(&format!(
+                    "<tr><td><code>{}:{}</code></td><td>{}</td></tr>\n",
+                    escape_html(file),
+                    line,
+                    hits
+                ));
+            }
+            out.push_str("</table>\n");
+        }
+
+        out.push_str("<h2>Final stack</h2>\n");
+        if self.stack.is_empty() {
+            out.push_str("<p>Not paused</p>\n");
+        } else {
+            out.push_str("<ol start=\"0\">\n");
+            for frame in &self.stack {
+                out.push_str(&format!(
+                    "<li><code>{}</code> at <code>{}</code></li>\n",
+                    escape_html(&frame.name),
+                    escape_html(&Self::frame_location(frame))
+                ));
+            }
+            out.push_str("</ol>\n");
+        }
+
+        out.push_str("<h2>Evaluations</h2>\n");
+        if self.evaluations.is_empty() {
+            out.push_str("<p>None</p>\n");
+        } else {
+            out.push_str("<dl>\n");
+            for evaluation in &self.evaluations {
+                let marker = if evaluation.success { "" } else { " (failed)" };
+                out.push_str(&format!(
+                    "<dt><code>{}</code>{}</dt><dd><pre>{}</pre></dd>\n",
+                    escape_html(&evaluation.expression),
+                    marker,
+                    escape_html(&evaluation.result)
+                ));
+            }
+            out.push_str("</dl>\n");
+        }
+
+        out.push_str("<h2>Output</h2>\n<pre>");
+        for line in &self.output {
+            out.push_str(&escape_html(line));
+            out.push('\n');
+        }
+        out.push_str("</pre>\n</body>\n</html>\n");
+        out
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
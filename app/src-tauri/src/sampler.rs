@@ -0,0 +1,181 @@
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use crate::debug_state::{DebugSessionState, DebuggerState};
+
+// Time between py-spy dumps while the debuggee is running.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(200);
+// Emit aggregated flame data to the frontend every this many samples.
+const EMIT_EVERY: u64 = 10;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SampledStack {
+    // Folded stack, outermost frame first, frames separated by ';'
+    pub stack: String,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SampledStacks {
+    pub pid: u32,
+    pub total_samples: u64,
+    pub stacks: Vec<SampledStack>,
+}
+
+// Periodically samples a running Python debuggee with `py-spy dump` and aggregates the stacks.
+// Sampling is skipped whenever the session isn't in the Running state, so pauses at breakpoints
+// don't show up as hot spots.
+pub struct PySpySampler {
+    pid: u32,
+    counts: Arc<Mutex<HashMap<String, u64>>>,
+    total_samples: Arc<AtomicU64>,
+    stop: Arc<AtomicBool>,
+}
+
+impl PySpySampler {
+    pub fn start(
+        py_spy: PathBuf,
+        pid: u32,
+        app_handle: AppHandle,
+        debug_state: Arc<DebugSessionState>,
+    ) -> Self {
+        let counts = Arc::new(Mutex::new(HashMap::new()));
+        let total_samples = Arc::new(AtomicU64::new(0));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let sampler = PySpySampler {
+            pid,
+            counts: Arc::clone(&counts),
+            total_samples: Arc::clone(&total_samples),
+            stop: Arc::clone(&stop),
+        };
+
+        thread::spawn(move || {
+            while !stop.load(Ordering::SeqCst) {
+                let running = matches!(*debug_state.state.read(), DebuggerState::Running);
+                if running {
+                    match dump_stacks(&py_spy, pid) {
+                        Ok(stacks) => {
+                            {
+                                let mut counts = counts.lock();
+                                for stack in stacks {
+                                    *counts.entry(stack).or_insert(0) += 1;
+                                }
+                            }
+                            let total = total_samples.fetch_add(1, Ordering::SeqCst) + 1;
+                            if total % EMIT_EVERY == 0 {
+                                let _ = app_handle
+                                    .emit("flame-data", snapshot(pid, &counts, &total_samples));
+                            }
+                        }
+                        Err(e) => {
+                            // The process may be exiting; keep trying until we're stopped
+                            tracing::debug!("py-spy sample failed: {}", e);
+                        }
+                    }
+                }
+                thread::sleep(SAMPLE_INTERVAL);
+            }
+            tracing::info!("py-spy sampler for pid {} stopped", pid);
+        });
+
+        sampler
+    }
+
+    pub fn stacks(&self) -> SampledStacks {
+        snapshot(self.pid, &self.counts, &self.total_samples)
+    }
+
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+}
+
+impl Drop for PySpySampler {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn snapshot(pid: u32, counts: &Mutex<HashMap<String, u64>>, total: &AtomicU64) -> SampledStacks {
+    let mut stacks: Vec<SampledStack> = counts
+        .lock()
+        .iter()
+        .map(|(stack, count)| SampledStack {
+            stack: stack.clone(),
+            count: *count,
+        })
+        .collect();
+    stacks.sort_by_key(|s| std::cmp::Reverse(s.count));
+
+    SampledStacks {
+        pid,
+        total_samples: total.load(Ordering::SeqCst),
+        stacks,
+    }
+}
+
+// Takes one non-blocking `py-spy dump` and returns a folded stack per thread.
+fn dump_stacks(py_spy: &PathBuf, pid: u32) -> Result<Vec<String>, String> {
+    let output = Command::new(py_spy)
+        .args(["dump", "--json", "--nonblocking", "--pid"])
+        .arg(pid.to_string())
+        .output()
+        .map_err(|e| format!("Failed to run py-spy: {}", e))?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+
+    let threads: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Invalid py-spy output: {}", e))?;
+
+    let mut stacks = Vec::new();
+    for thread in threads.as_array().into_iter().flatten() {
+        let thread_name = thread
+            .get("thread_name")
+            .and_then(|n| n.as_str())
+            .map(String::from)
+            .or_else(|| {
+                thread
+                    .get("thread_id")
+                    .and_then(|id| id.as_u64())
+                    .map(|id| format!("thread {}", id))
+            })
+            .unwrap_or_else(|| "thread".to_string());
+
+        // py-spy lists frames innermost first; folded stacks go outermost first
+        let frames: Vec<String> = thread
+            .get("frames")
+            .and_then(|f| f.as_array())
+            .into_iter()
+            .flatten()
+            .rev()
+            .map(|frame| {
+                let name = frame.get("name").and_then(|n| n.as_str()).unwrap_or("?");
+                let file = frame
+                    .get("short_filename")
+                    .or_else(|| frame.get("filename"))
+                    .and_then(|f| f.as_str())
+                    .unwrap_or("?");
+                let line = frame.get("line").and_then(|l| l.as_i64()).unwrap_or(0);
+                format!("{} ({}:{})", name, file, line)
+            })
+            .collect();
+
+        if !frames.is_empty() {
+            stacks.push(format!("{};{}", thread_name, frames.join(";")));
+        }
+    }
+    Ok(stacks)
+}
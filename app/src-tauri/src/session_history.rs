@@ -0,0 +1,245 @@
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::debug_state::LaunchRequest;
+use crate::session_summary::SessionSummary;
+use crate::timeline::SessionTimeline;
+
+// Sessions kept; the oldest are deleted as new ones are recorded
+const MAX_SESSIONS: i64 = 1000;
+const DEFAULT_LIST_LIMIT: usize = 100;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS sessions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session TEXT NOT NULL,
+    target TEXT,
+    engine TEXT,
+    started_at INTEGER NOT NULL,
+    ended_at INTEGER NOT NULL,
+    ended_by TEXT NOT NULL,
+    exit_code INTEGER,
+    signal TEXT,
+    stops INTEGER NOT NULL,
+    breakpoints_hit INTEGER NOT NULL,
+    launch TEXT,
+    breakpoints TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS sessions_started_at ON sessions (started_at);
+";
+
+const COLUMNS: &str = "id, session, target, engine, started_at, ended_at, ended_by, exit_code, \
+                       signal, stops, breakpoints_hit";
+
+// One ended session in the history list.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PastSession {
+    pub id: i64,
+    pub session: String,
+    // The script or workspace that was launched, when the session was launched from wayfind
+    pub target: Option<String>,
+    pub engine: Option<String>,
+    // Unix times in milliseconds
+    pub started_at: u64,
+    pub ended_at: u64,
+    pub ended_by: String,
+    pub exit_code: Option<i64>,
+    pub signal: Option<String>,
+    pub stops: u64,
+    // Stops at breakpoints; the locations are in PastSessionDetails
+    pub breakpoints_hit: u64,
+}
+
+// A breakpoint location the session stopped at, and how often.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakpointHit {
+    pub file: String,
+    pub line: i64,
+    pub hits: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PastSessionDetails {
+    #[serde(flatten)]
+    pub session: PastSession,
+    // What launch_debug_session was called with, to launch the session again
+    pub launch: Option<LaunchRequest>,
+    pub breakpoints: Vec<BreakpointHit>,
+}
+
+// Ended sessions, kept in a SQLite database in the app data directory so users can look back at
+// what they debugged and relaunch old configurations.
+pub struct SessionHistoryStore {
+    db: Option<Mutex<Connection>>,
+}
+
+impl SessionHistoryStore {
+    // Without a path, or if the database can't be opened, the history only lasts until the app
+    // quits.
+    pub fn open(path: Option<PathBuf>) -> Self {
+        let opened = match &path {
+            Some(path) => {
+                if let Some(dir) = path.parent() {
+                    let _ = std::fs::create_dir_all(dir);
+                }
+                Connection::open(path)
+            }
+            None => Connection::open_in_memory(),
+        };
+        let db = opened
+            .or_else(|e| {
+                tracing::warn!("Failed to open the session history at {:?}: {}", path, e);
+                Connection::open_in_memory()
+            })
+            .and_then(|conn| conn.execute_batch(SCHEMA).map(|()| conn));
+        match db {
+            Ok(conn) => SessionHistoryStore {
+                db: Some(Mutex::new(conn)),
+            },
+            Err(e) => {
+                tracing::warn!("Session history is unavailable: {}", e);
+                SessionHistoryStore { db: None }
+            }
+        }
+    }
+
+    pub fn record(
+        &self,
+        summary: &SessionSummary,
+        launch: Option<&LaunchRequest>,
+        breakpoints: &[BreakpointHit],
+    ) -> Result<(), String> {
+        let Some(db) = &self.db else {
+            return Ok(());
+        };
+        let launch_json = launch
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| e.to_string())?;
+        let breakpoints_json = serde_json::to_string(breakpoints).map_err(|e| e.to_string())?;
+        let hit_count: u64 = breakpoints.iter().map(|bp| bp.hits).sum();
+
+        let conn = db.lock();
+        conn.execute(
+            "INSERT INTO sessions (session, target, engine, started_at, ended_at, ended_by, \
+             exit_code, signal, stops, breakpoints_hit, launch, breakpoints) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                summary.session,
+                launch.map(|l| &l.script_path),
+                launch.map(|l| &l.debug_engine),
+                summary.started_at as i64,
+                summary.started_at.saturating_add(summary.duration_ms) as i64,
+                summary.ended_by,
+                summary.exit_code,
+                summary.signal,
+                summary.stops as i64,
+                hit_count as i64,
+                launch_json,
+                breakpoints_json,
+            ],
+        )
+        .and_then(|_| {
+            conn.execute(
+                "DELETE FROM sessions WHERE id <= (SELECT MAX(id) FROM sessions) - ?1",
+                params![MAX_SESSIONS],
+            )
+        })
+        .map(|_| ())
+        .map_err(|e| format!("Failed to record the session: {}", e))
+    }
+
+    // The most recent sessions, newest first.
+    pub fn list(&self, limit: Option<usize>) -> Result<Vec<PastSession>, String> {
+        let Some(db) = &self.db else {
+            return Ok(Vec::new());
+        };
+        let limit = limit.unwrap_or(DEFAULT_LIST_LIMIT) as i64;
+        let conn = db.lock();
+        let mut statement = conn
+            .prepare(&format!(
+                "SELECT {} FROM sessions ORDER BY started_at DESC, id DESC LIMIT ?1",
+                COLUMNS
+            ))
+            .map_err(|e| e.to_string())?;
+        let sessions = statement
+            .query_map(params![limit], past_session)
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to read the session history: {}", e))?;
+        Ok(sessions)
+    }
+
+    pub fn details(&self, id: i64) -> Result<Option<PastSessionDetails>, String> {
+        let Some(db) = &self.db else {
+            return Ok(None);
+        };
+        let conn = db.lock();
+        let row = conn
+            .query_row(
+                &format!(
+                    "SELECT {}, launch, breakpoints FROM sessions WHERE id = ?1",
+                    COLUMNS
+                ),
+                params![id],
+                |row| {
+                    Ok((
+                        past_session(row)?,
+                        row.get::<_, Option<String>>(11)?,
+                        row.get::<_, String>(12)?,
+                    ))
+                },
+            )
+            .optional()
+            .map_err(|e| format!("Failed to read the session history: {}", e))?;
+        let Some((session, launch, breakpoints)) = row else {
+            return Ok(None);
+        };
+        Ok(Some(PastSessionDetails {
+            session,
+            // Launches recorded by an older wayfind may no longer parse
+            launch: launch.and_then(|l| serde_json::from_str(&l).ok()),
+            breakpoints: serde_json::from_str(&breakpoints).unwrap_or_default(),
+        }))
+    }
+}
+
+fn past_session(row: &Row) -> rusqlite::Result<PastSession> {
+    Ok(PastSession {
+        id: row.get(0)?,
+        session: row.get(1)?,
+        target: row.get(2)?,
+        engine: row.get(3)?,
+        started_at: row.get::<_, i64>(4)? as u64,
+        ended_at: row.get::<_, i64>(5)? as u64,
+        ended_by: row.get(6)?,
+        exit_code: row.get(7)?,
+        signal: row.get(8)?,
+        stops: row.get::<_, i64>(9)? as u64,
+        breakpoints_hit: row.get::<_, i64>(10)? as u64,
+    })
+}
+
+// The breakpoint locations the session stopped at, from its timeline.
+pub fn breakpoint_hits(timeline: &SessionTimeline) -> Vec<BreakpointHit> {
+    let mut hits: BTreeMap<(String, i64), u64> = BTreeMap::new();
+    for entry in timeline.entries.iter().filter(|e| e.kind == "stopped") {
+        let detail = &entry.detail;
+        if detail.get("reason").and_then(|r| r.as_str()) != Some("breakpoint") {
+            continue;
+        }
+        let file = detail.get("file").and_then(|f| f.as_str());
+        let line = detail.get("line").and_then(|l| l.as_i64());
+        if let (Some(file), Some(line)) = (file, line) {
+            *hits.entry((file.to_string(), line)).or_insert(0) += 1;
+        }
+    }
+    hits.into_iter()
+        .map(|((file, line), hits)| BreakpointHit { file, line, hits })
+        .collect()
+}
@@ -0,0 +1,53 @@
+use serde::Serialize;
+use std::process::ExitStatus;
+
+// What the console shows when a session ends, instead of a bare "terminated".
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSummary {
+    pub session: String,
+    // "exited" when the program finished on its own, otherwise "terminated", "detached" or
+    // "killed" depending on how the user ended the session
+    pub ended_by: String,
+    pub exit_code: Option<i64>,
+    // Signal that ended the program, e.g. "SIGSEGV", when it's known
+    pub signal: Option<String>,
+    // Unix time in milliseconds the session was launched at
+    pub started_at: u64,
+    pub duration_ms: u64,
+    // Stops the user saw; stops resumed by value watches aren't counted
+    pub stops: u64,
+}
+
+// The exit code and signal of a debuggee process the session owns. Only Python sessions own the
+// program itself; lldb-dap reports its debuggee's exit code in the "exited" event instead.
+pub fn exit_details(status: ExitStatus) -> (Option<i64>, Option<String>) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return (None, Some(signal_name(signal)));
+        }
+    }
+    (status.code().map(i64::from), None)
+}
+
+#[cfg(unix)]
+fn signal_name(signal: i32) -> String {
+    let name = match signal {
+        1 => "SIGHUP",
+        2 => "SIGINT",
+        3 => "SIGQUIT",
+        4 => "SIGILL",
+        5 => "SIGTRAP",
+        6 => "SIGABRT",
+        8 => "SIGFPE",
+        9 => "SIGKILL",
+        11 => "SIGSEGV",
+        13 => "SIGPIPE",
+        14 => "SIGALRM",
+        15 => "SIGTERM",
+        _ => return format!("signal {}", signal),
+    };
+    name.to_string()
+}
@@ -0,0 +1,84 @@
+use parking_lot::RwLock;
+use std::collections::BTreeMap;
+use std::sync::atomic::AtomicU64;
+use std::sync::Arc;
+
+use crate::debug_state::{DebugSessionState, SessionInfo};
+
+// Token of the session that exists from startup; single-target launches use it.
+pub const DEFAULT_SESSION: &str = "default";
+
+// All debug sessions, each with its own adapter, breakpoints and event stream, plus which one
+// stepping, evaluation and the other session commands are routed to.
+pub struct SessionManager {
+    sessions: RwLock<BTreeMap<String, Arc<DebugSessionState>>>,
+    active: RwLock<String>,
+    status_seq: Arc<AtomicU64>,
+}
+
+impl SessionManager {
+    pub fn new() -> Self {
+        let status_seq = Arc::new(AtomicU64::new(0));
+        let mut sessions = BTreeMap::new();
+        sessions.insert(
+            DEFAULT_SESSION.to_string(),
+            Arc::new(DebugSessionState::new(
+                DEFAULT_SESSION,
+                Arc::clone(&status_seq),
+            )),
+        );
+        SessionManager {
+            sessions: RwLock::new(sessions),
+            active: RwLock::new(DEFAULT_SESSION.to_string()),
+            status_seq,
+        }
+    }
+
+    pub fn active(&self) -> Arc<DebugSessionState> {
+        let token = self.active.read().clone();
+        self.get_or_create(&token)
+    }
+
+    pub fn get_or_create(&self, token: &str) -> Arc<DebugSessionState> {
+        if let Some(session) = self.sessions.read().get(token) {
+            return Arc::clone(session);
+        }
+        let mut sessions = self.sessions.write();
+        Arc::clone(sessions.entry(token.to_string()).or_insert_with(|| {
+            Arc::new(DebugSessionState::new(token, Arc::clone(&self.status_seq)))
+        }))
+    }
+
+    pub fn get(&self, token: &str) -> Option<Arc<DebugSessionState>> {
+        self.sessions.read().get(token).map(Arc::clone)
+    }
+
+    // A new session for a child of `parent`, e.g. "default.child1".
+    pub fn create_child(&self, parent: &str) -> Arc<DebugSessionState> {
+        let mut sessions = self.sessions.write();
+        let token = (1..)
+            .map(|n| format!("{}.child{}", parent, n))
+            .find(|token| !sessions.contains_key(token))
+            .unwrap_or_default();
+        let session = Arc::new(DebugSessionState::new(&token, Arc::clone(&self.status_seq)));
+        *session.parent.write() = Some(parent.to_string());
+        sessions.insert(token, Arc::clone(&session));
+        session
+    }
+
+    pub fn set_active(&self, token: &str) -> Result<(), String> {
+        if !self.sessions.read().contains_key(token) {
+            return Err(format!("No debug session named '{}'", token));
+        }
+        *self.active.write() = token.to_string();
+        Ok(())
+    }
+
+    pub fn list(&self) -> Vec<SessionInfo> {
+        self.sessions
+            .read()
+            .values()
+            .map(|session| session.session_info())
+            .collect()
+    }
+}
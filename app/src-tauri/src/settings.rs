@@ -0,0 +1,84 @@
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::adapters::custom::CustomAdapterConfig;
+use crate::debugger::client::RetryPolicy;
+
+// User settings persisted as JSON in the app config directory. Every field is optional so older
+// settings files keep loading as new keys are added.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    // Explicit lldb-dap executable; takes precedence over automatic discovery
+    pub lldb_dap_path: Option<String>,
+    // Explicit gdb executable for the gdb engine; otherwise gdb is looked up on PATH
+    pub gdb_path: Option<String>,
+    // Python interpreter to run debugpy with, for projects that don't pin one; otherwise python3
+    // on PATH
+    pub python_interpreter: Option<String>,
+    // Sample running Python sessions with py-spy (must be on PATH)
+    pub py_spy_sampling: bool,
+    // Directories outside the workspace that filesystem commands may still access
+    pub allowed_paths: Vec<String>,
+    // Glob patterns of source paths never to stop in, e.g. `site-packages` or `/rustc/`. Steps
+    // into them step back out, and exceptions raised in them are continued past.
+    pub never_stop_in: Vec<String>,
+    // Retries with backoff when connecting to an adapter that isn't listening yet
+    pub connect_retry: RetryPolicy,
+    // Developer mode: check every DAP message against the protocol schema and log violations
+    pub validate_dap_messages: bool,
+    // Adapters for languages without a built-in one, launched as "custom:<name>"
+    pub custom_adapters: Vec<CustomAdapterConfig>,
+}
+
+pub struct SettingsStore {
+    path: Option<PathBuf>,
+    settings: RwLock<Settings>,
+}
+
+impl SettingsStore {
+    // Loads settings from `path`, falling back to defaults if the file is missing or unreadable.
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let settings = path
+            .as_ref()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|content| match serde_json::from_str::<Settings>(&content) {
+                Ok(settings) => Some(settings),
+                Err(e) => {
+                    tracing::warn!("Ignoring invalid settings file: {}", e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        SettingsStore {
+            path,
+            settings: RwLock::new(settings),
+        }
+    }
+
+    pub fn get(&self) -> Settings {
+        self.settings.read().clone()
+    }
+
+    // Replaces the current settings and writes them to disk.
+    pub fn set(&self, settings: Settings) -> Result<(), String> {
+        *self.settings.write() = settings;
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+        }
+        let json = serde_json::to_string_pretty(&*self.settings.read())
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        fs::write(path, json).map_err(|e| format!("Failed to write settings: {}", e))
+    }
+}
@@ -0,0 +1,71 @@
+use regex::Regex;
+
+// What to do about a stop in code the user never wants to stop in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SkipAction {
+    StepOut,
+    Continue,
+}
+
+// Source paths stops should never be left in, from the "never stop in" glob patterns in the
+// settings. `*` and `?` match within a path segment and `**` across segments. Patterns starting
+// with `/` are anchored at the root; others match anywhere, so `site-packages` matches every
+// file under a site-packages directory.
+pub struct SkipList {
+    patterns: Vec<Regex>,
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from(if glob.starts_with('/') { "^" } else { "(^|/)" });
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                regex.push_str(".*");
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    // A pattern naming a directory covers everything under it
+    if !glob.ends_with('/') {
+        regex.push_str("(/|$)");
+    }
+    regex
+}
+
+impl SkipList {
+    pub fn new(globs: &[String]) -> Self {
+        let patterns = globs
+            .iter()
+            .filter(|glob| !glob.is_empty())
+            .filter_map(|glob| match Regex::new(&glob_to_regex(glob)) {
+                Ok(regex) => Some(regex),
+                Err(e) => {
+                    tracing::warn!("Ignoring never-stop pattern {}: {}", glob, e);
+                    None
+                }
+            })
+            .collect();
+        SkipList { patterns }
+    }
+
+    pub fn matches(&self, path: &str) -> bool {
+        let path = path.replace('\\', "/");
+        self.patterns.iter().any(|pattern| pattern.is_match(&path))
+    }
+
+    // Stops the user asked for (breakpoints, pauses, entry) always stand. A step that lands in
+    // skipped code steps back out of it; anything else, e.g. an exception raised and handled
+    // inside a framework, continues.
+    pub fn action(&self, reason: &str, path: &str) -> Option<SkipAction> {
+        let action = match reason {
+            "step" => SkipAction::StepOut,
+            "exception" | "signal" => SkipAction::Continue,
+            _ => return None,
+        };
+        self.matches(path).then_some(action)
+    }
+}
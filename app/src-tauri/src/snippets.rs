@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Where a project keeps its snippets, relative to the workspace root. Read on every use so edits
+// show up without reloading.
+const SNIPPETS_FILE: &str = ".wayfind/snippets.json";
+
+// A named expression a team evaluates routinely, e.g. "dump request headers".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snippet {
+    pub name: String,
+    pub expression: String,
+    // "python" or "rust"; snippets without one run in any session
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct SnippetFile {
+    snippets: Vec<Snippet>,
+}
+
+impl Snippet {
+    // Whether the snippet is written for sessions of `debugger_type` (rr replays run Rust).
+    pub fn runs_in(&self, debugger_type: Option<&str>) -> bool {
+        let session_language = match debugger_type {
+            Some("rr") => Some("rust"),
+            other => other,
+        };
+        match self.language.as_deref() {
+            None => true,
+            Some(language) => Some(language) == session_language,
+        }
+    }
+}
+
+pub fn snippets_path(project: &Path) -> PathBuf {
+    project.join(SNIPPETS_FILE)
+}
+
+// The project's snippets; a project without the file has none.
+pub fn load(project: Option<&Path>) -> Result<Vec<Snippet>, String> {
+    let Some(path) = project.map(snippets_path).filter(|path| path.exists()) else {
+        return Ok(Vec::new());
+    };
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let file: SnippetFile = serde_json::from_str(&content)
+        .map_err(|e| format!("Invalid snippet file {}: {}", path.display(), e))?;
+    Ok(file.snippets)
+}
@@ -0,0 +1,37 @@
+use serde::Serialize;
+use serde_json::Value;
+
+// Source text the adapter serves by sourceReference rather than a path on disk, e.g. generated
+// code or modules imported from a zip. References are only valid for the adapter session that
+// handed them out.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdapterSource {
+    pub source_reference: i64,
+    pub name: Option<String>,
+    pub content: String,
+    pub mime_type: Option<String>,
+}
+
+impl AdapterSource {
+    // Builds the source from a "source" response body.
+    pub fn from_response(
+        source_reference: i64,
+        name: Option<String>,
+        body: &Value,
+    ) -> Result<Self, String> {
+        let content = body
+            .get("content")
+            .and_then(Value::as_str)
+            .ok_or("No content in source response")?;
+        Ok(AdapterSource {
+            source_reference,
+            name,
+            content: content.to_string(),
+            mime_type: body
+                .get("mimeType")
+                .and_then(Value::as_str)
+                .map(String::from),
+        })
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use crate::debugger::client::DAPClient;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StepKind {
+    In,
+    Over,
+    Out,
+}
+
+impl StepKind {
+    // Timeline entry name
+    pub fn name(self) -> &'static str {
+        match self {
+            StepKind::In => "stepIn",
+            StepKind::Over => "stepOver",
+            StepKind::Out => "stepOut",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            StepKind::In => "Step in",
+            StepKind::Over => "Step over",
+            StepKind::Out => "Step out",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub kind: StepKind,
+    pub granularity: Option<String>,
+    pub single_thread: bool,
+    // Which call of the line to step into, from stepInTargets
+    pub target_id: Option<i64>,
+}
+
+impl Step {
+    pub async fn send(&self, client: &DAPClient, thread_id: i64) -> Result<(), String> {
+        let granularity = self.granularity.as_deref();
+        let result = match self.kind {
+            StepKind::In => {
+                client
+                    .step_in_to(thread_id, self.target_id, granularity, self.single_thread)
+                    .await
+            }
+            StepKind::Over => {
+                client
+                    .next(thread_id, granularity, self.single_thread)
+                    .await
+            }
+            StepKind::Out => {
+                client
+                    .step_out(thread_id, granularity, self.single_thread)
+                    .await
+            }
+        };
+        result.map(|_| ()).map_err(|e| e.to_string())
+    }
+}
+
+// Outcome of asking to step a thread.
+#[derive(Debug, PartialEq)]
+pub enum StepRequest {
+    // Nothing in flight: send it now
+    Send,
+    // Sent once the step in flight stops, replacing any step already waiting
+    Queued,
+}
+
+// Serializes steps per thread. Holding the step key fires steps faster than the adapter stops,
+// so while one is in flight only the latest further request is kept and sent from the stop; the
+// stops in between aren't reported.
+#[derive(Debug, Default)]
+pub struct SteppingController {
+    // Threads with a step in flight, and the step to send when it stops
+    in_flight: HashMap<i64, Option<Step>>,
+}
+
+impl SteppingController {
+    pub fn request(&mut self, thread_id: i64, step: Step) -> StepRequest {
+        match self.in_flight.get_mut(&thread_id) {
+            Some(waiting) => {
+                *waiting = Some(step);
+                StepRequest::Queued
+            }
+            None => {
+                self.in_flight.insert(thread_id, None);
+                StepRequest::Send
+            }
+        }
+    }
+
+    // Called when `thread_id` stops. Returns the waiting step to send instead of reporting the
+    // stop; any other stop (a breakpoint hit mid-step, an exception, a pause) drops it.
+    pub fn stopped(&mut self, thread_id: i64, reason: &str) -> Option<Step> {
+        let waiting = self.in_flight.remove(&thread_id).flatten();
+        match waiting {
+            Some(step) if reason == "step" => {
+                self.in_flight.insert(thread_id, None);
+                Some(step)
+            }
+            _ => None,
+        }
+    }
+
+    // Forgets the thread's step, e.g. when sending it failed.
+    pub fn cancel(&mut self, thread_id: i64) {
+        self.in_flight.remove(&thread_id);
+    }
+
+    pub fn clear(&mut self) {
+        self.in_flight.clear();
+    }
+}
@@ -0,0 +1,108 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::debugger::protocol::StackFrame;
+
+// Stops kept per session; older ones are dropped.
+pub const MAX_STOPS: usize = 50;
+// Frames kept from the top of the stopped thread's stack.
+pub const SNAPSHOT_FRAMES: i64 = 10;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotFrame {
+    pub name: String,
+    pub file: Option<String>,
+    pub line: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchValue {
+    pub expression: String,
+    pub value: String,
+}
+
+// Where the program was at one stop and what the watch expressions showed there. Frame ids
+// aren't kept since they die as soon as the program resumes.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StopSnapshot {
+    // Numbered from 1 in order of stopping, stable as older stops are dropped
+    pub number: u64,
+    // Unix time in milliseconds
+    pub stopped_at: u64,
+    pub reason: String,
+    pub thread_id: i64,
+    pub thread_name: Option<String>,
+    pub frames: Vec<SnapshotFrame>,
+    pub watches: Vec<WatchValue>,
+}
+
+// The last MAX_STOPS stops of a session, oldest first.
+#[derive(Debug, Default)]
+pub struct StopHistory {
+    stops: VecDeque<StopSnapshot>,
+    recorded: u64,
+}
+
+impl StopHistory {
+    pub fn record(
+        &mut self,
+        reason: &str,
+        thread_id: i64,
+        thread_name: Option<String>,
+        frames: Vec<SnapshotFrame>,
+        watches: Vec<WatchValue>,
+    ) {
+        self.recorded += 1;
+        if self.stops.len() == MAX_STOPS {
+            self.stops.pop_front();
+        }
+        self.stops.push_back(StopSnapshot {
+            number: self.recorded,
+            stopped_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            reason: reason.to_string(),
+            thread_id,
+            thread_name,
+            frames,
+            watches,
+        });
+    }
+
+    // Stops recorded since the session started, including dropped ones
+    pub fn recorded(&self) -> u64 {
+        self.recorded
+    }
+
+    pub fn list(&self) -> Vec<StopSnapshot> {
+        self.stops.iter().cloned().collect()
+    }
+
+    // The stop `back` stops before the latest one (0 is the current stop).
+    pub fn get(&self, back: usize) -> Option<StopSnapshot> {
+        let index = self.stops.len().checked_sub(back + 1)?;
+        self.stops.get(index).cloned()
+    }
+
+    pub fn clear(&mut self) {
+        self.stops.clear();
+        self.recorded = 0;
+    }
+}
+
+// Frames of a "stackTrace" response body.
+pub fn frames_from_stack(frames: &[StackFrame]) -> Vec<SnapshotFrame> {
+    frames
+        .iter()
+        .map(|f| SnapshotFrame {
+            name: f.display_name(),
+            file: f.path().map(String::from),
+            line: f.line,
+        })
+        .collect()
+}
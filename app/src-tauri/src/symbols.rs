@@ -0,0 +1,186 @@
+use regex::Regex;
+use serde::Serialize;
+
+// Upper bound on results so a one-letter query doesn't flood the UI.
+const MAX_SYMBOL_RESULTS: usize = 200;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolMatch {
+    // Name to use for a function breakpoint
+    pub name: String,
+    pub module: Option<String>,
+    pub location: Option<String>,
+}
+
+// Characters with a meaning in POSIX extended regexes. Most are matched literally through a
+// one-character bracket expression, which means the same to lldb and Python; backslash escapes of
+// other characters are undefined in POSIX, and lldb's quoting unescapes some of them.
+const ERE_SPECIAL: &[char] = &['.', '(', ')', '*', '+', '?', '{', '|', '$'];
+
+// Turns a query into a case-insensitive subsequence regex, so "prsarg" finds "parse_args".
+// Case-insensitivity is spelled out with character classes because lldb's POSIX regexes have
+// no (?i) flag. Queries with '"' or '\' are refused: no symbol has them, and they'd end or
+// escape the quoted lldb argument.
+pub fn fuzzy_pattern(query: &str) -> Result<String, String> {
+    if query.contains(['"', '\\']) {
+        return Err("Symbol queries can't contain '\"' or '\\'".to_string());
+    }
+    Ok(query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| match c {
+            c if c.is_ascii_alphabetic() => {
+                format!("[{}{}]", c.to_ascii_lowercase(), c.to_ascii_uppercase())
+            }
+            // "[^]" would be a negated set and Python reads "[[]" as a nested one
+            '^' | '[' => format!("\\{}", c),
+            c if ERE_SPECIAL.contains(&c) => format!("[{}]", c),
+            c => c.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(".*"))
+}
+
+// lldb command run through lldb-dap's command escape prefix.
+pub fn lldb_lookup_command(query: &str) -> Result<String, String> {
+    Ok(format!("`image lookup -r -n \"{}\"", fuzzy_pattern(query)?))
+}
+
+// Parses `image lookup` output. Each match has a line like
+// "Summary: prog`prog::main::h1234 at main.rs:5:1".
+pub fn parse_lldb_lookup(output: &str) -> Vec<SymbolMatch> {
+    let summary = Regex::new(r"Summary:\s*([^`\s]+)`(.+?)(?: at (\S+))?\s*$").unwrap();
+    let mut matches: Vec<SymbolMatch> = Vec::new();
+    for line in output.lines() {
+        if let Some(caps) = summary.captures(line) {
+            let symbol = SymbolMatch {
+                name: strip_offset(&caps[2]).to_string(),
+                module: Some(caps[1].to_string()),
+                location: caps.get(3).map(|m| m.as_str().to_string()),
+            };
+            if !matches.contains(&symbol) {
+                matches.push(symbol);
+            }
+            if matches.len() >= MAX_SYMBOL_RESULTS {
+                break;
+            }
+        }
+    }
+    matches
+}
+
+// "main + 12" -> "main"
+fn strip_offset(symbol: &str) -> &str {
+    match symbol.rfind(" + ") {
+        Some(index) if symbol[index + 3..].chars().all(|c| c.is_ascii_digit()) => &symbol[..index],
+        _ => symbol,
+    }
+}
+
+// Python expression evaluated in the paused frame that lists matching functions of loaded
+// modules as a JSON array of [module, qualname] pairs.
+pub fn python_search_expression(query: &str) -> Result<String, String> {
+    Ok(format!(
+        "__import__('json').dumps(sorted({{(m.__name__, getattr(o, '__qualname__', n)) \
+         for m in list(__import__('sys').modules.values()) if getattr(m, '__file__', None) \
+         for n, o in list(vars(m).items()) \
+         if callable(o) and getattr(o, '__module__', None) == m.__name__ \
+         and __import__('re').search({:?}, n)}})[:{}])",
+        fuzzy_pattern(query)?,
+        MAX_SYMBOL_RESULTS
+    ))
+}
+
+// The evaluate result is the repr of the JSON string, e.g. '[["app", "main"]]'. Names only
+// contain identifier characters and dots, so stripping the quotes leaves valid JSON.
+pub fn parse_python_search(result: &str) -> Vec<SymbolMatch> {
+    let json = result.trim().trim_matches('\'');
+    let pairs: Vec<(String, String)> = serde_json::from_str(json).unwrap_or_default();
+    pairs
+        .into_iter()
+        .map(|(module, name)| SymbolMatch {
+            name,
+            module: Some(module),
+            location: None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_pattern_matches_subsequences_case_insensitively() {
+        let pattern = fuzzy_pattern("prs Arg").unwrap();
+        assert_eq!(pattern, "[pP].*[rR].*[sS].*[aA].*[rR].*[gG]");
+        let regex = Regex::new(&pattern).unwrap();
+        assert!(regex.is_match("parse_args"));
+        assert!(!regex.is_match("args_parse"));
+    }
+
+    #[test]
+    fn fuzzy_pattern_escapes_only_regex_metacharacters() {
+        assert_eq!(fuzzy_pattern("a.b").unwrap(), "[aA].*[.].*[bB]");
+        assert_eq!(fuzzy_pattern("^[$").unwrap(), r"\^.*\[.*[$]");
+        // Left alone: escaping them is undefined in POSIX regexes
+        assert_eq!(fuzzy_pattern("_:<&>-~#").unwrap(), "_.*:.*<.*&.*>.*-.*~.*#");
+
+        let regex = Regex::new(&fuzzy_pattern("Vec<T>::push(").unwrap()).unwrap();
+        assert!(regex.is_match("alloc::vec::Vec<T>::push(&mut self)"));
+    }
+
+    #[test]
+    fn fuzzy_pattern_refuses_quotes_and_backslashes() {
+        assert!(fuzzy_pattern("main\" -s").is_err());
+        assert!(fuzzy_pattern(r"a\b").is_err());
+        assert!(lldb_lookup_command("\"").is_err());
+    }
+
+    #[test]
+    fn lldb_lookup_command_quotes_the_pattern() {
+        assert_eq!(
+            lldb_lookup_command("m(").unwrap(),
+            "`image lookup -r -n \"[mM].*[(]\""
+        );
+    }
+
+    #[test]
+    fn parse_lldb_lookup_reads_summaries() {
+        let output = "\
+1 match found in /tmp/prog:
+        Address: prog[0x0000000100003f20] (prog.__TEXT.__text + 0)
+        Summary: prog`prog::main::h1234 at main.rs:5:1
+        Address: prog[0x0000000100003f40] (prog.__TEXT.__text + 32)
+        Summary: prog`prog::main::h1234 + 12 at main.rs:6:5
+        Summary: libsystem_c.dylib`printf";
+        assert_eq!(
+            parse_lldb_lookup(output),
+            vec![
+                SymbolMatch {
+                    name: "prog::main::h1234".to_string(),
+                    module: Some("prog".to_string()),
+                    location: Some("main.rs:5:1".to_string()),
+                },
+                SymbolMatch {
+                    name: "prog::main::h1234".to_string(),
+                    module: Some("prog".to_string()),
+                    location: Some("main.rs:6:5".to_string()),
+                },
+                SymbolMatch {
+                    name: "printf".to_string(),
+                    module: Some("libsystem_c.dylib".to_string()),
+                    location: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_lldb_lookup_drops_duplicates() {
+        let line = "Summary: prog`prog::run at lib.rs:1:1\n";
+        assert_eq!(parse_lldb_lookup(&line.repeat(3)).len(), 1);
+        assert!(parse_lldb_lookup("error: no symbols match").is_empty());
+    }
+}
@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+use crate::launch_progress::CANCELLED_MESSAGE;
+use crate::processes::{self, Wait};
+
+// How long a task may run when it doesn't set timeoutSecs
+const DEFAULT_TIMEOUT_SECS: u64 = 600;
+
+// A command run before the debuggee is started, e.g. `cargo build` or `npm run build`, so the
+// session doesn't debug a stale binary.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreLaunchTask {
+    // Shown in the output panel; defaults to the command line
+    pub label: Option<String>,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    // Defaults to the workspace root
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    // Killed, failing the launch, when it runs longer (10 minutes by default)
+    pub timeout_secs: Option<u64>,
+}
+
+impl PreLaunchTask {
+    pub fn display_name(&self) -> String {
+        match &self.label {
+            Some(label) => label.clone(),
+            None if self.args.is_empty() => self.command.clone(),
+            None => format!("{} {}", self.command, self.args.join(" ")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TaskStream {
+    Stdout,
+    Stderr,
+}
+
+impl TaskStream {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TaskStream::Stdout => "stdout",
+            TaskStream::Stderr => "stderr",
+        }
+    }
+}
+
+// Runs `task` to completion, handing each output line to `on_line` as it's printed. A task that
+// can't be started, exits unsuccessfully or times out is an error. Blocks until the task is done,
+// so async callers run it with spawn_blocking; once `cancelled` returns true it's killed.
+pub fn run(
+    task: &PreLaunchTask,
+    default_cwd: Option<&Path>,
+    cancelled: impl Fn() -> bool,
+    on_line: impl Fn(TaskStream, &str) + Send + Sync + 'static,
+) -> Result<(), String> {
+    let name = task.display_name();
+    let mut command = Command::new(&task.command);
+    processes::own_process_group(&mut command)
+        .args(&task.args)
+        .envs(&task.env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    match (&task.cwd, default_cwd) {
+        (Some(cwd), _) => {
+            command.current_dir(shellexpand::tilde(cwd).as_ref());
+        }
+        (None, Some(cwd)) => {
+            command.current_dir(cwd);
+        }
+        (None, None) => {}
+    }
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Failed to start task {}: {}", name, e))?;
+
+    // Both pipes are drained concurrently so a chatty stderr can't block the process
+    let on_line = std::sync::Arc::new(on_line);
+    let mut readers = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        let on_line = std::sync::Arc::clone(&on_line);
+        readers.push(thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                on_line(TaskStream::Stdout, &line);
+            }
+        }));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        let on_line = std::sync::Arc::clone(&on_line);
+        readers.push(thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                on_line(TaskStream::Stderr, &line);
+            }
+        }));
+    }
+
+    let timeout = Duration::from_secs(task.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS));
+    let wait = processes::wait_killable(&mut child, timeout, cancelled)
+        .map_err(|e| format!("Failed to wait for task {}: {}", name, e))?;
+    for reader in readers {
+        let _ = reader.join();
+    }
+
+    match wait {
+        Wait::Exited(status) if status.success() => Ok(()),
+        Wait::Exited(status) => Err(format!("Task {} failed ({})", name, status)),
+        Wait::Cancelled => Err(CANCELLED_MESSAGE.to_string()),
+        Wait::TimedOut => Err(format!(
+            "Task {} timed out after {}s",
+            name,
+            timeout.as_secs()
+        )),
+    }
+}
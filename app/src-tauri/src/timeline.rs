@@ -0,0 +1,52 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Upper bound on recorded entries so a long stepping session can't grow without limit.
+const MAX_TIMELINE_ENTRIES: usize = 10_000;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineEntry {
+    // Milliseconds since the session was launched
+    pub offset_ms: u64,
+    pub kind: String,
+    pub detail: Value,
+}
+
+// Ordered record of what happened in one debug session (launch, stops, steps, evaluations,
+// termination), exported as JSON so sessions can be documented or diffed across runs.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionTimeline {
+    // Unix time in milliseconds
+    pub started_at: u64,
+    pub entries: Vec<TimelineEntry>,
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+impl SessionTimeline {
+    pub fn new() -> Self {
+        SessionTimeline {
+            started_at: unix_millis(),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, kind: &str, detail: Value) {
+        if self.entries.len() >= MAX_TIMELINE_ENTRIES {
+            return;
+        }
+        self.entries.push(TimelineEntry {
+            offset_ms: unix_millis().saturating_sub(self.started_at),
+            kind: kind.to_string(),
+            detail,
+        });
+    }
+}
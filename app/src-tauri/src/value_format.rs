@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+// How much of a value adapters render before truncating, per session. Unset limits keep the
+// adapter's defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FormatLimits {
+    // Characters of a string summary (lldb's target.max-string-summary-length)
+    pub max_string_length: Option<u32>,
+    // Children shown for a collection (lldb's target.max-children-count)
+    pub max_children: Option<u32>,
+    // Integers in hexadecimal, via the DAP ValueFormat both adapters understand
+    pub hex: bool,
+}
+
+impl FormatLimits {
+    // The `format` argument for evaluate and variables requests.
+    pub fn dap_format(&self) -> Option<Value> {
+        self.hex.then(|| serde_json::json!({ "hex": true }))
+    }
+
+    // lldb settings applying the limits. debugpy has no equivalent per-request options, so for
+    // Python sessions only `hex` applies and fetch_full_value is the way to see everything.
+    pub fn lldb_commands(&self) -> Vec<String> {
+        let mut commands = Vec::new();
+        if let Some(length) = self.max_string_length {
+            commands.push(format!(
+                "settings set target.max-string-summary-length {}",
+                length
+            ));
+        }
+        if let Some(children) = self.max_children {
+            commands.push(format!(
+                "settings set target.max-children-count {}",
+                children
+            ));
+        }
+        commands
+    }
+}
+
+// A value fetched in full by fetch_full_value: the untruncated rendering and one page of its
+// children.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FullValue {
+    pub name: String,
+    pub value: String,
+    // The value as the project's renderer for its type shows it
+    pub rendered_value: Option<String>,
+    pub variables_reference: i64,
+    // Children from `start`, as DAP Variable objects
+    pub children: Vec<Value>,
+    pub start: u32,
+    // Total indexed children when the adapter reports it, for paging
+    pub indexed_children: Option<i64>,
+}
@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+// An expression watched by re-evaluating it at every stop, for adapters without data
+// breakpoints (debugpy). Stops where it hasn't changed are resumed automatically.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValueWatch {
+    pub expression: String,
+    // Step over line by line between checks rather than only checking at other stops
+    // (breakpoints). Catches every change, at the cost of one round trip per line.
+    #[serde(default)]
+    pub step: bool,
+    // Value at the last check; None until the first one
+    #[serde(default)]
+    pub last_value: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValueChange {
+    pub expression: String,
+    pub old_value: String,
+    pub new_value: String,
+}
+
+impl ValueWatch {
+    // Records the value seen at a stop, returning the change if it differs from the last one.
+    // Evaluation errors count as values, so a variable going in or out of scope is a change.
+    pub fn observe(&mut self, value: String) -> Option<ValueChange> {
+        let previous = self.last_value.replace(value.clone())?;
+        (previous != value).then(|| ValueChange {
+            expression: self.expression.clone(),
+            old_value: previous,
+            new_value: value,
+        })
+    }
+}
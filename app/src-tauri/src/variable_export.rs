@@ -0,0 +1,63 @@
+use serde_json::{Map, Value};
+
+// Nodes visited by one export at most, so a huge or cyclic structure can't run away
+pub const MAX_EXPORTED_NODES: usize = 10_000;
+
+// Entries adapters add next to a value's real members: debugpy's grouping nodes and `len()`,
+// lldb's raw view of synthetic children.
+pub fn is_synthetic(name: &str) -> bool {
+    matches!(
+        name,
+        "special variables"
+            | "function variables"
+            | "class variables"
+            | "protected variables"
+            | "len()"
+            | "[raw]"
+    )
+}
+
+// Converts a rendered leaf value to the closest JSON value: numbers, booleans, null and quoted
+// strings become native JSON; anything else is kept as its rendering.
+pub fn leaf_value(rendered: &str) -> Value {
+    let rendered = rendered.trim();
+    match rendered {
+        "True" | "true" => return Value::Bool(true),
+        "False" | "false" => return Value::Bool(false),
+        "None" | "null" | "nullptr" => return Value::Null,
+        _ => {}
+    }
+    if let Ok(int) = rendered.parse::<i64>() {
+        return Value::from(int);
+    }
+    if let Some(float) = rendered
+        .parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+    {
+        return Value::Number(float);
+    }
+    for quote in ['"', '\''] {
+        if rendered.len() >= 2 && rendered.starts_with(quote) && rendered.ends_with(quote) {
+            return Value::String(rendered[1..rendered.len() - 1].to_string());
+        }
+    }
+    Value::String(rendered.to_string())
+}
+
+// Builds a container from its named children. Children named by position ("0", "[0]", ...) in
+// order make an array; anything else an object.
+pub fn assemble(children: Vec<(String, Value)>) -> Value {
+    let is_sequence = !children.is_empty()
+        && children.iter().enumerate().all(|(i, (name, _))| {
+            name.trim_start_matches('[')
+                .trim_end_matches(']')
+                .parse::<usize>()
+                == Ok(i)
+        });
+    if is_sequence {
+        Value::Array(children.into_iter().map(|(_, value)| value).collect())
+    } else {
+        Value::Object(children.into_iter().collect::<Map<String, Value>>())
+    }
+}
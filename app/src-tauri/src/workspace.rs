@@ -0,0 +1,222 @@
+use parking_lot::RwLock;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+// The directory the user opened. Filesystem commands coming from the webview are only allowed
+// to touch paths inside it, or inside one of the user's explicitly allowlisted directories.
+#[derive(Default)]
+pub struct Workspace {
+    root: RwLock<Option<PathBuf>>,
+}
+
+impl Workspace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Registers `path` as the workspace root and returns its canonical form. Callers get `path`
+    // from the user (see open_workspace), never straight from the webview.
+    pub fn open(&self, path: &str) -> Result<PathBuf, String> {
+        let root = check_root(path)?;
+        *self.root.write() = Some(root.clone());
+        Ok(root)
+    }
+
+    pub fn root(&self) -> Option<PathBuf> {
+        self.root.read().clone()
+    }
+
+    // Normalizes `path` and checks it lies inside the workspace or an allowlisted directory.
+    // The path need not exist yet (export targets), but its parent directory must.
+    pub fn resolve(&self, path: &str, allowlist: &[String]) -> Result<PathBuf, String> {
+        let resolved = normalize(path)?;
+        let root = self.root();
+
+        let allowed = root.iter().cloned().chain(
+            allowlist
+                .iter()
+                .filter_map(|dir| fs::canonicalize(shellexpand::tilde(dir).as_ref()).ok()),
+        );
+        for dir in allowed {
+            if resolved.starts_with(&dir) {
+                return Ok(resolved);
+            }
+        }
+
+        Err(match root {
+            Some(root) => format!(
+                "Access denied: {} is outside the workspace {}",
+                resolved.display(),
+                root.display()
+            ),
+            None => format!(
+                "Access denied: no workspace is open (requested {})",
+                resolved.display()
+            ),
+        })
+    }
+}
+
+// Canonicalizes a prospective workspace root. The filesystem root and the home directory are
+// refused: confined to either, the webview could reach nearly every file the user has.
+pub fn check_root(path: &str) -> Result<PathBuf, String> {
+    let expanded = shellexpand::tilde(path).into_owned();
+    let root = fs::canonicalize(&expanded)
+        .map_err(|e| format!("Cannot open workspace {}: {}", expanded, e))?;
+    if !root.is_dir() {
+        return Err(format!("Workspace {} is not a directory", root.display()));
+    }
+    let home = fs::canonicalize(shellexpand::tilde("~").as_ref()).ok();
+    if root.parent().is_none() || home.as_ref() == Some(&root) {
+        return Err(format!(
+            "{} is too broad for a workspace; open a project directory",
+            root.display()
+        ));
+    }
+    Ok(root)
+}
+
+// Expands `~` and resolves symlinks and `..`. For a path that doesn't exist yet the parent is
+// canonicalized and the final component is re-attached.
+fn normalize(path: &str) -> Result<PathBuf, String> {
+    let expanded = PathBuf::from(shellexpand::tilde(path).into_owned());
+    if !expanded.is_absolute() {
+        return Err(format!("Path must be absolute: {}", path));
+    }
+    if let Ok(resolved) = fs::canonicalize(&expanded) {
+        return Ok(resolved);
+    }
+
+    let name = match expanded.components().next_back() {
+        Some(Component::Normal(name)) => name.to_owned(),
+        _ => return Err(format!("Invalid path: {}", path)),
+    };
+    let parent = expanded.parent().unwrap_or(Path::new("/"));
+    let parent = fs::canonicalize(parent).map_err(|e| format!("Invalid path {}: {}", path, e))?;
+    Ok(parent.join(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A fresh directory under the system temp dir, removed when dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "wayfind-workspace-{}-{}",
+                std::process::id(),
+                name
+            ));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            TempDir(fs::canonicalize(&dir).unwrap())
+        }
+
+        fn path(&self, relative: &str) -> String {
+            self.0.join(relative).to_string_lossy().into_owned()
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn open(dir: &TempDir) -> Workspace {
+        fs::create_dir_all(dir.0.join("project/src")).unwrap();
+        fs::write(dir.0.join("project/src/main.rs"), "fn main() {}").unwrap();
+        let workspace = Workspace::new();
+        workspace.open(&dir.path("project")).unwrap();
+        workspace
+    }
+
+    #[test]
+    fn resolve_allows_paths_inside_the_root() {
+        let dir = TempDir::new("inside");
+        let workspace = open(&dir);
+        assert_eq!(
+            workspace.resolve(&dir.path("project/src/main.rs"), &[]),
+            Ok(dir.0.join("project/src/main.rs"))
+        );
+    }
+
+    #[test]
+    fn resolve_refuses_dot_dot_traversal() {
+        let dir = TempDir::new("traversal");
+        let workspace = open(&dir);
+        fs::write(dir.0.join("secret"), "").unwrap();
+        assert!(workspace
+            .resolve(&dir.path("project/src/../../secret"), &[])
+            .is_err());
+        // Also for a file that doesn't exist yet
+        assert!(workspace
+            .resolve(&dir.path("project/../new.json"), &[])
+            .is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn resolve_follows_symlinks_out_of_the_root() {
+        let dir = TempDir::new("symlink");
+        let workspace = open(&dir);
+        fs::create_dir(dir.0.join("outside")).unwrap();
+        fs::write(dir.0.join("outside/secret"), "").unwrap();
+        std::os::unix::fs::symlink(dir.0.join("outside"), dir.0.join("project/link")).unwrap();
+        assert!(workspace
+            .resolve(&dir.path("project/link/secret"), &[])
+            .is_err());
+        assert!(workspace
+            .resolve(&dir.path("project/link/new.json"), &[])
+            .is_err());
+    }
+
+    #[test]
+    fn resolve_allows_allowlisted_directories() {
+        let dir = TempDir::new("allowlist");
+        let workspace = open(&dir);
+        fs::create_dir(dir.0.join("exports")).unwrap();
+        let allowlist = [dir.path("exports")];
+        assert_eq!(
+            workspace.resolve(&dir.path("exports/timeline.json"), &allowlist),
+            Ok(dir.0.join("exports/timeline.json"))
+        );
+        assert!(workspace
+            .resolve(&dir.path("elsewhere.json"), &allowlist)
+            .is_err());
+    }
+
+    #[test]
+    fn resolve_accepts_export_targets_that_dont_exist_yet() {
+        let dir = TempDir::new("export");
+        let workspace = open(&dir);
+        assert_eq!(
+            workspace.resolve(&dir.path("project/report.html"), &[]),
+            Ok(dir.0.join("project/report.html"))
+        );
+        // The parent has to exist
+        assert!(workspace
+            .resolve(&dir.path("project/missing/report.html"), &[])
+            .is_err());
+    }
+
+    #[test]
+    fn resolve_needs_an_open_workspace() {
+        let dir = TempDir::new("closed");
+        fs::write(dir.0.join("file"), "").unwrap();
+        assert!(Workspace::new().resolve(&dir.path("file"), &[]).is_err());
+    }
+
+    #[test]
+    fn check_root_refuses_the_filesystem_root_and_home() {
+        assert!(check_root("/").is_err());
+        if fs::canonicalize(shellexpand::tilde("~").as_ref()).is_ok() {
+            assert!(check_root("~").is_err());
+        }
+        let dir = TempDir::new("root");
+        assert_eq!(check_root(&dir.path("")), Ok(dir.0.clone()));
+    }
+}
@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+
+// The features an adapter reported in its initialize response. Anything it left out is
+// unsupported, so every flag defaults to false.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Capabilities {
+    pub supports_configuration_done_request: bool,
+    pub supports_function_breakpoints: bool,
+    pub supports_conditional_breakpoints: bool,
+    pub supports_hit_conditional_breakpoints: bool,
+    pub supports_log_points: bool,
+    pub supports_data_breakpoints: bool,
+    pub supports_instruction_breakpoints: bool,
+    pub supports_breakpoint_locations_request: bool,
+    pub supports_exception_options: bool,
+    pub supports_exception_filter_options: bool,
+    pub supports_exception_info_request: bool,
+    pub supports_evaluate_for_hovers: bool,
+    pub supports_step_back: bool,
+    pub supports_stepping_granularity: bool,
+    pub supports_single_thread_execution_requests: bool,
+    pub supports_set_variable: bool,
+    pub supports_set_expression: bool,
+    pub supports_restart_frame: bool,
+    pub supports_goto_targets_request: bool,
+    pub supports_step_in_targets_request: bool,
+    pub supports_completions_request: bool,
+    pub supports_modules_request: bool,
+    pub supports_loaded_sources_request: bool,
+    pub supports_restart_request: bool,
+    pub supports_terminate_request: bool,
+    pub supports_terminate_threads_request: bool,
+    pub support_terminate_debuggee: bool,
+    pub support_suspend_debuggee: bool,
+    pub supports_delayed_stack_trace_loading: bool,
+    pub supports_value_formatting_options: bool,
+    pub supports_read_memory_request: bool,
+    pub supports_write_memory_request: bool,
+    pub supports_disassemble_request: bool,
+    pub supports_cancel_request: bool,
+    pub supports_clipboard_context: bool,
+}
+
+impl Capabilities {
+    // Parses the body of an initialize response. A missing or malformed body means the adapter
+    // supports nothing optional.
+    pub fn from_body(body: Option<&serde_json::Value>) -> Self {
+        body.and_then(|b| serde_json::from_value(b.clone()).ok())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_initialize_response_bodies() {
+        // Trimmed from debugpy's initialize response
+        let body = json!({
+            "supportsCompletionsRequest": true,
+            "supportsConditionalBreakpoints": true,
+            "supportsConfigurationDoneRequest": true,
+            "supportsLogPoints": true,
+            "supportTerminateDebuggee": true,
+            "exceptionBreakpointFilters": [{"filter": "raised", "label": "Raised Exceptions"}],
+            "supportsStepBack": false
+        });
+        let capabilities = Capabilities::from_body(Some(&body));
+        assert!(capabilities.supports_conditional_breakpoints);
+        assert!(capabilities.support_terminate_debuggee);
+        assert!(!capabilities.supports_step_back);
+        assert!(!capabilities.supports_data_breakpoints);
+
+        assert_eq!(Capabilities::from_body(None), Capabilities::default());
+        let malformed = json!({"supportsStepBack": "yes"});
+        assert_eq!(
+            Capabilities::from_body(Some(&malformed)),
+            Capabilities::default()
+        );
+    }
+}
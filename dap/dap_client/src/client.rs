@@ -0,0 +1,1143 @@
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::TcpStream;
+use std::process::Child;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{debug, error, trace, warn};
+
+use crate::events::DapEvent;
+use crate::message::{BreakpointInput, Checksum, DAPMessage, FunctionBreakpointInput, MessageType};
+use crate::retry::RetryPolicy;
+use crate::schema::SchemaValidator;
+use crate::transport::{read_message, write_message};
+
+// Locks a std mutex, recovering the guard if a previous holder panicked. The data behind these
+// locks (sequence counters, message maps, the stream) stays usable after a panic elsewhere, so
+// recovering keeps one failed request from poisoning every later one.
+pub fn lock_or_recover<T: ?Sized>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// Receives everything the client observes. The receiver thread calls these as messages arrive,
+// so implementations must not block on requests to the same client.
+pub trait EventSink: Send + Sync {
+    // Every parsed message, before responses are handed to the waiting request.
+    fn on_message(&self, _message: &DAPMessage) {}
+    // Events, parsed once into their typed form, right after on_message.
+    fn on_event(&self, _event: &DapEvent, _message: &DAPMessage) {}
+    // A response to `command`, `elapsed` after its request was sent.
+    fn on_response(&self, _command: &str, _elapsed: Duration, _response: &DAPMessage) {}
+    fn on_timeout(&self, _command: &str, _seq: i32, _timeout_secs: f64) {}
+    // The stream failed; the receiver stops after this.
+    fn on_transport_error(&self, _error: &std::io::Error) {}
+    // The connection closed or failed while the session was still on: no disconnect or
+    // terminate had been sent and the client wasn't dropped. Called after on_transport_error.
+    fn on_connection_lost(&self) {}
+    // A reverse request from the adapter, e.g. startDebugging. The result is sent back as the
+    // response: a body on success, an error message on failure. Must answer without waiting on
+    // the adapter.
+    fn on_request(&self, request: &DAPMessage) -> Result<Option<serde_json::Value>, String> {
+        Err(format!(
+            "Unsupported request {}",
+            request.command.as_deref().unwrap_or("")
+        ))
+    }
+}
+
+// Logs where `message` breaks the DAP schema. `direction` is "-->" for sent messages and "<--"
+// for received ones, as in the other message logs.
+fn log_violations(schema: &SchemaValidator, message: &serde_json::Value, direction: &str) {
+    let violations = schema.validate(message);
+    if violations.is_empty() {
+        return;
+    }
+    let definition = schema.definition_for(message);
+    for violation in violations {
+        warn!(
+            direction,
+            seq = message.get("seq").and_then(|s| s.as_i64()),
+            path = %violation.path,
+            "DAP schema violation in {}: {}",
+            definition,
+            violation.message
+        );
+    }
+}
+
+// Sink for callers that only use wait_for_response/wait_for_event.
+pub struct NoopSink;
+
+impl EventSink for NoopSink {}
+
+type Reader = Box<dyn BufRead + Send>;
+type Writer = Box<dyn Write + Send>;
+type PendingCommands = Arc<Mutex<HashMap<i32, (String, Instant)>>>;
+
+// Assigns the next request sequence number.
+fn take_seq(next_seq: &Mutex<i32>) -> i32 {
+    let mut seq_lock = lock_or_recover(next_seq);
+    let current = *seq_lock;
+    *seq_lock += 1;
+    current
+}
+
+// A request still waiting for its response.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InFlightRequest {
+    pub seq: i32,
+    pub command: String,
+    pub elapsed_ms: u64,
+}
+
+// Cancels a client's requests from outside it. Callers usually hold the client locked while a
+// request is in flight, so cancelling can't go through the client itself.
+#[derive(Clone)]
+pub struct RequestCanceller {
+    writer: Arc<Mutex<Writer>>,
+    next_seq: Arc<Mutex<i32>>,
+    pending_commands: PendingCommands,
+    responses: Arc<Mutex<HashMap<i32, DAPMessage>>>,
+    cancelled: Arc<Mutex<HashSet<i32>>>,
+}
+
+impl RequestCanceller {
+    // Requests still waiting for a response, oldest first.
+    pub fn in_flight(&self) -> Vec<InFlightRequest> {
+        let mut requests: Vec<InFlightRequest> = lock_or_recover(&self.pending_commands)
+            .iter()
+            .map(|(seq, (command, sent_at))| InFlightRequest {
+                seq: *seq,
+                command: command.clone(),
+                elapsed_ms: sent_at.elapsed().as_millis() as u64,
+            })
+            .collect();
+        requests.sort_by_key(|r| r.seq);
+        requests
+    }
+
+    // Stops waiting for request `request_seq`: its caller gets a failed response with message
+    // "cancelled" right away. With `notify_adapter` (for adapters that report
+    // supportsCancelRequest) the adapter is also sent a cancel request so it can abandon the
+    // work. Returns false if the request wasn't in flight.
+    pub async fn cancel(&self, request_seq: i32, notify_adapter: bool) -> std::io::Result<bool> {
+        if !lock_or_recover(&self.pending_commands).contains_key(&request_seq) {
+            return Ok(false);
+        }
+        lock_or_recover(&self.cancelled).insert(request_seq);
+        if !notify_adapter {
+            return Ok(true);
+        }
+
+        let mut message = DAPMessage::request(
+            "cancel",
+            Some(serde_json::json!({ "requestId": request_seq })),
+        );
+        message.seq = take_seq(&self.next_seq);
+        let seq = message.seq;
+        lock_or_recover(&self.pending_commands).insert(seq, ("cancel".to_string(), Instant::now()));
+        write_message(&mut **lock_or_recover(&self.writer), &message)?;
+
+        // Collect the acknowledgement so it doesn't linger in the response map
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(2) {
+            let response = lock_or_recover(&self.responses).remove(&seq);
+            if response.is_some() {
+                return Ok(true);
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        lock_or_recover(&self.pending_commands).remove(&seq);
+        Ok(true)
+    }
+}
+
+pub struct DAPClient {
+    // The writer is used to send messages.
+    writer: Option<Arc<Mutex<Writer>>>,
+    // The reader is only used by the receiver thread.
+    reader: Option<Arc<Mutex<Reader>>>,
+    // next_seq generates unique sequence numbers for requests.
+    next_seq: Arc<Mutex<i32>>,
+    // responses: when we receive a Response message, we store it here by its request_seq.
+    responses: Arc<Mutex<HashMap<i32, DAPMessage>>>,
+    // pending_commands: the command name and send time of each request still awaiting a response, by seq.
+    pending_commands: PendingCommands,
+    // Requests given up on through a RequestCanceller, until their waiter notices
+    cancelled: Arc<Mutex<HashSet<i32>>>,
+    // events: when we receive an Event (e.g. "initialized", "terminated"), we store them here.
+    events: Arc<Mutex<HashMap<String, Vec<DAPMessage>>>>,
+    // receiver_handle: the join handle for the receiver thread.
+    receiver_handle: Option<thread::JoinHandle<()>>,
+    sink: Arc<dyn EventSink>,
+    // Checks every message sent and received against the DAP schema when set
+    schema: Option<&'static SchemaValidator>,
+    // Set once the connection is expected to close, so the receiver doesn't report it lost
+    closing: Arc<AtomicBool>,
+}
+
+impl Default for DAPClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The receiver thread outlives the client; whatever happens to the connection after this isn't
+// news to anyone.
+impl Drop for DAPClient {
+    fn drop(&mut self) {
+        self.closing.store(true, Ordering::SeqCst);
+    }
+}
+
+impl DAPClient {
+    pub fn new() -> Self {
+        Self {
+            writer: None,
+            reader: None,
+            next_seq: Arc::new(Mutex::new(1)),
+            responses: Arc::new(Mutex::new(HashMap::new())),
+            pending_commands: Arc::new(Mutex::new(HashMap::new())),
+            cancelled: Arc::new(Mutex::new(HashSet::new())),
+            events: Arc::new(Mutex::new(HashMap::new())),
+            receiver_handle: None,
+            sink: Arc::new(NoopSink),
+            schema: None,
+            closing: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    // Developer mode: log every message that doesn't match the bundled DAP schema, with the
+    // offending field. Must be enabled before start_receiver to cover incoming messages.
+    pub fn enable_schema_validation(&mut self) {
+        self.schema = Some(SchemaValidator::bundled());
+    }
+
+    // Connect over TCP: clone the stream so that one instance is used for writing and one for reading.
+    pub fn connect(&mut self, host: &str, port: u16) -> std::io::Result<()> {
+        let stream = TcpStream::connect((host, port))?;
+        let writer = stream.try_clone()?;
+        self.connect_streams(BufReader::new(stream), writer);
+        Ok(())
+    }
+
+    // Like connect, retrying with backoff while the adapter isn't listening yet. `on_retry` is
+    // told about each failed attempt that will be retried.
+    pub async fn connect_with_retry(
+        &mut self,
+        host: &str,
+        port: u16,
+        policy: &RetryPolicy,
+        mut on_retry: impl FnMut(u32, &std::io::Error),
+    ) -> std::io::Result<()> {
+        let mut attempt = 1;
+        loop {
+            match self.connect(host, port) {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt >= policy.attempts => return Err(e),
+                Err(e) => {
+                    on_retry(attempt, &e);
+                    tokio::time::sleep(policy.delay(attempt)).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    // Speak DAP over an adapter's stdin/stdout, for adapters spawned without a port. Both pipes
+    // are taken from `child`; its stderr is left to the caller.
+    pub fn connect_child(&mut self, child: &mut Child) -> std::io::Result<()> {
+        let not_piped = |stream| {
+            std::io::Error::new(
+                ErrorKind::NotConnected,
+                format!("Adapter {} is not piped", stream),
+            )
+        };
+        let stdout = child.stdout.take().ok_or_else(|| not_piped("stdout"))?;
+        let stdin = child.stdin.take().ok_or_else(|| not_piped("stdin"))?;
+        self.connect_streams(BufReader::new(stdout), stdin);
+        Ok(())
+    }
+
+    // Use an arbitrary transport, e.g. an adapter's stdin/stdout.
+    pub fn connect_streams<R, W>(&mut self, reader: R, writer: W)
+    where
+        R: BufRead + Send + 'static,
+        W: Write + Send + 'static,
+    {
+        self.writer = Some(Arc::new(Mutex::new(Box::new(writer))));
+        self.reader = Some(Arc::new(Mutex::new(Box::new(reader))));
+        self.closing.store(false, Ordering::SeqCst);
+    }
+
+    // A handle for cancelling this client's requests while it's borrowed elsewhere. None until
+    // the client is connected.
+    pub fn canceller(&self) -> Option<RequestCanceller> {
+        Some(RequestCanceller {
+            writer: Arc::clone(self.writer.as_ref()?),
+            next_seq: Arc::clone(&self.next_seq),
+            pending_commands: Arc::clone(&self.pending_commands),
+            responses: Arc::clone(&self.responses),
+            cancelled: Arc::clone(&self.cancelled),
+        })
+    }
+
+    // send_message: assigns a sequence number, serializes the message along with a header, and writes it to the stream.
+    // Returns the assigned sequence number.
+    pub fn send_message(&self, mut message: DAPMessage) -> std::io::Result<i32> {
+        let seq = take_seq(&self.next_seq);
+        message.seq = seq;
+        if let Some(ref command) = message.command {
+            lock_or_recover(&self.pending_commands).insert(seq, (command.clone(), Instant::now()));
+            // The adapter may hang up as soon as it has answered these
+            if matches!(command.as_str(), "disconnect" | "terminate") {
+                self.closing.store(true, Ordering::SeqCst);
+            }
+        }
+
+        let writer = self.writer.as_ref().ok_or_else(|| {
+            std::io::Error::new(ErrorKind::NotConnected, "Stream is not connected")
+        })?;
+        if let Some(schema) = self.schema {
+            if let Ok(value) = serde_json::to_value(&message) {
+                log_violations(schema, &value, "-->");
+            }
+        }
+        write_message(&mut **lock_or_recover(writer), &message)?;
+
+        Ok(seq)
+    }
+
+    // start_receiver: spawns a dedicated thread to continuously read incoming messages, reporting
+    // each one to `sink`.
+    pub fn start_receiver(&mut self, sink: Arc<dyn EventSink>) -> std::io::Result<()> {
+        let reader_arc = Arc::clone(self.reader.as_ref().ok_or_else(|| {
+            std::io::Error::new(ErrorKind::NotConnected, "Stream is not connected")
+        })?);
+        let responses_arc = Arc::clone(&self.responses);
+        let pending_arc = Arc::clone(&self.pending_commands);
+        let events_arc = Arc::clone(&self.events);
+        // Reverse requests are answered from the receiver thread
+        let writer_arc = self.writer.as_ref().map(Arc::clone);
+        let seq_arc = Arc::clone(&self.next_seq);
+        let schema = self.schema;
+        let closing = Arc::clone(&self.closing);
+        self.sink = Arc::clone(&sink);
+        // Carry the caller's span (e.g. the session) over to the receiver thread
+        let span = tracing::Span::current();
+
+        self.receiver_handle = Some(thread::spawn(move || {
+            span.in_scope(|| loop {
+                let message_str = match read_message(&mut **lock_or_recover(&reader_arc)) {
+                    Ok(Some(message_str)) => message_str,
+                    // Connection closed.
+                    Ok(None) => {
+                        if !closing.load(Ordering::SeqCst) {
+                            warn!("Adapter closed the connection");
+                            sink.on_connection_lost();
+                        }
+                        return;
+                    }
+                    Err(e) if e.kind() == ErrorKind::InvalidData => {
+                        warn!("Skipping malformed message: {}", e);
+                        continue;
+                    }
+                    Err(e) => {
+                        error!("Error reading message: {}", e);
+                        sink.on_transport_error(&e);
+                        if !closing.load(Ordering::SeqCst) {
+                            sink.on_connection_lost();
+                        }
+                        return;
+                    }
+                };
+
+                trace!(payload = %message_str, "<-- payload");
+                if let Some(schema) = schema {
+                    if let Ok(value) = serde_json::from_str::<serde_json::Value>(&message_str) {
+                        log_violations(schema, &value, "<--");
+                    }
+                }
+
+                match serde_json::from_str::<DAPMessage>(&message_str) {
+                    Ok(msg) => {
+                        debug!(
+                            seq = msg.seq,
+                            request_seq = msg.request_seq,
+                            command = msg.command.as_deref(),
+                            event = msg.event.as_deref(),
+                            "<-- received"
+                        );
+                        sink.on_message(&msg);
+                        if let Some(event) = DapEvent::parse(&msg) {
+                            sink.on_event(&event, &msg);
+                        }
+
+                        match msg.message_type {
+                            MessageType::Response => {
+                                if let Some(req_seq) = msg.request_seq {
+                                    if let Some((command, sent_at)) =
+                                        lock_or_recover(&pending_arc).remove(&req_seq)
+                                    {
+                                        sink.on_response(&command, sent_at.elapsed(), &msg);
+                                    }
+                                    lock_or_recover(&responses_arc).insert(req_seq, msg);
+                                }
+                            }
+                            MessageType::Event => {
+                                if let Some(evt) = msg.event.clone() {
+                                    lock_or_recover(&events_arc)
+                                        .entry(evt)
+                                        .or_default()
+                                        .push(msg);
+                                }
+                            }
+                            MessageType::Request => {
+                                let mut response =
+                                    DAPMessage::response(&msg, sink.on_request(&msg));
+                                response.seq = take_seq(&seq_arc);
+                                if let Some(writer) = &writer_arc {
+                                    if let Err(e) =
+                                        write_message(&mut **lock_or_recover(writer), &response)
+                                    {
+                                        warn!("Failed to answer {:?}: {}", msg.command, e);
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    Err(_) => warn!("Error parsing message: {}", message_str),
+                }
+
+                // Don't busy‐spin.
+                thread::sleep(Duration::from_millis(10));
+            })
+        }));
+        Ok(())
+    }
+
+    // wait_for_response: polls the internal responses HashMap until the response with the given sequence is available,
+    // or the timeout expires.
+    pub async fn wait_for_response(&self, seq: i32, timeout_secs: f64) -> Option<DAPMessage> {
+        let start = Instant::now();
+        while start.elapsed().as_secs_f64() < timeout_secs {
+            // Bind first so the guard is released before the await below
+            let response = lock_or_recover(&self.responses).remove(&seq);
+            if response.is_some() {
+                return response;
+            }
+            if lock_or_recover(&self.cancelled).remove(&seq) {
+                let command = lock_or_recover(&self.pending_commands)
+                    .remove(&seq)
+                    .map(|(command, _)| command);
+                return Some(DAPMessage {
+                    seq: 0,
+                    message_type: MessageType::Response,
+                    command,
+                    request_seq: Some(seq),
+                    success: Some(false),
+                    message: Some("cancelled".to_string()),
+                    body: None,
+                    event: None,
+                    arguments: None,
+                });
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        let command = lock_or_recover(&self.pending_commands)
+            .remove(&seq)
+            .map(|(command, _)| command)
+            .unwrap_or_else(|| "unknown".to_string());
+        self.sink.on_timeout(&command, seq, timeout_secs);
+        None
+    }
+
+    // request: sends `command` and waits for its response. This is what every typed request
+    // below goes through.
+    pub async fn request(
+        &self,
+        command: &str,
+        arguments: Option<serde_json::Value>,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        let seq = self.send_message(DAPMessage::request(command, arguments))?;
+        match self.wait_for_response(seq, 10.0).await {
+            Some(response) => Ok(response),
+            None => Err(format!("Timeout waiting for {} response", command).into()),
+        }
+    }
+
+    // wait_for_event: polls for an event by its name until it arrives or the timeout expires.
+    pub fn wait_for_event(&self, name: &str, timeout_secs: f64) -> Option<DAPMessage> {
+        let start = Instant::now();
+        while start.elapsed().as_secs_f64() < timeout_secs {
+            if let Some(list) = lock_or_recover(&self.events).get_mut(name) {
+                if !list.is_empty() {
+                    return Some(list.remove(0));
+                }
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        None
+    }
+
+    // wait_for_event_async: like wait_for_event, without blocking the async runtime while it
+    // polls.
+    pub async fn wait_for_event_async(&self, name: &str, timeout_secs: f64) -> Option<DAPMessage> {
+        let start = Instant::now();
+        while start.elapsed().as_secs_f64() < timeout_secs {
+            // Bind first so the guard is released before the await below
+            let event = lock_or_recover(&self.events)
+                .get_mut(name)
+                .filter(|list| !list.is_empty())
+                .map(|list| list.remove(0));
+            if event.is_some() {
+                return event;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        None
+    }
+
+    // initialize: sends an "initialize" request and then waits for its response.
+    pub async fn initialize(&self) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        self.request(
+            "initialize",
+            Some(serde_json::json!({
+                "adapterID": "python",
+                "clientID": "dap_test_client",
+                "clientName": "DAP Test",
+                "linesStartAt1": true,
+                "columnsStartAt1": true,
+                "pathFormat": "path",
+                "supportsVariableType": true,
+                "supportsEvaluateForHovers": true,
+                "supportsStartDebuggingRequest": true
+            })),
+        )
+        .await
+    }
+
+    // attach: sends an "attach" request.
+    pub async fn attach(&self, host: &str, port: u16) -> Result<i32, Box<dyn std::error::Error>> {
+        self.attach_with(host, port, serde_json::Map::new()).await
+    }
+
+    // attach_with: like attach, with adapter-specific arguments (e.g. debugpy's autoReload)
+    // merged into the request. Returns the request's seq without waiting for the response:
+    // adapters like debugpy only answer once configuration is done, so callers wait for the
+    // "initialized" event first.
+    pub async fn attach_with(
+        &self,
+        host: &str,
+        port: u16,
+        extra: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<i32, Box<dyn std::error::Error>> {
+        let arguments = Self::attach_arguments(host, port, extra);
+        Ok(self.send_message(DAPMessage::request("attach", Some(arguments)))?)
+    }
+
+    // The arguments attach_with sends, for callers that send the request themselves. There's no
+    // stopOnEntry: debugpy only honours it when launching, so callers wanting to stop at entry
+    // set a breakpoint there instead.
+    pub fn attach_arguments(
+        host: &str,
+        port: u16,
+        extra: serde_json::Map<String, serde_json::Value>,
+    ) -> serde_json::Value {
+        let mut arguments = serde_json::json!({
+            "host": host,
+            "port": port,
+        });
+        if let Some(map) = arguments.as_object_mut() {
+            map.extend(extra);
+        }
+        arguments
+    }
+
+    // configuration_done: sends a "configurationDone" request and waits for its response.
+    pub async fn configuration_done(&self) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        self.request("configurationDone", Some(serde_json::json!({})))
+            .await
+    }
+
+    // set_breakpoints: sends a "setBreakpoints" request and waits for its response.
+    pub async fn set_breakpoints(
+        &self,
+        file_path: String,
+        breakpoints: Vec<BreakpointInput>,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        self.set_breakpoints_checked(file_path, breakpoints, Vec::new(), false)
+            .await
+    }
+
+    // set_breakpoints_checked: like set_breakpoints, also sending the file's checksums and whether
+    // it has unsaved changes, so the adapter can tell when the running code doesn't match it.
+    pub async fn set_breakpoints_checked(
+        &self,
+        file_path: String,
+        breakpoints: Vec<BreakpointInput>,
+        checksums: Vec<Checksum>,
+        source_modified: bool,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        let mut source = serde_json::json!({
+            "path": file_path,
+            "name": file_path.split('/').next_back().unwrap_or("unknown")
+        });
+        if !checksums.is_empty() {
+            source["checksums"] = serde_json::json!(checksums);
+        }
+        self.set_breakpoints_in(source, breakpoints, source_modified)
+            .await
+    }
+
+    // set_breakpoints_by_reference: like set_breakpoints, for a source the adapter only knows by
+    // its sourceReference (generated code, modules imported from a zip, ...).
+    pub async fn set_breakpoints_by_reference(
+        &self,
+        source_reference: i64,
+        name: Option<String>,
+        breakpoints: Vec<BreakpointInput>,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        let mut source = serde_json::json!({ "sourceReference": source_reference });
+        if let Some(name) = name {
+            source["name"] = serde_json::Value::from(name);
+        }
+        self.set_breakpoints_in(source, breakpoints, false).await
+    }
+
+    async fn set_breakpoints_in(
+        &self,
+        source: serde_json::Value,
+        breakpoints: Vec<BreakpointInput>,
+        source_modified: bool,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        self.request(
+            "setBreakpoints",
+            Some(serde_json::json!({
+                "source": source,
+                "breakpoints": breakpoints
+                    .iter()
+                    .map(BreakpointInput::source_breakpoint)
+                    .collect::<Vec<_>>(),
+                "sourceModified": source_modified
+            })),
+        )
+        .await
+    }
+
+    // source: fetches the content of a source the adapter provides by reference.
+    pub async fn source(
+        &self,
+        source_reference: i64,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        self.request(
+            "source",
+            Some(serde_json::json!({
+                "source": { "sourceReference": source_reference },
+                "sourceReference": source_reference
+            })),
+        )
+        .await
+    }
+
+    // loaded_sources: lists every source the adapter has loaded so far, including ones only
+    // available by sourceReference. Only valid when the adapter reported
+    // supportsLoadedSourcesRequest.
+    pub async fn loaded_sources(&self) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        self.request("loadedSources", Some(serde_json::json!({})))
+            .await
+    }
+
+    // set_function_breakpoints: replaces all function breakpoints and waits for the response.
+    pub async fn set_function_breakpoints(
+        &self,
+        breakpoints: Vec<FunctionBreakpointInput>,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        self.request(
+            "setFunctionBreakpoints",
+            Some(serde_json::json!({
+                "breakpoints": breakpoints
+            })),
+        )
+        .await
+    }
+
+    // set_exception_breakpoints: replaces the enabled exception filters (ids from the adapter's
+    // exceptionBreakpointFilters capability, e.g. debugpy's "raised") and waits for the response.
+    pub async fn set_exception_breakpoints(
+        &self,
+        filters: Vec<String>,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        self.request(
+            "setExceptionBreakpoints",
+            Some(serde_json::json!({
+                "filters": filters
+            })),
+        )
+        .await
+    }
+
+    // stack_trace: sends a "stackTrace" request for the top frame and waits for its response.
+    pub async fn stack_trace(
+        &self,
+        thread_id: i64,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        self.stack_trace_page(thread_id, 0, 1).await
+    }
+
+    // stack_trace_page: fetches `levels` frames of a thread starting at `start_frame`.
+    pub async fn stack_trace_page(
+        &self,
+        thread_id: i64,
+        start_frame: i64,
+        levels: i64,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        self.request(
+            "stackTrace",
+            Some(serde_json::json!({
+                "threadId": thread_id,
+                "startFrame": start_frame,
+                "levels": levels
+            })),
+        )
+        .await
+    }
+
+    // scopes: lists the variable scopes (locals, globals, registers, ...) of a stack frame.
+    pub async fn scopes(&self, frame_id: i64) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        self.request(
+            "scopes",
+            Some(serde_json::json!({
+                "frameId": frame_id
+            })),
+        )
+        .await
+    }
+
+    // variables: fetches the children of a scope or structured value.
+    pub async fn variables(
+        &self,
+        variables_reference: i64,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        self.variables_page(variables_reference, None, None, None)
+            .await
+    }
+
+    // variables_page: fetches `count` children starting at `start` (all when unset), with an
+    // optional ValueFormat such as `{"hex": true}`.
+    pub async fn variables_page(
+        &self,
+        variables_reference: i64,
+        start: Option<u32>,
+        count: Option<u32>,
+        format: Option<serde_json::Value>,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        let mut args = serde_json::json!({
+            "variablesReference": variables_reference
+        });
+        if let Some(start) = start {
+            args["start"] = serde_json::json!(start);
+        }
+        if let Some(count) = count {
+            args["count"] = serde_json::json!(count);
+        }
+        if let Some(format) = format {
+            args["format"] = format;
+        }
+        self.request("variables", Some(args)).await
+    }
+
+    // threads: sends a "threads" request and waits for its response.
+    pub async fn threads(&self) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        self.request("threads", Some(serde_json::json!({}))).await
+    }
+
+    // continue_execution: resumes the program. With `single_thread`, only `thread_id` runs and
+    // the others stay suspended.
+    pub async fn continue_execution(
+        &self,
+        thread_id: i64,
+        single_thread: bool,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        let mut args = serde_json::json!({
+            "threadId": thread_id
+        });
+        if single_thread {
+            if let serde_json::Value::Object(ref mut map) = args {
+                map.insert("singleThread".to_string(), serde_json::json!(true));
+            }
+        }
+
+        self.request("continue", Some(args)).await
+    }
+
+    // goto_targets: asks where execution can jump to at `line` of `path`. Only valid when the
+    // adapter reported supportsGotoTargetsRequest.
+    pub async fn goto_targets(
+        &self,
+        path: &str,
+        line: i64,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        self.request(
+            "gotoTargets",
+            Some(serde_json::json!({
+                "source": { "path": path },
+                "line": line
+            })),
+        )
+        .await
+    }
+
+    // goto: moves `thread_id` to a target from gotoTargets. The adapter reports the new position
+    // with a "stopped" event (reason "goto").
+    pub async fn goto(
+        &self,
+        thread_id: i64,
+        target_id: i64,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        self.request(
+            "goto",
+            Some(serde_json::json!({
+                "threadId": thread_id,
+                "targetId": target_id
+            })),
+        )
+        .await
+    }
+
+    // restart_frame: rewinds execution to the start of `frame_id`, discarding the frames above
+    // it. Only valid when the adapter reported supportsRestartFrame; the new position arrives
+    // as a "stopped" event (reason "restart").
+    pub async fn restart_frame(
+        &self,
+        frame_id: i64,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        self.request(
+            "restartFrame",
+            Some(serde_json::json!({
+                "frameId": frame_id
+            })),
+        )
+        .await
+    }
+
+    // pause: asks the adapter to suspend `thread_id`. The stop itself arrives as a "stopped"
+    // event with reason "pause".
+    pub async fn pause(&self, thread_id: i64) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        self.request(
+            "pause",
+            Some(serde_json::json!({
+                "threadId": thread_id
+            })),
+        )
+        .await
+    }
+
+    pub async fn step_in(
+        &self,
+        thread_id: i64,
+        granularity: Option<&str>,
+        single_thread: bool,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        self.step_in_to(thread_id, None, granularity, single_thread)
+            .await
+    }
+
+    // Like step_in; with `target_id` (from step_in_targets) steps into that particular call of
+    // the line.
+    pub async fn step_in_to(
+        &self,
+        thread_id: i64,
+        target_id: Option<i64>,
+        granularity: Option<&str>,
+        single_thread: bool,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        let mut args = serde_json::json!({
+            "threadId": thread_id
+        });
+        if let Some(target_id) = target_id {
+            args["targetId"] = serde_json::json!(target_id);
+        }
+
+        // Add granularity if provided
+        if let Some(g) = granularity {
+            if let serde_json::Value::Object(ref mut map) = args {
+                map.insert("granularity".to_string(), serde_json::json!(g));
+            }
+        }
+        // Keep the other threads suspended while this one steps
+        if single_thread {
+            if let serde_json::Value::Object(ref mut map) = args {
+                map.insert("singleThread".to_string(), serde_json::json!(true));
+            }
+        }
+
+        self.request("stepIn", Some(args)).await
+    }
+
+    // step_in_targets: lists the calls on the current line of `frame_id` that stepIn can pick
+    // between, e.g. `g` and `f` in `f(g(x))`. Only valid when the adapter reported
+    // supportsStepInTargetsRequest.
+    pub async fn step_in_targets(
+        &self,
+        frame_id: i64,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        self.request(
+            "stepInTargets",
+            Some(serde_json::json!({
+                "frameId": frame_id
+            })),
+        )
+        .await
+    }
+
+    pub async fn next(
+        &self,
+        thread_id: i64,
+        granularity: Option<&str>,
+        single_thread: bool,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        let mut args = serde_json::json!({
+            "threadId": thread_id
+        });
+
+        // Add granularity if provided
+        if let Some(g) = granularity {
+            if let serde_json::Value::Object(ref mut map) = args {
+                map.insert("granularity".to_string(), serde_json::json!(g));
+            }
+        }
+        // Keep the other threads suspended while this one steps
+        if single_thread {
+            if let serde_json::Value::Object(ref mut map) = args {
+                map.insert("singleThread".to_string(), serde_json::json!(true));
+            }
+        }
+
+        self.request("next", Some(args)).await
+    }
+
+    pub async fn step_out(
+        &self,
+        thread_id: i64,
+        granularity: Option<&str>,
+        single_thread: bool,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        let mut args = serde_json::json!({
+            "threadId": thread_id
+        });
+
+        // Add granularity if provided
+        if let Some(g) = granularity {
+            if let serde_json::Value::Object(ref mut map) = args {
+                map.insert("granularity".to_string(), serde_json::json!(g));
+            }
+        }
+        // Keep the other threads suspended while this one steps
+        if single_thread {
+            if let serde_json::Value::Object(ref mut map) = args {
+                map.insert("singleThread".to_string(), serde_json::json!(true));
+            }
+        }
+
+        self.request("stepOut", Some(args)).await
+    }
+
+    pub async fn step_back(
+        &self,
+        thread_id: i64,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        self.request(
+            "stepBack",
+            Some(serde_json::json!({
+                "threadId": thread_id
+            })),
+        )
+        .await
+    }
+
+    pub async fn reverse_continue(
+        &self,
+        thread_id: i64,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        self.request(
+            "reverseContinue",
+            Some(serde_json::json!({
+                "threadId": thread_id
+            })),
+        )
+        .await
+    }
+
+    // disassemble: sends a "disassemble" request around `memory_reference` and waits for its response.
+    pub async fn disassemble(
+        &self,
+        memory_reference: &str,
+        instruction_offset: i64,
+        instruction_count: i64,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        self.request(
+            "disassemble",
+            Some(serde_json::json!({
+                "memoryReference": memory_reference,
+                "instructionOffset": instruction_offset,
+                "instructionCount": instruction_count,
+                "resolveSymbols": true
+            })),
+        )
+        .await
+    }
+
+    // read_memory: reads `count` bytes at `offset` from `memory_reference`. The bytes come back
+    // base64-encoded in the response's `data`.
+    pub async fn read_memory(
+        &self,
+        memory_reference: &str,
+        offset: i64,
+        count: u32,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        self.request(
+            "readMemory",
+            Some(serde_json::json!({
+                "memoryReference": memory_reference,
+                "offset": offset,
+                "count": count
+            })),
+        )
+        .await
+    }
+
+    pub async fn evaluate(
+        &self,
+        expression: &str,
+        frame_id: Option<i32>,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        // Build arguments according to DAP spec.
+        // Default context is "repl"; if a frame id is provided we override context to "hover".
+        let mut args_json = serde_json::json!({
+            "expression": expression,
+            "context": "repl"
+        });
+
+        if let Some(fid) = frame_id {
+            if let serde_json::Value::Object(ref mut map) = args_json {
+                map.insert("frameId".to_string(), serde_json::json!(fid));
+                map.insert("context".to_string(), serde_json::json!("hover"));
+            }
+        }
+
+        self.request("evaluate", Some(args_json)).await
+    }
+
+    // evaluate_in: evaluate with an explicit context (e.g. "clipboard", which asks adapters for
+    // the untruncated value) and an optional ValueFormat.
+    pub async fn evaluate_in(
+        &self,
+        expression: &str,
+        frame_id: Option<i32>,
+        context: &str,
+        format: Option<serde_json::Value>,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        let mut args = serde_json::json!({
+            "expression": expression,
+            "context": context
+        });
+        if let Some(fid) = frame_id {
+            args["frameId"] = serde_json::json!(fid);
+        }
+        if let Some(format) = format {
+            args["format"] = format;
+        }
+        self.request("evaluate", Some(args)).await
+    }
+
+    // Restarts the debuggee within the running adapter. Only valid when the adapter reported
+    // supportsRestartRequest; callers fall back to a full relaunch on failure.
+    pub async fn restart(&self) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        self.request("restart", Some(serde_json::json!({}))).await
+    }
+
+    pub async fn terminate(&self) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        self.request(
+            "terminate",
+            Some(serde_json::json!({
+                "restart": false
+            })),
+        )
+        .await
+    }
+
+    // Ends the debug session. Without `terminate_debuggee` the adapter detaches and leaves the
+    // program running.
+    pub async fn disconnect(
+        &self,
+        terminate_debuggee: bool,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        self.disconnect_with(terminate_debuggee, false).await
+    }
+
+    // Like disconnect; with `suspend_debuggee` a program left running is suspended instead, for
+    // adapters that report supportSuspendDebuggee.
+    pub async fn disconnect_with(
+        &self,
+        terminate_debuggee: bool,
+        suspend_debuggee: bool,
+    ) -> Result<DAPMessage, Box<dyn std::error::Error>> {
+        let mut args = serde_json::json!({
+            "restart": false,
+            "terminateDebuggee": terminate_debuggee
+        });
+        if suspend_debuggee {
+            args["suspendDebuggee"] = serde_json::json!(true);
+        }
+        self.request("disconnect", Some(args)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use std::sync::atomic::AtomicUsize;
+
+    #[derive(Default)]
+    struct LostCounter(AtomicUsize);
+
+    impl EventSink for LostCounter {
+        fn on_connection_lost(&self) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    // Runs a receiver over a connection that closes straight away, after `before` has had the
+    // client to itself, and counts the losses reported.
+    fn losses_on_close(before: impl FnOnce(&DAPClient)) -> usize {
+        let sink = Arc::new(LostCounter::default());
+        let mut client = DAPClient::new();
+        client.connect_streams(Cursor::new(Vec::new()), Vec::new());
+        before(&client);
+        client.start_receiver(sink.clone()).unwrap();
+        client.receiver_handle.take().unwrap().join().unwrap();
+        sink.0.load(Ordering::SeqCst)
+    }
+
+    #[test]
+    fn unexpected_close_is_reported_lost() {
+        assert_eq!(losses_on_close(|_| {}), 1);
+    }
+
+    #[test]
+    fn close_after_disconnect_is_expected() {
+        let disconnect = |client: &DAPClient| {
+            client
+                .send_message(DAPMessage::request("disconnect", None))
+                .unwrap();
+        };
+        assert_eq!(losses_on_close(disconnect), 0);
+    }
+}
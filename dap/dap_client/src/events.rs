@@ -0,0 +1,118 @@
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::message::{DAPMessage, MessageType};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoppedBody {
+    pub reason: String,
+    pub description: Option<String>,
+    pub thread_id: Option<i64>,
+    pub all_threads_stopped: Option<bool>,
+    pub hit_breakpoint_ids: Option<Vec<i64>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContinuedBody {
+    pub thread_id: i64,
+    pub all_threads_continued: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExitedBody {
+    pub exit_code: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadBody {
+    pub reason: String,
+    pub thread_id: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputBody {
+    pub category: Option<String>,
+    pub output: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakpointBody {
+    pub reason: String,
+    pub breakpoint: Value,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessBody {
+    pub name: String,
+    pub system_process_id: Option<i64>,
+    pub is_local_process: Option<bool>,
+    pub start_method: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModuleBody {
+    // "new", "changed" or "removed"
+    pub reason: String,
+    pub module: Value,
+}
+
+// An adapter event with its body parsed. Events this client doesn't model, and known events
+// whose body doesn't match the spec, come through as Other.
+#[derive(Debug, Clone)]
+pub enum DapEvent {
+    Initialized,
+    Stopped(StoppedBody),
+    Continued(ContinuedBody),
+    Exited(ExitedBody),
+    Terminated(Option<Value>),
+    Thread(ThreadBody),
+    Output(OutputBody),
+    Breakpoint(BreakpointBody),
+    Process(ProcessBody),
+    Module(ModuleBody),
+    Other { event: String, body: Option<Value> },
+}
+
+fn parse_body<T: DeserializeOwned>(body: &Option<Value>) -> Option<T> {
+    body.as_ref()
+        .and_then(|body| serde_json::from_value(body.clone()).ok())
+}
+
+impl DapEvent {
+    // Returns None for requests and responses.
+    pub fn parse(message: &DAPMessage) -> Option<DapEvent> {
+        if message.message_type != MessageType::Event {
+            return None;
+        }
+        let event = message.event.as_deref()?;
+        let body = &message.body;
+
+        let parsed = match event {
+            "initialized" => Some(DapEvent::Initialized),
+            "stopped" => parse_body(body).map(DapEvent::Stopped),
+            "continued" => parse_body(body).map(DapEvent::Continued),
+            "exited" => parse_body(body).map(DapEvent::Exited),
+            "terminated" => Some(DapEvent::Terminated(body.clone())),
+            "thread" => parse_body(body).map(DapEvent::Thread),
+            "output" => parse_body(body).map(DapEvent::Output),
+            "breakpoint" => parse_body(body).map(DapEvent::Breakpoint),
+            "process" => parse_body(body).map(DapEvent::Process),
+            "module" => parse_body(body).map(DapEvent::Module),
+            _ => None,
+        };
+
+        Some(parsed.unwrap_or_else(|| DapEvent::Other {
+            event: event.to_string(),
+            body: body.clone(),
+        }))
+    }
+}
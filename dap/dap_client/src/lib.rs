@@ -0,0 +1,19 @@
+// Debug Adapter Protocol client shared by the app and the adapter test harnesses. Framing and
+// request building live here; what happens with incoming messages is up to the EventSink each
+// consumer plugs in.
+pub mod capabilities;
+pub mod client;
+pub mod events;
+pub mod message;
+pub mod retry;
+pub mod schema;
+pub mod transport;
+
+pub use capabilities::Capabilities;
+pub use client::{
+    lock_or_recover, DAPClient, EventSink, InFlightRequest, NoopSink, RequestCanceller,
+};
+pub use events::DapEvent;
+pub use message::{BreakpointInput, Checksum, DAPMessage, FunctionBreakpointInput, MessageType};
+pub use retry::RetryPolicy;
+pub use schema::{SchemaValidator, Violation};
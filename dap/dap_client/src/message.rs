@@ -0,0 +1,233 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")] // This tells serde to use lowercase strings.
+pub enum MessageType {
+    Request,
+    Response,
+    Event,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DAPMessage {
+    pub seq: i32,
+    #[serde(rename = "type")]
+    pub message_type: MessageType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_seq: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub success: Option<bool>,
+    // Short error description set by adapters on failed responses.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<serde_json::Value>,
+}
+
+impl DAPMessage {
+    // A request with only the fields the spec defines for one. `seq` is assigned on send.
+    pub fn request(command: &str, arguments: Option<serde_json::Value>) -> Self {
+        DAPMessage {
+            seq: -1,
+            message_type: MessageType::Request,
+            command: Some(command.to_string()),
+            request_seq: None,
+            success: None,
+            message: None,
+            body: None,
+            event: None,
+            arguments,
+        }
+    }
+
+    // The response to a request the adapter sent us (a reverse request such as
+    // startDebugging): `result` is the body on success, the error message on failure.
+    pub fn response(
+        request: &DAPMessage,
+        result: Result<Option<serde_json::Value>, String>,
+    ) -> Self {
+        let (success, message, body) = match result {
+            Ok(body) => (true, None, body),
+            Err(message) => (false, Some(message), None),
+        };
+        DAPMessage {
+            seq: -1,
+            message_type: MessageType::Response,
+            command: request.command.clone(),
+            request_seq: Some(request.seq),
+            success: Some(success),
+            message,
+            body,
+            event: None,
+            arguments: None,
+        }
+    }
+}
+
+// A DAP SourceBreakpoint, plus options the client manages itself.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakpointInput {
+    pub line: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hit_condition: Option<String>,
+    // Makes this a logpoint: the message is printed instead of stopping
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_message: Option<String>,
+    // Client-managed: disable the breakpoint once it has been hit this many times. Never sent to
+    // the adapter.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disable_after_hits: Option<u32>,
+}
+
+impl BreakpointInput {
+    // The part the adapter understands, without client-managed options.
+    pub fn source_breakpoint(&self) -> BreakpointInput {
+        BreakpointInput {
+            disable_after_hits: None,
+            ..self.clone()
+        }
+    }
+}
+
+// A DAP Checksum of a source file, e.g. algorithm "SHA256" and the hex digest.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Checksum {
+    pub algorithm: String,
+    pub checksum: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FunctionBreakpointInput {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_golden(message: DAPMessage, golden: &str) {
+        let actual: serde_json::Value = serde_json::to_value(&message).unwrap();
+        let expected: serde_json::Value = serde_json::from_str(golden).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn request_without_arguments_has_no_null_fields() {
+        let mut message = DAPMessage::request("threads", None);
+        message.seq = 3;
+        assert_golden(message, r#"{"seq":3,"type":"request","command":"threads"}"#);
+    }
+
+    #[test]
+    fn request_with_arguments() {
+        let mut message = DAPMessage::request(
+            "setBreakpoints",
+            Some(serde_json::json!({
+                "source": { "path": "/tmp/a.py", "name": "a.py" },
+                "breakpoints": [BreakpointInput {
+                    line: 25,
+                    ..Default::default()
+                }],
+                "sourceModified": false
+            })),
+        );
+        message.seq = 7;
+        assert_golden(
+            message,
+            r#"{
+                "seq": 7,
+                "type": "request",
+                "command": "setBreakpoints",
+                "arguments": {
+                    "source": { "path": "/tmp/a.py", "name": "a.py" },
+                    "breakpoints": [{ "line": 25 }],
+                    "sourceModified": false
+                }
+            }"#,
+        );
+    }
+
+    #[test]
+    fn function_breakpoint_omits_missing_condition() {
+        let mut message = DAPMessage::request(
+            "setFunctionBreakpoints",
+            Some(serde_json::json!({
+                "breakpoints": [FunctionBreakpointInput {
+                    name: "rust_panic".to_string(),
+                    condition: None,
+                }]
+            })),
+        );
+        message.seq = 1;
+        assert_golden(
+            message,
+            r#"{
+                "seq": 1,
+                "type": "request",
+                "command": "setFunctionBreakpoints",
+                "arguments": { "breakpoints": [{ "name": "rust_panic" }] }
+            }"#,
+        );
+    }
+
+    #[test]
+    fn source_breakpoint_drops_client_managed_options() {
+        let breakpoint = BreakpointInput {
+            line: 8,
+            condition: Some("x > 1".to_string()),
+            disable_after_hits: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(breakpoint.source_breakpoint()).unwrap(),
+            serde_json::json!({ "line": 8, "condition": "x > 1" })
+        );
+    }
+
+    #[test]
+    fn reverse_request_responses() {
+        let request: DAPMessage = serde_json::from_str(
+            r#"{"seq":9,"type":"request","command":"startDebugging","arguments":{"request":"attach","configuration":{}}}"#,
+        )
+        .unwrap();
+
+        let mut accepted = DAPMessage::response(&request, Ok(None));
+        accepted.seq = 4;
+        assert_golden(
+            accepted,
+            r#"{"seq":4,"type":"response","request_seq":9,"success":true,"command":"startDebugging"}"#,
+        );
+
+        let mut refused = DAPMessage::response(&request, Err("not supported".to_string()));
+        refused.seq = 5;
+        assert_golden(
+            refused,
+            r#"{
+                "seq": 5,
+                "type": "response",
+                "request_seq": 9,
+                "success": false,
+                "command": "startDebugging",
+                "message": "not supported"
+            }"#,
+        );
+    }
+
+    #[test]
+    fn response_round_trips_without_adding_fields() {
+        let raw = r#"{"seq":12,"type":"response","request_seq":4,"success":true,"command":"continue","body":{"allThreadsContinued":true}}"#;
+        let message: DAPMessage = serde_json::from_str(raw).unwrap();
+        assert_golden(message, raw);
+    }
+}
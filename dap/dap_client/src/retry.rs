@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// How often and how patiently to retry connecting to an adapter that may still be starting up.
+// The delay doubles after every failed attempt, up to `max_delay_ms`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetryPolicy {
+    // Attempts in total, including the first
+    pub attempts: u32,
+    pub initial_delay_ms: u64,
+    pub max_delay_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            attempts: 6,
+            initial_delay_ms: 100,
+            max_delay_ms: 2000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    // The wait after failed attempt number `attempt` (counting from 1).
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let doublings = attempt.saturating_sub(1).min(32);
+        let delay = self.initial_delay_ms.saturating_mul(1u64 << doublings);
+        Duration::from_millis(delay.min(self.max_delay_ms))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_doubles_up_to_the_cap() {
+        let policy = RetryPolicy {
+            attempts: 10,
+            initial_delay_ms: 100,
+            max_delay_ms: 1000,
+        };
+        let delays: Vec<u64> = (1..=6)
+            .map(|attempt| policy.delay(attempt).as_millis() as u64)
+            .collect();
+        assert_eq!(delays, vec![100, 200, 400, 800, 1000, 1000]);
+    }
+
+    #[test]
+    fn delay_does_not_overflow() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay(u32::MAX), Duration::from_millis(2000));
+    }
+
+    #[test]
+    fn missing_fields_take_defaults() {
+        let policy: RetryPolicy = serde_json::from_str(r#"{"attempts": 3}"#).unwrap();
+        assert_eq!(
+            policy,
+            RetryPolicy {
+                attempts: 3,
+                ..RetryPolicy::default()
+            }
+        );
+    }
+}
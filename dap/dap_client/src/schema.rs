@@ -0,0 +1,277 @@
+use serde_json::{Map, Value};
+use std::sync::OnceLock;
+
+// The requests, responses, events and types wayfind exchanges with adapters, from the Debug
+// Adapter Protocol JSON schema.
+const BUNDLED_SCHEMA: &str = include_str!("../schema/debugAdapterProtocol.json");
+
+// One way a message breaks the schema. `path` points at the offending field, e.g.
+// `body.stackFrames[2].line`; it's empty for the message itself.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub path: String,
+    pub message: String,
+}
+
+// Validates messages against the DAP schema. Only the keywords that schema uses are supported:
+// $ref, allOf, type, enum, properties, required, items and additionalProperties.
+pub struct SchemaValidator {
+    definitions: Map<String, Value>,
+}
+
+fn capitalize(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn type_matches(value: &Value, name: &str) -> bool {
+    match name {
+        "array" => value.is_array(),
+        "boolean" => value.is_boolean(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "object" => value.is_object(),
+        "string" => value.is_string(),
+        _ => true,
+    }
+}
+
+fn violation(out: &mut Vec<Violation>, path: &str, message: String) {
+    out.push(Violation {
+        path: path.to_string(),
+        message,
+    });
+}
+
+fn join(path: &str, field: &str) -> String {
+    if path.is_empty() {
+        field.to_string()
+    } else {
+        format!("{}.{}", path, field)
+    }
+}
+
+impl SchemaValidator {
+    pub fn new(schema: &Value) -> Self {
+        let definitions = schema
+            .get("definitions")
+            .and_then(|d| d.as_object())
+            .cloned()
+            .unwrap_or_default();
+        SchemaValidator { definitions }
+    }
+
+    // The validator for the bundled schema, parsed on first use.
+    pub fn bundled() -> &'static SchemaValidator {
+        static BUNDLED: OnceLock<SchemaValidator> = OnceLock::new();
+        BUNDLED.get_or_init(|| {
+            let schema = serde_json::from_str(BUNDLED_SCHEMA).expect("bundled DAP schema is valid");
+            SchemaValidator::new(&schema)
+        })
+    }
+
+    // The definition a message is checked against: e.g. StackTraceRequest for a stackTrace
+    // request, ErrorResponse for a failed response. Messages the schema has no specific
+    // definition for fall back to the base Request, Response or Event.
+    pub fn definition_for(&self, message: &Value) -> String {
+        let field = |name| message.get(name).and_then(|v| v.as_str()).unwrap_or("");
+        let (specific, base) = match field("type") {
+            "request" => (
+                format!("{}Request", capitalize(field("command"))),
+                "Request",
+            ),
+            "event" => (format!("{}Event", capitalize(field("event"))), "Event"),
+            "response" if message.get("success") == Some(&Value::Bool(false)) => {
+                return "ErrorResponse".to_string();
+            }
+            "response" => (
+                format!("{}Response", capitalize(field("command"))),
+                "Response",
+            ),
+            _ => return "ProtocolMessage".to_string(),
+        };
+        if self.definitions.contains_key(&specific) {
+            specific
+        } else {
+            base.to_string()
+        }
+    }
+
+    pub fn validate(&self, message: &Value) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        if let Some(schema) = self.definitions.get(&self.definition_for(message)) {
+            self.check(message, schema, String::new(), &mut violations);
+        }
+        violations
+    }
+
+    fn check(&self, value: &Value, schema: &Value, path: String, out: &mut Vec<Violation>) {
+        if let Some(reference) = schema.get("$ref").and_then(|r| r.as_str()) {
+            let name = reference.trim_start_matches("#/definitions/");
+            match self.definitions.get(name) {
+                Some(definition) => self.check(value, definition, path, out),
+                None => violation(
+                    out,
+                    &path,
+                    format!("unknown schema reference {}", reference),
+                ),
+            }
+            return;
+        }
+
+        if let Some(all_of) = schema.get("allOf").and_then(|a| a.as_array()) {
+            for part in all_of {
+                self.check(value, part, path.clone(), out);
+            }
+        }
+
+        match schema.get("type") {
+            Some(Value::String(name)) if !type_matches(value, name) => {
+                violation(out, &path, format!("expected {}, got {}", name, value));
+                return;
+            }
+            Some(Value::Array(names))
+                if !names
+                    .iter()
+                    .filter_map(|n| n.as_str())
+                    .any(|n| type_matches(value, n)) =>
+            {
+                violation(out, &path, format!("unexpected type of {}", value));
+                return;
+            }
+            _ => {}
+        }
+
+        if let Some(allowed) = schema.get("enum").and_then(|e| e.as_array()) {
+            if !allowed.contains(value) {
+                violation(
+                    out,
+                    &path,
+                    format!("{} is not one of {}", value, Value::from(allowed.clone())),
+                );
+            }
+        }
+
+        if let Some(object) = value.as_object() {
+            if let Some(required) = schema.get("required").and_then(|r| r.as_array()) {
+                for field in required.iter().filter_map(|f| f.as_str()) {
+                    if !object.contains_key(field) {
+                        violation(
+                            out,
+                            &join(&path, field),
+                            "missing required field".to_string(),
+                        );
+                    }
+                }
+            }
+            let properties = schema.get("properties").and_then(|p| p.as_object());
+            for (field, field_value) in object {
+                match properties.and_then(|p| p.get(field)) {
+                    Some(field_schema) => {
+                        self.check(field_value, field_schema, join(&path, field), out)
+                    }
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => {
+                            violation(out, &join(&path, field), "unexpected field".to_string())
+                        }
+                        Some(extra) if extra.is_object() => {
+                            self.check(field_value, extra, join(&path, field), out)
+                        }
+                        _ => {}
+                    },
+                }
+            }
+        }
+
+        if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+            for (index, item) in array.iter().enumerate() {
+                self.check(item, items, format!("{}[{}]", path, index), out);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn valid_request_has_no_violations() {
+        let message = json!({
+            "seq": 3,
+            "type": "request",
+            "command": "setBreakpoints",
+            "arguments": {
+                "source": { "path": "/tmp/main.py" },
+                "breakpoints": [{ "line": 4 }, { "line": 9, "condition": "x > 1" }],
+                "sourceModified": false
+            }
+        });
+        assert_eq!(SchemaValidator::bundled().validate(&message), vec![]);
+    }
+
+    #[test]
+    fn violations_point_at_the_field() {
+        let message = json!({
+            "seq": 7,
+            "type": "response",
+            "request_seq": 6,
+            "success": true,
+            "command": "stackTrace",
+            "body": {
+                "stackFrames": [
+                    { "id": 1, "name": "main", "line": 3, "column": 1 },
+                    { "id": 2, "name": "run", "line": "12", "column": 1 }
+                ]
+            }
+        });
+        assert_eq!(
+            SchemaValidator::bundled().validate(&message),
+            vec![Violation {
+                path: "body.stackFrames[1].line".to_string(),
+                message: "expected integer, got \"12\"".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn missing_required_fields_are_reported() {
+        let message = json!({
+            "seq": 1,
+            "type": "event",
+            "event": "stopped",
+            "body": { "threadId": 1 }
+        });
+        assert_eq!(
+            SchemaValidator::bundled().validate(&message),
+            vec![Violation {
+                path: "body.reason".to_string(),
+                message: "missing required field".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn unknown_commands_fall_back_to_the_base_definition() {
+        let validator = SchemaValidator::bundled();
+        let request = json!({ "seq": 1, "type": "request", "command": "readMemory" });
+        assert_eq!(validator.definition_for(&request), "Request");
+        assert_eq!(validator.validate(&request), vec![]);
+
+        let failed = json!({
+            "seq": 2,
+            "type": "response",
+            "request_seq": 1,
+            "success": false,
+            "command": "stackTrace",
+            "message": "notStopped"
+        });
+        assert_eq!(validator.definition_for(&failed), "ErrorResponse");
+        assert_eq!(validator.validate(&failed), vec![]);
+    }
+}
@@ -0,0 +1,136 @@
+use std::io::{BufRead, ErrorKind, Write};
+
+use crate::message::DAPMessage;
+
+// Writes one message with its Content-Length header and flushes.
+pub fn write_message<W: Write + ?Sized>(
+    writer: &mut W,
+    message: &DAPMessage,
+) -> std::io::Result<()> {
+    let json = serde_json::to_string(message)?;
+    let header = format!("Content-Length: {}\r\n\r\n", json.len());
+
+    tracing::debug!(
+        seq = message.seq,
+        command = message.command.as_deref(),
+        "--> sending"
+    );
+    tracing::trace!(seq = message.seq, payload = %json, "--> payload");
+
+    writer.write_all(header.as_bytes())?;
+    writer.write_all(json.as_bytes())?;
+    writer.flush()
+}
+
+// Headers of one framed message. Keys are lowercased; values are trimmed.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Headers {
+    fields: Vec<(String, String)>,
+}
+
+impl Headers {
+    pub fn get(&self, name: &str) -> Option<&str> {
+        let name = name.to_ascii_lowercase();
+        self.fields
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn content_length(&self) -> Option<usize> {
+        self.get("content-length")?.parse().ok()
+    }
+}
+
+// Reads header lines up to the blank separator line. Accepts CRLF or bare LF line endings, any
+// key casing, optional whitespace around values, and headers other than Content-Length (e.g.
+// Content-Type). Returns Ok(None) if the stream ends before any header byte.
+pub fn read_headers<R: BufRead + ?Sized>(reader: &mut R) -> std::io::Result<Option<Headers>> {
+    let mut headers = Headers::default();
+    let mut started = false;
+    loop {
+        let mut line = Vec::new();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return if started {
+                Err(std::io::Error::new(
+                    ErrorKind::UnexpectedEof,
+                    "Stream ended inside message headers",
+                ))
+            } else {
+                Ok(None)
+            };
+        }
+        let line = String::from_utf8_lossy(&line);
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.is_empty() {
+            // Tolerate stray blank lines between messages
+            if started {
+                return Ok(Some(headers));
+            }
+            continue;
+        }
+        started = true;
+        match line.split_once(':') {
+            Some((key, value)) => headers
+                .fields
+                .push((key.trim().to_ascii_lowercase(), value.trim().to_string())),
+            None => tracing::warn!("Ignoring malformed header line: {}", line),
+        }
+    }
+}
+
+// Reads the next framed message body. Returns Ok(None) once the adapter closes the stream.
+pub fn read_message<R: BufRead + ?Sized>(reader: &mut R) -> std::io::Result<Option<String>> {
+    let headers = match read_headers(reader)? {
+        Some(headers) => headers,
+        // Connection closed.
+        None => return Ok(None),
+    };
+
+    let content_length = headers.content_length().ok_or_else(|| {
+        std::io::Error::new(
+            ErrorKind::InvalidData,
+            format!("No Content-Length found in headers: {:?}", headers),
+        )
+    })?;
+
+    let mut body_bytes = vec![0; content_length];
+    reader.read_exact(&mut body_bytes)?;
+    String::from_utf8(body_bytes)
+        .map(Some)
+        .map_err(|e| std::io::Error::new(ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn read_all(raw: &str) -> Vec<String> {
+        let mut reader = Cursor::new(raw.as_bytes().to_vec());
+        let mut messages = Vec::new();
+        while let Some(message) = read_message(&mut reader).unwrap() {
+            messages.push(message);
+        }
+        messages
+    }
+
+    #[test]
+    fn reads_standard_framing() {
+        assert_eq!(read_all("Content-Length: 2\r\n\r\n{}"), vec!["{}"]);
+    }
+
+    #[test]
+    fn accepts_extra_headers_casing_and_lf() {
+        let raw = "content-type: application/vscode-jsonrpc; charset=utf-8\nCONTENT-LENGTH:2\n\n{}\
+                   Content-Length:  4 \r\n\r\n[1 ]";
+        assert_eq!(read_all(raw), vec!["{}", "[1 ]"]);
+    }
+
+    #[test]
+    fn missing_content_length_is_invalid_data() {
+        let mut reader = Cursor::new(b"Content-Type: x\r\n\r\n{}".to_vec());
+        let error = read_message(&mut reader).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+}
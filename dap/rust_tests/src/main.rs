@@ -1,8 +1,7 @@
-mod dap_client;
-
-use crate::dap_client::DAPClient;
+use dap_client::{BreakpointInput, DAPClient, NoopSink};
 use std::path::Path;
 use std::process::{Command, Stdio};
+use std::sync::Arc;
 use std::time::Duration;
 
 #[tokio::main]
@@ -17,8 +16,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let script_path_str = script_path.to_str().unwrap();
 
     // Start capturing output from the target script
-    let child = Command::new("python")
-        .args(&[
+    let mut child = Command::new("python")
+        .args([
             "-m",
             "debugpy",
             "--listen",
@@ -33,6 +32,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     println!("Launched Python process with PID: {}", child.id());
 
+    let result = run_test(debugpy_port, script_path_str).await;
+
+    // Reap debugpy so the script doesn't outlive the test
+    let _ = child.kill();
+    let _ = child.wait();
+    result
+}
+
+async fn run_test(
+    debugpy_port: u16,
+    script_path_str: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
     // Give debugpy a moment to start up
     tokio::time::sleep(Duration::from_secs(1)).await;
 
@@ -41,7 +52,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     client
         .connect("127.0.0.1", debugpy_port)
         .expect("Failed to connect");
-    client.start_receiver();
+    client
+        .start_receiver(Arc::new(NoopSink))
+        .expect("Failed to start receiver");
     println!("Connected to debugpy.");
 
     // Step 3: Send initialize request
@@ -51,10 +64,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Step 4: Send attach request
     client
         .attach("127.0.0.1", debugpy_port)
+        .await
         .expect("Failed to send attach");
 
     // Wait for the 'initialized' event
-    if let Some(_) = client.wait_for_event("initialized", 10.0) {
+    if client.wait_for_event("initialized", 10.0).is_some() {
         println!("Initialization complete");
     } else {
         println!("Timed out waiting for 'initialized' event");
@@ -63,7 +77,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let breakpoint_line = 25;
     let bp_response = client
-        .set_breakpoints(script_path_str, &[breakpoint_line])
+        .set_breakpoints(
+            script_path_str.to_string(),
+            vec![BreakpointInput {
+                line: breakpoint_line,
+                ..Default::default()
+            }],
+        )
         .await
         .expect("Failed to set breakpoint");
     println!("Breakpoints response: {:?}", bp_response);
@@ -88,7 +108,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         // Get the thread ID from the stopped event
         let thread_id = if let Some(body) = &stopped.body {
             if let Some(tid) = body.get("threadId") {
-                tid.as_i64().unwrap_or(1) as i32
+                tid.as_i64().unwrap_or(1)
             } else {
                 1
             }
@@ -107,7 +127,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             .as_ref()
             .and_then(|b| b.get("stackFrames"))
             .and_then(|frames| frames.as_array())
-            .and_then(|frames| frames.get(0))
+            .and_then(|frames| frames.first())
             .and_then(|frame| frame.get("id"))
             .and_then(|id| id.as_i64())
             .map(|id| id as i32);
@@ -125,7 +145,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("Value of fib_series at breakpoint: {:?}", result_value);
 
         // Step 9: Continue execution
-        let continue_response = client.continue_execution(thread_id).await?;
+        let continue_response = client.continue_execution(thread_id, false).await?;
         println!("Continue response: {:?}", continue_response);
 
         // Loop to send continue requests if more stopped events are received
@@ -134,7 +154,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
             let extra_thread_id = if let Some(body) = &extra_stopped.body {
                 if let Some(tid) = body.get("threadId") {
-                    tid.as_i64().unwrap_or(1) as i32
+                    tid.as_i64().unwrap_or(1)
                 } else {
                     thread_id
                 }
@@ -142,7 +162,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 thread_id
             };
 
-            let extra_continue = client.continue_execution(extra_thread_id).await?;
+            let extra_continue = client.continue_execution(extra_thread_id, false).await?;
             println!("Extra continue response: {:?}", extra_continue);
         }
     } else {